@@ -0,0 +1,11 @@
+-- Creating table for sessions (one row per issued access token, keyed by the token's jti)
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Creating indexes for better query performance
+CREATE INDEX idx_sessions_user_id ON sessions(user_id);
\ No newline at end of file
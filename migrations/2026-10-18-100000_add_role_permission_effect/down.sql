@@ -0,0 +1 @@
+ALTER TABLE role_permissions DROP COLUMN IF EXISTS effect;
\ No newline at end of file
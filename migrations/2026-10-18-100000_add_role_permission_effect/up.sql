@@ -0,0 +1,4 @@
+-- Adding grant effect to role_permissions; deny grants override any matching allow grant
+ALTER TABLE role_permissions
+    ADD COLUMN effect VARCHAR(5) NOT NULL DEFAULT 'allow'
+    CONSTRAINT role_permissions_effect_check CHECK (effect IN ('allow', 'deny'));
\ No newline at end of file
@@ -0,0 +1,15 @@
+-- Creating table for resource_grants (role assignments limited to a single resource or, with resource_id '*', every resource of a type)
+CREATE TABLE resource_grants (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    resource_type VARCHAR(50) NOT NULL,
+    resource_id VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, role_id, resource_type, resource_id)
+);
+
+-- Creating indexes for better query performance
+CREATE INDEX idx_resource_grants_user_id ON resource_grants(user_id);
+CREATE INDEX idx_resource_grants_role_id ON resource_grants(role_id);
+CREATE INDEX idx_resource_grants_resource ON resource_grants(resource_type, resource_id);
\ No newline at end of file
@@ -0,0 +1,17 @@
+-- Creating table for relation_tuples (Zanzibar-style `namespace:object_id#relation@subject` tuples).
+-- The subject is either a user (`user:<id>`, empty subject_relation) or a userset such as `group:eng#member`.
+CREATE TABLE relation_tuples (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    namespace VARCHAR(50) NOT NULL,
+    object_id VARCHAR(255) NOT NULL,
+    relation VARCHAR(50) NOT NULL,
+    subject_namespace VARCHAR(50) NOT NULL,
+    subject_id VARCHAR(255) NOT NULL,
+    subject_relation VARCHAR(50) NOT NULL DEFAULT '',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (namespace, object_id, relation, subject_namespace, subject_id, subject_relation)
+);
+
+-- Creating indexes for better query performance
+CREATE INDEX idx_relation_tuples_subject ON relation_tuples(subject_namespace, subject_id, subject_relation);
+CREATE INDEX idx_relation_tuples_namespace_relation ON relation_tuples(namespace, relation);
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE users DROP COLUMN attributes;
+ALTER TABLE role_permissions DROP COLUMN conditions;
\ No newline at end of file
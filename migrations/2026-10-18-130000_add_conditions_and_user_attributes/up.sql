@@ -0,0 +1,5 @@
+-- Adding optional attribute-based conditions to role_permissions (NULL means unconditional)
+ALTER TABLE role_permissions ADD COLUMN conditions JSONB;
+
+-- Adding free-form attributes (e.g. department) to users, matched by conditions
+ALTER TABLE users ADD COLUMN attributes JSONB NOT NULL DEFAULT '{}';
\ No newline at end of file
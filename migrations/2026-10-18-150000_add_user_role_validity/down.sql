@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS expired_user_roles;
+DROP INDEX IF EXISTS idx_user_roles_valid_until;
+ALTER TABLE user_roles DROP CONSTRAINT user_roles_validity_check;
+ALTER TABLE user_roles DROP COLUMN valid_until;
+ALTER TABLE user_roles DROP COLUMN valid_from;
\ No newline at end of file
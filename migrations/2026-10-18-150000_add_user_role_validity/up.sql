@@ -0,0 +1,19 @@
+-- Adding an optional validity window to user_roles (NULL bounds are open)
+ALTER TABLE user_roles ADD COLUMN valid_from TIMESTAMP WITH TIME ZONE;
+ALTER TABLE user_roles ADD COLUMN valid_until TIMESTAMP WITH TIME ZONE;
+ALTER TABLE user_roles ADD CONSTRAINT user_roles_validity_check CHECK (valid_until IS NULL OR valid_from IS NULL OR valid_until > valid_from);
+
+CREATE INDEX idx_user_roles_valid_until ON user_roles(valid_until) WHERE valid_until IS NOT NULL;
+
+-- Creating table for expired_user_roles (assignments moved here by the expiry sweeper)
+CREATE TABLE expired_user_roles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    role_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    valid_from TIMESTAMP WITH TIME ZONE,
+    valid_until TIMESTAMP WITH TIME ZONE NOT NULL,
+    expired_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_expired_user_roles_user_id ON expired_user_roles(user_id);
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS elevation_request_events;
+DROP TABLE IF EXISTS elevation_requests;
\ No newline at end of file
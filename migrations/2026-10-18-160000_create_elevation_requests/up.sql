@@ -0,0 +1,30 @@
+-- Creating table for elevation_requests (temporary role assignments granted on approval)
+CREATE TABLE elevation_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    requester_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    justification TEXT NOT NULL,
+    duration_seconds INTEGER NOT NULL CHECK (duration_seconds > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'denied', 'cancelled', 'expired')),
+    approver_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    decision_comment TEXT,
+    decided_at TIMESTAMP WITH TIME ZONE,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Creating table for elevation_request_events (audit trail of every state change)
+CREATE TABLE elevation_request_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    elevation_request_id UUID NOT NULL REFERENCES elevation_requests(id) ON DELETE CASCADE,
+    action VARCHAR(20) NOT NULL,
+    actor_id UUID,
+    comment TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Creating indexes for better query performance
+CREATE UNIQUE INDEX idx_elevation_requests_pending ON elevation_requests(requester_id, role_id) WHERE status = 'pending';
+CREATE INDEX idx_elevation_requests_status ON elevation_requests(status);
+CREATE INDEX idx_elevation_requests_expires_at ON elevation_requests(expires_at) WHERE status = 'approved';
+CREATE INDEX idx_elevation_request_events_request_id ON elevation_request_events(elevation_request_id);
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS sod_constraint_roles;
+DROP TABLE IF EXISTS sod_constraints;
\ No newline at end of file
@@ -0,0 +1,17 @@
+-- Creating table for sod_constraints (sets of mutually exclusive roles; nobody may hold two roles of a set)
+CREATE TABLE sod_constraints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Creating table for sod_constraint_roles (the roles of each set)
+CREATE TABLE sod_constraint_roles (
+    constraint_id UUID NOT NULL REFERENCES sod_constraints(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    PRIMARY KEY (constraint_id, role_id)
+);
+
+-- Creating indexes for better query performance
+CREATE INDEX idx_sod_constraint_roles_role_id ON sod_constraint_roles(role_id);
\ No newline at end of file
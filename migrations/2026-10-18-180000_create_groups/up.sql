@@ -0,0 +1,38 @@
+-- Creating table for groups (teams of users that roles can be assigned to)
+CREATE TABLE groups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Creating table for group_users (direct user membership)
+CREATE TABLE group_users (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (group_id, user_id)
+);
+
+-- Creating table for group_subgroups (members of the child group are members of the parent)
+CREATE TABLE group_subgroups (
+    parent_group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    child_group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (parent_group_id, child_group_id),
+    CHECK (parent_group_id <> child_group_id)
+);
+
+-- Creating table for group_roles (roles held by every member of a group)
+CREATE TABLE group_roles (
+    group_id UUID NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (group_id, role_id)
+);
+
+-- Creating indexes for better query performance
+CREATE INDEX idx_group_users_user_id ON group_users(user_id);
+CREATE INDEX idx_group_subgroups_child_group_id ON group_subgroups(child_group_id);
+CREATE INDEX idx_group_roles_role_id ON group_roles(role_id);
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE permissions DROP COLUMN system;
+ALTER TABLE roles DROP COLUMN system;
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- Adding a system flag to roles and permissions; system rows cannot be deleted or renamed
+ALTER TABLE roles ADD COLUMN system BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE permissions ADD COLUMN system BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
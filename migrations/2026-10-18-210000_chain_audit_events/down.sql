@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS audit_checkpoints;
+ALTER TABLE audit_events DROP CONSTRAINT IF EXISTS audit_events_seq_key;
+ALTER TABLE audit_events
+    DROP COLUMN IF EXISTS hash,
+    DROP COLUMN IF EXISTS prev_hash,
+    DROP COLUMN IF EXISTS seq;
\ No newline at end of file
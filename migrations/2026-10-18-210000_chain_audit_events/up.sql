@@ -0,0 +1,36 @@
+-- Chaining audit_events: every event stores the hash of its predecessor and its own hash
+ALTER TABLE audit_events
+    ADD COLUMN seq BIGINT,
+    ADD COLUMN prev_hash VARCHAR(64),
+    ADD COLUMN hash VARCHAR(64);
+
+-- Numbering the events recorded so far; they stay unchained (hash is NULL)
+ALTER TABLE audit_events DISABLE TRIGGER audit_events_append_only;
+UPDATE audit_events e SET seq = numbered.seq
+FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY created_at, id) AS seq FROM audit_events) numbered
+WHERE e.id = numbered.id;
+ALTER TABLE audit_events ENABLE TRIGGER audit_events_append_only;
+
+ALTER TABLE audit_events ALTER COLUMN seq SET NOT NULL;
+ALTER TABLE audit_events ADD CONSTRAINT audit_events_seq_key UNIQUE (seq);
+
+-- Creating table for audit_checkpoints (signed hashes of the chain up to an event)
+CREATE TABLE audit_checkpoints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    seq BIGINT NOT NULL UNIQUE,
+    hash VARCHAR(64) NOT NULL,
+    signature VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Naming the table in the error now that checkpoints share the trigger function
+CREATE OR REPLACE FUNCTION audit_events_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION '% is append-only', TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_checkpoints_append_only BEFORE UPDATE OR DELETE ON audit_checkpoints
+    FOR EACH ROW EXECUTE FUNCTION audit_events_append_only();
+CREATE TRIGGER audit_checkpoints_no_truncate BEFORE TRUNCATE ON audit_checkpoints
+    FOR EACH STATEMENT EXECUTE FUNCTION audit_events_append_only();
\ No newline at end of file
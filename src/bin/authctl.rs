@@ -0,0 +1,259 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use chrono::{DateTime, Utc};
+use rust_authen_service::config::Config;
+use rust_authen_service::database::{DatabasePool, PgPool};
+use rust_authen_service::handlers::audit::AuditHandler;
+use rust_authen_service::handlers::authorization::AuthorizationHandler;
+use rust_authen_service::handlers::bundle::BundleHandler;
+use rust_authen_service::handlers::role::RoleHandler;
+use rust_authen_service::handlers::user::UserHandler;
+use rust_authen_service::handlers::user_role::UserRoleHandler;
+use rust_authen_service::models::audit::AuditContext;
+use rust_authen_service::models::bundle::BundleFormat;
+use rust_authen_service::models::user::UserResponse;
+use rust_authen_service::models::user_role::UserRoleResponse;
+use rust_authen_service::utilities::encryption::Encryption;
+use rust_authen_service::utilities::error::AppError;
+use serde::Serialize;
+
+const GENERATED_PASSWORD_LENGTH: usize = 24;
+
+// Break-glass tooling that talks to the database directly, bypassing the API and its permission
+// checks. Errors are reported on stderr; set AUTHCTL_LOG (e.g. `info`) to see the service logs.
+#[derive(Parser)]
+#[command(name = "authctl", about = "Administrative tooling for rust-authen-service")]
+struct Cli {
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Create users and reset passwords
+  User {
+    #[command(subcommand)]
+    command: UserCommand,
+  },
+  /// Assign and revoke roles
+  Role {
+    #[command(subcommand)]
+    command: RoleCommand,
+  },
+  /// Print the effective permissions of a user
+  Permissions { username: String },
+  /// Apply pending database migrations
+  Migrate {
+    /// Only list the pending migrations
+    #[arg(long)]
+    dry_run: bool,
+  },
+  /// Export roles, permissions and optionally assignments as a bundle
+  Export {
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    #[arg(long)]
+    include_user_roles: bool,
+    /// Write to this file instead of stdout
+    #[arg(long, short)]
+    output: Option<String>,
+  },
+  /// Import a bundle, printing the changes it makes
+  Import {
+    file: String,
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    #[arg(long)]
+    dry_run: bool,
+    /// Delete roles, permissions and assignments missing from the bundle
+    #[arg(long)]
+    prune: bool,
+  },
+  /// Verify and checkpoint the audit log
+  Audit {
+    #[command(subcommand)]
+    command: AuditCommand,
+  },
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+  /// Create a user; a one-time password is generated unless --password is given
+  Create {
+    username: String,
+    #[arg(long)]
+    email: String,
+    #[arg(long)]
+    password: Option<String>,
+  },
+  /// Set a new password; a one-time password is generated unless --password is given
+  ResetPassword {
+    username: String,
+    #[arg(long)]
+    password: Option<String>,
+  },
+}
+
+#[derive(Subcommand)]
+enum RoleCommand {
+  /// Assign a role to a user
+  Assign {
+    username: String,
+    role: String,
+    #[arg(long)]
+    valid_from: Option<DateTime<Utc>>,
+    #[arg(long)]
+    valid_until: Option<DateTime<Utc>>,
+  },
+  /// Revoke a role from a user
+  Revoke { username: String, role: String },
+}
+
+#[derive(Subcommand)]
+enum AuditCommand {
+  /// Walk the hash chain and report the first broken link; exits with 3 when it is broken
+  Verify,
+  /// Sign the head of the chain now instead of waiting for the next periodic checkpoint
+  Checkpoint,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+  Json,
+  Yaml,
+}
+
+impl From<Format> for BundleFormat {
+  fn from(format: Format) -> Self {
+    match format {
+      Format::Json => BundleFormat::Json,
+      Format::Yaml => BundleFormat::Yaml,
+    }
+  }
+}
+
+fn main() {
+  dotenvy::dotenv().ok();
+  env_logger::Builder::from_env(env_logger::Env::new().filter_or("AUTHCTL_LOG", "off")).init();
+  let cli = Cli::parse();
+  let config = Config::load().unwrap_or_else(|e| {
+    eprintln!("authctl: invalid configuration: {}", e);
+    std::process::exit(2);
+  });
+  let pool = DatabasePool::new(&config.database.url);
+  if let Err(e) = run(cli.command, &pool, &config) {
+    eprintln!("authctl: {}", e);
+    std::process::exit(1);
+  }
+}
+
+fn run(command: Command, pool: &PgPool, config: &Config) -> Result<(), AppError> {
+  let context = audit_context();
+  match command {
+    Command::User { command: UserCommand::Create { username, email, password } } => {
+      let (password, generated) = password_or_generated(password)?;
+      let user = UserHandler::new(pool).create(&username, &email, &password, &context)?;
+      print_json(&UserResponse::from(user))?;
+      if generated {
+        eprintln!("One-time password for '{}': {}", username, password);
+      }
+    }
+    Command::User { command: UserCommand::ResetPassword { username, password } } => {
+      let handler = UserHandler::new(pool);
+      let user = handler.find_by_username(&username)?;
+      let (password, generated) = password_or_generated(password)?;
+      handler.update(user.id, None, None, Some(&password), &context)?;
+      if generated {
+        eprintln!("One-time password for '{}': {}", username, password);
+      } else {
+        eprintln!("Password updated for '{}'", username);
+      }
+    }
+    Command::Role { command: RoleCommand::Assign { username, role, valid_from, valid_until } } => {
+      let user = UserHandler::new(pool).find_by_username(&username)?;
+      let role = RoleHandler::new(pool).find_by_name(&role)?;
+      let user_role = UserRoleHandler::new(pool).create(user.id, role.id, valid_from, valid_until, &context)?;
+      print_json(&UserRoleResponse::from(user_role))?;
+    }
+    Command::Role { command: RoleCommand::Revoke { username, role } } => {
+      let user = UserHandler::new(pool).find_by_username(&username)?;
+      let role = RoleHandler::new(pool).find_by_name(&role)?;
+      UserRoleHandler::new(pool).delete(user.id, role.id, &context)?;
+      eprintln!("Revoked role '{}' from '{}'", role.name, username);
+    }
+    Command::Permissions { username } => {
+      let user = UserHandler::new(pool).find_by_username(&username)?;
+      let effective = AuthorizationHandler::new(pool, config.auth.clone()).effective_permissions(user.id)?;
+      print_json(&effective)?;
+    }
+    Command::Migrate { dry_run: true } => {
+      for version in DatabasePool::pending_migrations(pool)? {
+        println!("{}", version);
+      }
+    }
+    Command::Migrate { dry_run: false } => {
+      let applied = DatabasePool::run_pending_migrations(pool)?;
+      for version in &applied {
+        println!("{}", version);
+      }
+      eprintln!("Applied {} migrations", applied.len());
+    }
+    Command::Export { format, include_user_roles, output } => {
+      let format = format.map(BundleFormat::from).or_else(|| output.as_deref().and_then(format_from_path)).unwrap_or(BundleFormat::Yaml);
+      let bundle = BundleHandler::new(pool).export(include_user_roles)?;
+      let body = format.serialize(&bundle)?;
+      match output {
+        Some(path) => std::fs::write(&path, body).map_err(|e| AppError::BadRequest(format!("Failed to write {}: {}", path, e)))?,
+        None => print!("{}", body),
+      }
+    }
+    Command::Import { file, format, dry_run, prune } => {
+      let format = format.map(BundleFormat::from).or_else(|| format_from_path(&file)).unwrap_or(BundleFormat::Yaml);
+      let body = std::fs::read_to_string(&file).map_err(|e| AppError::BadRequest(format!("Failed to read {}: {}", file, e)))?;
+      let bundle = format.deserialize(&body)?;
+      let report = BundleHandler::new(pool).import(&bundle, prune, dry_run, &context)?;
+      print_json(&report)?;
+    }
+    Command::Audit { command: AuditCommand::Verify } => {
+      let report = AuditHandler::new(pool).verify(config.audit.checkpoint_secret.as_deref())?;
+      print_json(&report)?;
+      if !report.valid {
+        std::process::exit(3);
+      }
+    }
+    Command::Audit { command: AuditCommand::Checkpoint } => {
+      let secret = config.audit.checkpoint_secret.as_deref().ok_or_else(|| AppError::BadRequest("AUDIT__CHECKPOINT_SECRET is not set".into()))?;
+      match AuditHandler::new(pool).checkpoint(secret)? {
+        Some(seq) => eprintln!("Checkpointed the audit chain at seq {}", seq),
+        None => eprintln!("The audit chain is already checkpointed"),
+      }
+    }
+  }
+  Ok(())
+}
+
+// Changes are audited as made by authctl and the operating system user running it.
+fn audit_context() -> AuditContext {
+  match std::env::var("USER") {
+    Ok(user) => AuditContext::system(&format!("authctl:{}", user)),
+    Err(_) => AuditContext::system("authctl"),
+  }
+}
+
+// Same minimum length as the API enforces.
+fn password_or_generated(password: Option<String>) -> Result<(String, bool), AppError> {
+  match password {
+    Some(password) if password.len() < 8 => Err(AppError::BadRequest("Password must be at least 8 characters long".into())),
+    Some(password) => Ok((password, false)),
+    None => Ok((Encryption::generate_secret(GENERATED_PASSWORD_LENGTH), true)),
+  }
+}
+
+fn format_from_path(path: &str) -> Option<BundleFormat> {
+  path.rsplit_once('.').and_then(|(_, extension)| BundleFormat::parse(extension))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), AppError> {
+  let json = serde_json::to_string_pretty(value).map_err(|e| AppError::BadRequest(format!("Failed to serialize output: {}", e)))?;
+  println!("{}", json);
+  Ok(())
+}
\ No newline at end of file
@@ -6,11 +6,25 @@ pub struct Config {
   pub database: Database,
   pub server: Server,
   pub auth: Auth,
+  #[serde(default)]
+  pub relations: Relations,
+  #[serde(default)]
+  pub elevation: Elevation,
+  #[serde(default)]
+  pub bootstrap: Bootstrap,
+  #[serde(default)]
+  pub audit: Audit,
+  #[serde(default)]
+  pub webhooks: Webhooks,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Database {
   pub url: String,
+  // Apply pending embedded migrations before serving. Off by default so deployments that
+  // migrate separately keep doing so.
+  #[serde(default)]
+  pub run_migrations: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -26,6 +40,154 @@ pub struct Auth {
   pub jwt_secret: String,
   #[serde(default = "default_expiration_seconds")]
   pub expiration_seconds: i64,
+  #[serde(default = "default_issuer")]
+  pub issuer: String,
+  #[serde(default = "default_audience")]
+  pub audience: String,
+  #[serde(default)]
+  pub allowed_audiences: Vec<String>,
+  #[serde(default)]
+  pub authorization_mode: AuthorizationMode,
+  #[serde(default = "default_permission_cache_ttl_seconds")]
+  pub permission_cache_ttl_seconds: u64,
+  // Take the client IP for IP-range conditions from Forwarded/X-Forwarded-For. Only enable
+  // behind a proxy that overwrites these headers.
+  #[serde(default)]
+  pub trust_forwarded_for: bool,
+  #[serde(default)]
+  pub decision_backend: DecisionBackend,
+  // `*.cedar` files loaded alongside the policies stored in the database.
+  #[serde(default = "default_policy_directory")]
+  pub policy_directory: String,
+  #[serde(default = "default_role_expiry_sweep_interval_seconds")]
+  pub role_expiry_sweep_interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Relations {
+  #[serde(default = "default_namespace_config_path")]
+  pub namespace_config_path: String,
+}
+
+impl Default for Relations {
+  fn default() -> Self {
+    Relations {
+      namespace_config_path: default_namespace_config_path(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Elevation {
+  #[serde(default = "default_elevation_duration_minutes")]
+  pub default_duration_minutes: i64,
+  #[serde(default = "default_elevation_max_duration_minutes")]
+  pub max_duration_minutes: i64,
+}
+
+impl Default for Elevation {
+  fn default() -> Self {
+    Elevation {
+      default_duration_minutes: default_elevation_duration_minutes(),
+      max_duration_minutes: default_elevation_max_duration_minutes(),
+    }
+  }
+}
+
+// The initial admin created at startup when no user holds the admin role. Without a password a
+// one-time password is generated and printed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Bootstrap {
+  #[serde(default = "default_bootstrap_admin_username")]
+  pub admin_username: String,
+  #[serde(default = "default_bootstrap_admin_email")]
+  pub admin_email: String,
+  pub admin_password: Option<String>,
+}
+
+impl Default for Bootstrap {
+  fn default() -> Self {
+    Bootstrap {
+      admin_username: default_bootstrap_admin_username(),
+      admin_email: default_bootstrap_admin_email(),
+      admin_password: None,
+    }
+  }
+}
+
+// Checkpoints sign the head of the audit chain with HMAC-SHA256 so the chain cannot be
+// rewritten without the secret. Without a secret no checkpoints are written.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Audit {
+  pub checkpoint_secret: Option<String>,
+  #[serde(default = "default_audit_checkpoint_interval_seconds")]
+  pub checkpoint_interval_seconds: u64,
+}
+
+impl Default for Audit {
+  fn default() -> Self {
+    Audit {
+      checkpoint_secret: None,
+      checkpoint_interval_seconds: default_audit_checkpoint_interval_seconds(),
+    }
+  }
+}
+
+// Outbox events are fanned out to webhooks and delivered with exponential backoff; a delivery
+// still failing after `max_attempts` is dead-lettered.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Webhooks {
+  #[serde(default = "default_webhooks_dispatch_interval_seconds")]
+  pub dispatch_interval_seconds: u64,
+  #[serde(default = "default_webhooks_max_attempts")]
+  pub max_attempts: i32,
+  #[serde(default = "default_webhooks_timeout_seconds")]
+  pub timeout_seconds: u64,
+  #[serde(default = "default_webhooks_retry_base_seconds")]
+  pub retry_base_seconds: i64,
+}
+
+impl Default for Webhooks {
+  fn default() -> Self {
+    Webhooks {
+      dispatch_interval_seconds: default_webhooks_dispatch_interval_seconds(),
+      max_attempts: default_webhooks_max_attempts(),
+      timeout_seconds: default_webhooks_timeout_seconds(),
+      retry_base_seconds: default_webhooks_retry_base_seconds(),
+    }
+  }
+}
+
+// `Database` re-checks sessions and permissions on every request; `Stateless` trusts the
+// roles and permissions embedded in the token at login until it expires.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorizationMode {
+  #[default]
+  Database,
+  Stateless,
+}
+
+// `Rbac` evaluates role grants; `Policy` evaluates the policy set instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecisionBackend {
+  #[default]
+  Rbac,
+  Policy,
+}
+
+impl Auth {
+  // The service's own audience is always accepted alongside the configured client audiences.
+  pub fn known_audiences(&self) -> Vec<&str> {
+    std::iter::once(self.audience.as_str())
+      .chain(self.allowed_audiences.iter().map(String::as_str))
+      .collect()
+  }
+
+  pub fn is_known_audience(&self, audience: &str) -> bool {
+    self.known_audiences().contains(&audience)
+  }
 }
 
 fn default_server_host() -> String {
@@ -40,6 +202,66 @@ fn default_expiration_seconds() -> i64 {
   3600
 }
 
+fn default_permission_cache_ttl_seconds() -> u64 {
+  300
+}
+
+fn default_role_expiry_sweep_interval_seconds() -> u64 {
+  60
+}
+
+fn default_elevation_duration_minutes() -> i64 {
+  60
+}
+
+fn default_elevation_max_duration_minutes() -> i64 {
+  480
+}
+
+fn default_bootstrap_admin_username() -> String {
+  "admin".into()
+}
+
+fn default_bootstrap_admin_email() -> String {
+  "admin@example.com".into()
+}
+
+fn default_audit_checkpoint_interval_seconds() -> u64 {
+  3600
+}
+
+fn default_webhooks_dispatch_interval_seconds() -> u64 {
+  5
+}
+
+fn default_webhooks_max_attempts() -> i32 {
+  8
+}
+
+fn default_webhooks_timeout_seconds() -> u64 {
+  10
+}
+
+fn default_webhooks_retry_base_seconds() -> i64 {
+  30
+}
+
+fn default_namespace_config_path() -> String {
+  "namespaces.json".into()
+}
+
+fn default_policy_directory() -> String {
+  "policies".into()
+}
+
+fn default_issuer() -> String {
+  "rust-authen-service".into()
+}
+
+fn default_audience() -> String {
+  "rust-authen-service".into()
+}
+
 impl Config {
   pub fn load() -> Result<Self, ConfigError> {
     // Load .env.local (if exists, as override)
@@ -56,7 +278,13 @@ impl Config {
 
     // Build config with override
     let builder = RawConfig::builder()
-      .add_source(Environment::default().separator("__"))
+      .add_source(
+        Environment::default()
+          .separator("__")
+          .try_parsing(true)
+          .list_separator(",")
+          .with_list_parse_key("auth.allowed_audiences"),
+      )
       .set_override("database.url", db_url)?; // 👈 manual override here
 
     let config: Config = builder.build()?.try_deserialize()?;
@@ -75,6 +303,62 @@ impl Config {
       ));
     }
 
+    if self.auth.issuer.trim().is_empty() || self.auth.audience.trim().is_empty() {
+      return Err(ConfigError::Message(
+        "AUTH__ISSUER and AUTH__AUDIENCE must not be empty".into(),
+      ));
+    }
+
+    if self.elevation.default_duration_minutes < 1
+      || self.elevation.max_duration_minutes < self.elevation.default_duration_minutes
+    {
+      return Err(ConfigError::Message(
+        "ELEVATION__DEFAULT_DURATION_MINUTES must be positive and at most ELEVATION__MAX_DURATION_MINUTES".into(),
+      ));
+    }
+
+    if self.auth.role_expiry_sweep_interval_seconds == 0 {
+      return Err(ConfigError::Message(
+        "AUTH__ROLE_EXPIRY_SWEEP_INTERVAL_SECONDS must be greater than zero".into(),
+      ));
+    }
+
+    if self.bootstrap.admin_username.len() < 3 {
+      return Err(ConfigError::Message(
+        "BOOTSTRAP__ADMIN_USERNAME must be at least 3 characters long".into(),
+      ));
+    }
+
+    if self.bootstrap.admin_password.as_ref().is_some_and(|password| password.len() < 8) {
+      return Err(ConfigError::Message(
+        "BOOTSTRAP__ADMIN_PASSWORD must be at least 8 characters long".into(),
+      ));
+    }
+
+    if self.audit.checkpoint_secret.as_ref().is_some_and(|secret| secret.len() < 32) {
+      return Err(ConfigError::Message(
+        "AUDIT__CHECKPOINT_SECRET must be at least 32 characters long".into(),
+      ));
+    }
+
+    if self.audit.checkpoint_interval_seconds == 0 {
+      return Err(ConfigError::Message(
+        "AUDIT__CHECKPOINT_INTERVAL_SECONDS must be greater than zero".into(),
+      ));
+    }
+
+    if self.webhooks.dispatch_interval_seconds == 0 || self.webhooks.timeout_seconds == 0 {
+      return Err(ConfigError::Message(
+        "WEBHOOKS__DISPATCH_INTERVAL_SECONDS and WEBHOOKS__TIMEOUT_SECONDS must be greater than zero".into(),
+      ));
+    }
+
+    if self.webhooks.max_attempts < 1 || self.webhooks.retry_base_seconds < 1 {
+      return Err(ConfigError::Message(
+        "WEBHOOKS__MAX_ATTEMPTS and WEBHOOKS__RETRY_BASE_SECONDS must be positive".into(),
+      ));
+    }
+
     Ok(())
   }
 }
\ No newline at end of file
@@ -1,12 +1,23 @@
 use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
 use diesel::r2d2::{ConnectionManager, Pool};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use crate::utilities::error::AppError;
+use log::{error, info};
 
 pub type PgPool = Pool<ConnectionManager<PgConnection>>;
 
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+// Serializes migration runs of instances starting at the same time.
+const MIGRATIONS_LOCK_KEY: i64 = 0x6d69_6772_6174_6500;
+
 #[derive(Debug, Clone)]
 pub struct DatabasePool;
 
 impl DatabasePool {
+  #[allow(clippy::new_ret_no_self)]
   pub fn new(database_url: &str) -> PgPool {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     Pool::builder()
@@ -14,4 +25,48 @@ impl DatabasePool {
         .build(manager)
         .expect("Could not build connection pool")
   }
+
+  // Versions of the embedded migrations not yet applied to the database.
+  pub fn pending_migrations(pool: &PgPool) -> Result<Vec<String>, AppError> {
+    let mut conn = pool.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let pending = conn.pending_migrations(MIGRATIONS).map_err(|e| {
+      error!("Failed to list pending migrations: {}", e);
+      AppError::DatabaseError(format!("Failed to list pending migrations: {}", e))
+    })?;
+    Ok(pending.iter().map(|m| m.name().version().to_string()).collect())
+  }
+
+  pub fn run_pending_migrations(pool: &PgPool) -> Result<Vec<String>, AppError> {
+    let mut conn = pool.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    // Session-level lock, since every migration runs in its own transaction.
+    diesel::sql_query("SELECT pg_advisory_lock($1)")
+      .bind::<BigInt, _>(MIGRATIONS_LOCK_KEY)
+      .execute(&mut conn)
+      .map_err(|e| {
+        error!("Failed to take the migrations lock: {}", e);
+        AppError::DatabaseError(format!("Failed to take the migrations lock: {}", e))
+      })?;
+    let applied = conn.run_pending_migrations(MIGRATIONS).map(|applied| {
+      applied.iter().map(|version| version.to_string()).collect::<Vec<String>>()
+    });
+    let unlocked = diesel::sql_query("SELECT pg_advisory_unlock($1)")
+      .bind::<BigInt, _>(MIGRATIONS_LOCK_KEY)
+      .execute(&mut conn);
+    let applied = applied.map_err(|e| {
+      error!("Failed to run migrations: {}", e);
+      AppError::DatabaseError(format!("Failed to run migrations: {}", e))
+    })?;
+    unlocked.map_err(|e| {
+      error!("Failed to release the migrations lock: {}", e);
+      AppError::DatabaseError(format!("Failed to release the migrations lock: {}", e))
+    })?;
+    info!("Applied {} migrations", applied.len());
+    Ok(applied)
+  }
 }
\ No newline at end of file
@@ -0,0 +1,148 @@
+use crate::database::PgPool;
+use crate::models::audit::{
+  AuditContext, AuditEvent, AuditFilter, AuditRecord, BrokenLink, ChainVerification, NewAuditCheckpoint, NewAuditEvent,
+};
+use crate::repositories::audit::AuditRepository;
+use crate::utilities::audit_chain::{self, GENESIS_HASH};
+use crate::utilities::error::AppError;
+use log::{debug, info, warn};
+
+const CHAIN_PAGE_SIZE: i64 = 1000;
+
+pub struct AuditHandler<'a> {
+  repo: AuditRepository<'a>,
+}
+
+impl<'a> AuditHandler<'a> {
+  pub fn new(pool: &'a PgPool) -> Self {
+    debug!("Creating AuditHandler");
+    Self {
+      repo: AuditRepository::new(pool),
+    }
+  }
+
+  // For events that change nothing else, such as failed logins. Changes record their events
+  // within their own transaction through repositories::audit::record.
+  pub fn record(&self, context: &AuditContext, record: AuditRecord) -> Result<(), AppError> {
+    debug!("Recording audit event {} on {} {:?}", record.action, record.target_type, record.target_id);
+    self.repo.create(NewAuditEvent::new(context, &record))?;
+    Ok(())
+  }
+
+  pub fn find_all(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>, AppError> {
+    info!("Listing audit events");
+    debug!("Calling AuditRepository to list audit events");
+    let events = self.repo.find_all(filter)?;
+    info!("Found {} audit events", events.len());
+    Ok(events)
+  }
+
+  // Signs the head of the chain unless it is already covered by a checkpoint. Returns the seq
+  // of the new checkpoint.
+  pub fn checkpoint(&self, secret: &str) -> Result<Option<i64>, AppError> {
+    debug!("Checkpointing the audit chain");
+    let Some((seq, hash)) = self.repo.find_chain_head()? else {
+      debug!("No chained audit events to checkpoint");
+      return Ok(None);
+    };
+    if self.repo.find_latest_checkpoint_seq()?.is_some_and(|latest| latest >= seq) {
+      debug!("Audit chain already checkpointed at seq {}", seq);
+      return Ok(None);
+    }
+    let signature = audit_chain::sign_checkpoint(secret, seq, &hash);
+    if !self.repo.create_checkpoint(NewAuditCheckpoint { seq, hash: &hash, signature: &signature })? {
+      return Ok(None);
+    }
+    info!("Audit chain checkpointed at seq {}", seq);
+    Ok(Some(seq))
+  }
+
+  // Walks the chain from the first event and stops at the first broken link: a missing event,
+  // an event whose hash or predecessor does not match, or a checkpoint that does not match the
+  // chain or its signature. Checkpoints past the last event mean events were removed from the end.
+  pub fn verify(&self, secret: Option<&str>) -> Result<ChainVerification, AppError> {
+    info!("Verifying the audit chain");
+    let checkpoints = self.repo.find_checkpoints()?;
+    let mut checkpoints = checkpoints.iter().peekable();
+    let mut report = ChainVerification {
+      signatures_checked: secret.is_some(),
+      ..Default::default()
+    };
+    let mut last_seq = 0;
+    let mut last_hash: Option<String> = None;
+    'walk: loop {
+      let page = self.repo.find_chain_page(last_seq, CHAIN_PAGE_SIZE)?;
+      if page.is_empty() {
+        break;
+      }
+      for event in page {
+        if event.seq != last_seq + 1 {
+          report.first_broken_link = Some(BrokenLink {
+            seq: last_seq + 1,
+            id: None,
+            reason: format!("Events {} to {} are missing", last_seq + 1, event.seq - 1),
+          });
+          break 'walk;
+        }
+        if let Some(reason) = broken_reason(&event, last_hash.as_deref()) {
+          report.first_broken_link = Some(BrokenLink { seq: event.seq, id: Some(event.id), reason });
+          break 'walk;
+        }
+        last_seq = event.seq;
+        report.events_checked += 1;
+        let Some(hash) = event.hash else {
+          report.unchained_events += 1;
+          continue;
+        };
+        while let Some(checkpoint) = checkpoints.next_if(|c| c.seq == event.seq) {
+          report.checkpoints_checked += 1;
+          let reason = if checkpoint.hash != hash {
+            Some(format!("Checkpoint {} does not match the hash of the event", checkpoint.id))
+          } else if secret.is_some_and(|secret| !audit_chain::verify_checkpoint(secret, checkpoint.seq, &checkpoint.hash, &checkpoint.signature)) {
+            Some(format!("Checkpoint {} has an invalid signature", checkpoint.id))
+          } else {
+            None
+          };
+          if let Some(reason) = reason {
+            report.first_broken_link = Some(BrokenLink { seq: event.seq, id: Some(event.id), reason });
+            break 'walk;
+          }
+        }
+        last_hash = Some(hash);
+      }
+    }
+    if report.first_broken_link.is_none() {
+      // Checkpoints at a seq without a chained event: either the event is gone or the
+      // checkpoint was forged.
+      if let Some(checkpoint) = checkpoints.next() {
+        report.first_broken_link = Some(BrokenLink {
+          seq: checkpoint.seq.min(last_seq + 1),
+          id: None,
+          reason: format!("Checkpoint {} covers event {} which is not in the chain", checkpoint.id, checkpoint.seq),
+        });
+      }
+    }
+    report.last_seq = (last_seq > 0).then_some(last_seq);
+    report.valid = report.first_broken_link.is_none();
+    match &report.first_broken_link {
+      Some(link) => warn!("Audit chain broken at seq {}: {}", link.seq, link.reason),
+      None => info!("Audit chain verified: {} events, {} checkpoints", report.events_checked, report.checkpoints_checked),
+    }
+    Ok(report)
+  }
+}
+
+// Unchained events are only allowed before the first chained one.
+fn broken_reason(event: &AuditEvent, prev_hash: Option<&str>) -> Option<String> {
+  let Some(hash) = &event.hash else {
+    return prev_hash.map(|_| "Event is not chained although earlier events are".to_string());
+  };
+  let expected_prev = prev_hash.unwrap_or(GENESIS_HASH);
+  if event.prev_hash.as_deref() != Some(expected_prev) {
+    return Some(format!("Event does not link to the hash of event {}", event.seq - 1));
+  }
+  if event.compute_hash() != *hash {
+    return Some("Event hash does not match its contents".to_string());
+  }
+  None
+}
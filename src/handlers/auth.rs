@@ -1,13 +1,22 @@
+use crate::config::{Auth, AuthorizationMode};
 use crate::database::PgPool;
+use crate::handlers::audit::AuditHandler;
+use crate::handlers::authorization::AuthorizationHandler;
+use crate::handlers::oauth::{narrow_scope, restrict_to_scope};
 use crate::handlers::user::UserHandler;
 use crate::utilities::error::AppError;
 use crate::utilities::encryption::Encryption;
+use crate::models::audit::{AuditContext, AuditRecord, ACTION_LOGIN, ACTION_LOGIN_FAILED, TARGET_USER};
 use crate::models::user::UserResponse;
+use crate::models::session::NewSession;
+use crate::models::authorization::Grant;
+use crate::repositories::role::RoleRepository;
+use crate::repositories::session::SessionRepository;
 use crate::middlewares::jwt;
-use jsonwebtoken::{encode, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
 use chrono::{Duration, Utc};
 use log::{debug, error, info};
+use uuid::Uuid;
 
 #[derive(Deserialize)]
 pub struct RegisterRequest {
@@ -20,6 +29,8 @@ pub struct RegisterRequest {
 pub struct LoginRequest {
   pub username: String,
   pub password: String,
+  pub audience: Option<String>,
+  pub scope: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -30,50 +41,90 @@ pub struct LoginResponse {
 
 pub struct AuthHandler<'a> {
   user_handler: UserHandler<'a>,
-  jwt_secret: String,
-  expiration_seconds: i64,
+  session_repo: SessionRepository<'a>,
+  authorization_handler: AuthorizationHandler<'a>,
+  role_repo: RoleRepository<'a>,
+  audit: AuditHandler<'a>,
+  auth: Auth,
 }
 
 impl<'a> AuthHandler<'a> {
-  pub fn new(pool: &'a PgPool, jwt_secret: String, expiration_seconds: i64) -> Self {
-    debug!("Initializing AuthHandler with expiration_seconds: {}", expiration_seconds);
+  pub fn new(pool: &'a PgPool, auth: Auth) -> Self {
+    debug!("Initializing AuthHandler with expiration_seconds: {}", auth.expiration_seconds);
     AuthHandler {
       user_handler: UserHandler::new(pool),
-      jwt_secret,
-      expiration_seconds,
+      session_repo: SessionRepository::new(pool),
+      authorization_handler: AuthorizationHandler::new(pool, auth.clone()),
+      role_repo: RoleRepository::new(pool),
+      audit: AuditHandler::new(pool),
+      auth,
     }
   }
 
-  pub fn register(&self, req: &RegisterRequest) -> Result<UserResponse, AppError> {
+  pub fn register(&self, req: &RegisterRequest, context: &AuditContext) -> Result<UserResponse, AppError> {
     info!("Registering user: {}", req.username);
     debug!("Calling UserHandler to create user: {}", req.username);
-    let user = self.user_handler.create(&req.username, &req.email, &req.password)?;
+    let user = self.user_handler.create(&req.username, &req.email, &req.password, context)?;
     info!("User registered successfully: {}", user.username);
     Ok(user.into())
   }
 
-  pub fn login(&self, req: &LoginRequest) -> Result<LoginResponse, AppError> {
+  pub fn login(&self, req: &LoginRequest, context: &AuditContext) -> Result<LoginResponse, AppError> {
     info!("Attempting login for user: {}", req.username);
+    let audience = req.audience.clone().unwrap_or_else(|| self.auth.audience.clone());
+    if !self.auth.is_known_audience(&audience) {
+      error!("Login for user {} requested unknown audience {}", req.username, audience);
+      return Err(AppError::BadRequest(format!("invalid_target: unknown audience {}", audience)));
+    }
     debug!("Looking up user: {}", req.username);
-    let user = self.user_handler.find_by_username(&req.username)?;
+    let user = match self.user_handler.find_by_username(&req.username) {
+      Ok(user) => user,
+      Err(e) => {
+        self.record_failed_login(context, &req.username, None)?;
+        return Err(e);
+      }
+    };
     debug!("Verifying password for user: {}", user.username);
     let is_valid = Encryption::verify_password(&req.password, &user.password_hash)?;
     if is_valid {
-      debug!("Generating JWT for user: {}", user.username);
-      let exp = (Utc::now() + Duration::seconds(self.expiration_seconds)).timestamp() as usize;
-      let claims = jwt::Claims {
-        sub: user.id.to_string(),
-        exp,
+      let stateless = self.auth.authorization_mode == AuthorizationMode::Stateless;
+      let grants: Vec<Grant> = if stateless || req.scope.is_some() {
+        debug!("Loading permissions for user: {}", user.username);
+        self.authorization_handler.grants_for_user(user.id)?.to_vec()
+      } else {
+        Vec::new()
+      };
+      let scope = match req.scope.as_deref() {
+        Some(requested) => {
+          debug!("Narrowing token scope for user: {}", user.username);
+          Some(narrow_scope(&grants, Some(requested))?)
+        }
+        None => None,
       };
-      let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(self.jwt_secret.as_ref()),
-      )
-      .map_err(|e| {
-        error!("Failed to generate JWT for user {}: {:?}", user.username, e);
-        AppError::JwtError(format!("Failed to generate JWT: {}", e))
-      })?;
+      debug!("Generating JWT for user: {}", user.username);
+      let expires_at = Utc::now() + Duration::seconds(self.auth.expiration_seconds);
+      let session_id = Uuid::new_v4();
+      let context = AuditContext { actor_id: Some(user.id), ..context.clone() };
+      let mut record = AuditRecord::new(ACTION_LOGIN, TARGET_USER, Some(user.id.to_string()));
+      record.after = Some(serde_json::json!({ "session_id": session_id, "audience": audience }));
+      let session = self.session_repo.create(NewSession {
+        id: session_id,
+        user_id: user.id,
+        expires_at,
+      }, &context, &record)?;
+      let mut claims = jwt::Claims::new(&self.auth, user.id.to_string(), audience, session.id.to_string(), expires_at);
+      claims.scope = scope.as_ref().map(|scope| scope.join(" "));
+      if stateless {
+        debug!("Embedding roles and permissions in token for user: {}", user.username);
+        let roles = self.role_repo.find_by_user_id(user.id)?;
+        claims.roles = Some(roles.into_iter().map(|r| r.name).collect());
+        let grants = match &scope {
+          Some(scope) => restrict_to_scope(&grants, scope),
+          None => grants,
+        };
+        claims.permissions = Some(grants.iter().filter_map(Grant::to_claim).collect());
+      }
+      let token = jwt::encode_token(&claims, &self.auth.jwt_secret)?;
       info!("Login successful for user: {}", user.username);
       Ok(LoginResponse {
         user: user.into(),
@@ -81,7 +132,14 @@ impl<'a> AuthHandler<'a> {
       })
     } else {
         error!("Invalid password for user: {}", req.username);
+        self.record_failed_login(context, &req.username, Some(user.id))?;
         Err(AppError::InvalidCredentials)
     }
   }
+
+  // The attempted username is kept as the actor, also when no such user exists.
+  fn record_failed_login(&self, context: &AuditContext, username: &str, user_id: Option<Uuid>) -> Result<(), AppError> {
+    let context = AuditContext { actor: Some(username.to_string()), ..context.clone() };
+    self.audit.record(&context, AuditRecord::new(ACTION_LOGIN_FAILED, TARGET_USER, user_id.map(|id| id.to_string())))
+  }
 }
\ No newline at end of file
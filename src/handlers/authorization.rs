@@ -0,0 +1,167 @@
+use crate::config::{Auth, AuthorizationMode, DecisionBackend};
+use crate::database::PgPool;
+use crate::middlewares::jwt::Claims;
+use crate::models::authorization::{self, Decision, EffectivePermissions, Grant, ResourceRef};
+use crate::models::condition::RequestContext;
+use crate::handlers::policy::PolicyHandler;
+use crate::repositories::group::GroupRepository;
+use crate::repositories::permission::PermissionRepository;
+use crate::repositories::role::RoleRepository;
+use crate::repositories::user::UserRepository;
+use crate::utilities::error::AppError;
+use crate::utilities::permission_cache;
+use crate::utilities::policy::{PolicyRequest, Value};
+use chrono::{Datelike, Timelike};
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+pub struct AuthorizationHandler<'a> {
+  pool: &'a PgPool,
+  auth: Auth,
+}
+
+impl<'a> AuthorizationHandler<'a> {
+  pub fn new(pool: &'a PgPool, auth: Auth) -> Self {
+    debug!("Creating AuthorizationHandler");
+    AuthorizationHandler {
+      pool,
+      auth,
+    }
+  }
+
+  // Effective grants of a user across all of their roles, served from the permission cache.
+  pub fn grants_for_user(&self, user_id: Uuid) -> Result<Arc<Vec<Grant>>, AppError> {
+    let ttl = Duration::from_secs(self.auth.permission_cache_ttl_seconds);
+    if let Some(grants) = permission_cache::get(user_id, ttl) {
+      debug!("Permission cache hit for user_id={}", user_id);
+      return Ok(grants);
+    }
+    debug!("Permission cache miss for user_id={}", user_id);
+    let generation = permission_cache::generation();
+    let grants = PermissionRepository::new(self.pool).find_grants_by_user_id(user_id)?;
+    Ok(permission_cache::insert(user_id, grants, generation))
+  }
+
+  pub fn effective_permissions(&self, user_id: Uuid) -> Result<EffectivePermissions, AppError> {
+    info!("Resolving effective permissions for user_id={}", user_id);
+    UserRepository::new(self.pool).find_by_id(user_id)?;
+    let groups = GroupRepository::new(self.pool).find_by_user_id(user_id)?;
+    let roles = RoleRepository::new(self.pool).find_by_user_id(user_id)?;
+    let grants = self.grants_for_user(user_id)?;
+    Ok(EffectivePermissions {
+      user_id,
+      groups: groups.into_iter().map(|group| group.name).collect(),
+      roles: roles.into_iter().map(|role| role.name).collect(),
+      grants: grants.to_vec(),
+    })
+  }
+
+  // Attributes are only loaded when a grant has conditions on them.
+  fn with_user_attributes(&self, user_id: Uuid, grants: &[Grant], context: &RequestContext) -> Result<RequestContext, AppError> {
+    let mut context = context.clone();
+    if grants.iter().any(Grant::uses_user_attributes) {
+      debug!("Loading attributes of user_id={} for conditional grants", user_id);
+      let user = UserRepository::new(self.pool).find_by_id(user_id)?;
+      context.user_attributes = user.attribute_map();
+    }
+    Ok(context)
+  }
+
+  pub fn check(&self, user_id: Uuid, permission: &str, resource: Option<&ResourceRef>, context: &RequestContext) -> Result<Decision, AppError> {
+    info!("Checking permission {} on {:?} for user_id={}", permission, resource, user_id);
+    if self.auth.decision_backend == DecisionBackend::Policy {
+      return self.check_policies(user_id, None, permission, resource, context);
+    }
+    let grants = self.grants_for_user(user_id)?;
+    let context = self.with_user_attributes(user_id, &grants, context)?;
+    let decision = authorization::evaluate(&grants, permission, resource, &context);
+    info!("Permission {} for user_id={}: {}", permission, user_id, decision.reason);
+    Ok(decision)
+  }
+
+  // Decision for a request carrying `claims`: the token scope must cover the permission, and
+  // stateless tokens are evaluated against the grants they embed instead of the database.
+  pub fn check_token(&self, user_id: Uuid, claims: &Claims, permission: &str, resource: Option<&ResourceRef>, context: &RequestContext) -> Result<Decision, AppError> {
+    if !claims.has_scope(permission) {
+      debug!("Token for user_id={} is not scoped for {}", user_id, permission);
+      return Ok(Decision::denied(permission, resource, "outside token scope"));
+    }
+    if self.auth.decision_backend == DecisionBackend::Policy {
+      let roles = match self.auth.authorization_mode {
+        AuthorizationMode::Stateless => claims.roles.as_deref(),
+        AuthorizationMode::Database => None,
+      };
+      return self.check_policies(user_id, roles, permission, resource, context);
+    }
+    match (self.auth.authorization_mode, claims.permissions.as_ref()) {
+      (AuthorizationMode::Stateless, Some(permissions)) => {
+        debug!("Authorizing user_id={} from token claims", user_id);
+        let grants: Vec<Grant> = permissions.iter().map(|p| Grant::from_claim(p)).collect();
+        Ok(authorization::evaluate(&grants, permission, resource, context))
+      }
+      _ => self.check(user_id, permission, resource, context),
+    }
+  }
+
+  // Policy backend: the request is described to the policy set instead of matched against role
+  // grants. Roles come from `roles` when given (stateless tokens), otherwise from the database.
+  // Failing to load the policies denies the request.
+  fn check_policies(&self, user_id: Uuid, roles: Option<&[String]>, permission: &str, resource: Option<&ResourceRef>, context: &RequestContext) -> Result<Decision, AppError> {
+    let policies = match PolicyHandler::new(self.pool).policy_set(&self.auth.policy_directory, Duration::from_secs(self.auth.permission_cache_ttl_seconds)) {
+      Ok(policies) => policies,
+      Err(e) => {
+        error!("Failed to load policies: {}", e);
+        return Ok(Decision::denied(permission, resource, "policies could not be loaded"));
+      }
+    };
+    let mut principal = HashMap::new();
+    principal.insert("id".to_string(), Value::String(format!("user:{}", user_id)));
+    let roles = match roles {
+      Some(roles) => roles.to_vec(),
+      None => {
+        let user = UserRepository::new(self.pool).find_by_id(user_id)?;
+        for (name, value) in user.attribute_map() {
+          principal.insert(name, Value::String(value));
+        }
+        principal.insert("username".to_string(), Value::String(user.username));
+        RoleRepository::new(self.pool).find_by_user_id(user_id)?.into_iter().map(|r| r.name).collect()
+      }
+    };
+    principal.insert("roles".to_string(), Value::Set(roles.into_iter().map(Value::String).collect()));
+    let request = PolicyRequest {
+      principal,
+      action: permission.to_string(),
+      resource: resource.map(|r| (r.resource_type.clone(), r.resource_id.clone())),
+      context: policy_context(context),
+    };
+    let result = policies.is_authorized(&request);
+    for e in &result.errors {
+      warn!("Policy evaluation error for user_id={}: {}", user_id, e);
+    }
+    info!("Permission {} for user_id={}: {}", permission, user_id, result.reason);
+    Ok(Decision {
+      allowed: result.allowed,
+      permission: permission.to_string(),
+      resource: resource.cloned(),
+      reason: result.reason,
+      matched_grant: None,
+      policy: result.policy,
+    })
+  }
+}
+
+// `context.time` is unix seconds; `hour` and `day` (`mon` .. `sun`) are in UTC.
+fn policy_context(context: &RequestContext) -> HashMap<String, Value> {
+  let mut values = HashMap::new();
+  values.insert("time".to_string(), Value::Long(context.time.timestamp()));
+  values.insert("hour".to_string(), Value::Long(i64::from(context.time.hour())));
+  let day = context.time.weekday().to_string().to_lowercase();
+  values.insert("day".to_string(), Value::String(day));
+  if let Some(ip) = context.client_ip {
+    values.insert("ip".to_string(), Value::String(ip.to_string()));
+  }
+  values
+}
\ No newline at end of file
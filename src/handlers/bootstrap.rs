@@ -0,0 +1,59 @@
+use crate::config::Bootstrap;
+use crate::database::PgPool;
+use crate::models::bootstrap::{BUILTIN_PERMISSIONS, ADMIN_ROLE};
+use crate::models::user::NewUser;
+use crate::repositories::bootstrap::BootstrapRepository;
+use crate::utilities::encryption::Encryption;
+use crate::utilities::error::AppError;
+use log::{debug, info};
+
+const GENERATED_PASSWORD_LENGTH: usize = 24;
+
+pub struct BootstrapHandler<'a> {
+  repo: BootstrapRepository<'a>,
+  settings: Bootstrap,
+}
+
+impl<'a> BootstrapHandler<'a> {
+  pub fn new(pool: &'a PgPool, settings: Bootstrap) -> Self {
+    debug!("Creating BootstrapHandler");
+    Self {
+      repo: BootstrapRepository::new(pool),
+      settings,
+    }
+  }
+
+  // Creates missing built-in permissions and, when nobody holds the admin role, the admin role
+  // and initial admin user. Safe to run on every startup.
+  pub fn run(&self) -> Result<(), AppError> {
+    info!("Running startup bootstrap");
+    self.repo.ensure_builtins(BUILTIN_PERMISSIONS)?;
+    if self.repo.admin_exists()? {
+      info!("A user already holds the {} role; skipping admin bootstrap", ADMIN_ROLE);
+      return Ok(());
+    }
+
+    let (password, generated) = match &self.settings.admin_password {
+      Some(password) => (password.clone(), false),
+      None => (Encryption::generate_secret(GENERATED_PASSWORD_LENGTH), true),
+    };
+    let password_hash = Encryption::hash_password(&password)?;
+    let new_user = NewUser {
+      username: &self.settings.admin_username,
+      email: &self.settings.admin_email,
+      password_hash: &password_hash,
+    };
+    let admin_permissions: Vec<&str> = BUILTIN_PERMISSIONS.iter().filter(|p| p.admin).map(|p| p.name).collect();
+    debug!("Calling BootstrapRepository to create admin user {}", self.settings.admin_username);
+    match self.repo.create_admin(new_user, &admin_permissions)? {
+      None => info!("Another instance bootstrapped the admin user"),
+      Some(admin) if generated => {
+        // Printed once and never stored in clear; set BOOTSTRAP__ADMIN_PASSWORD to choose it instead.
+        println!("Created initial admin user '{}' with one-time password: {}", admin.user.username, password);
+        println!("Change this password after the first login; it will not be shown again.");
+      }
+      Some(admin) => info!("Created initial admin user {} with the configured password", admin.user.username),
+    }
+    Ok(())
+  }
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::bundle::{ImportReport, RbacBundle};
+use crate::repositories::bundle::BundleRepository;
+use crate::utilities::error::AppError;
+use log::{debug, error, info};
+
+pub struct BundleHandler<'a> {
+  repo: BundleRepository<'a>,
+}
+
+impl<'a> BundleHandler<'a> {
+  pub fn new(pool: &'a PgPool) -> Self {
+    debug!("Creating BundleHandler");
+    Self {
+      repo: BundleRepository::new(pool),
+    }
+  }
+
+  pub fn export(&self, include_user_roles: bool) -> Result<RbacBundle, AppError> {
+    info!("Exporting RBAC bundle");
+    debug!("Calling BundleRepository to export RBAC state");
+    self.repo.export(include_user_roles)
+  }
+
+  pub fn import(&self, bundle: &RbacBundle, prune: bool, dry_run: bool, context: &AuditContext) -> Result<ImportReport, AppError> {
+    info!("Importing RBAC bundle (prune={}, dry_run={})", prune, dry_run);
+    bundle.validate().map_err(|e| {
+      error!("Invalid RBAC bundle: {}", e);
+      AppError::BadRequest(e)
+    })?;
+    debug!("Calling BundleRepository to import RBAC bundle");
+    let plan = self.repo.import(bundle, prune, dry_run, context)?;
+    info!("RBAC bundle import {} {} changes", if dry_run { "would make" } else { "made" }, plan.len());
+    Ok(ImportReport {
+      dry_run,
+      prune,
+      changes: plan.len(),
+      plan,
+    })
+  }
+}
\ No newline at end of file
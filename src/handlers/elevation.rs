@@ -0,0 +1,166 @@
+use crate::config::{Auth, Elevation};
+use crate::database::PgPool;
+use crate::handlers::authorization::AuthorizationHandler;
+use crate::middlewares::jwt::Claims;
+use crate::models::authorization::ResourceRef;
+use crate::models::audit::AuditContext;
+use crate::models::condition::RequestContext;
+use crate::models::elevation::{
+  ElevationEvent, ElevationRequest, ElevationTransition, NewElevationRequest,
+  STATUS_APPROVED, STATUS_CANCELLED, STATUS_DENIED, STATUS_PENDING,
+};
+use crate::repositories::elevation::ElevationRepository;
+use crate::repositories::role::RoleRepository;
+use crate::utilities::error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, error, info};
+use uuid::Uuid;
+
+// Checked against the requested role (`role:<role_id>`), so approvers can be limited to
+// specific roles with resource grants.
+const APPROVE_PERMISSION: &str = "elevation.approve";
+
+pub struct ElevationHandler<'a> {
+  pool: &'a PgPool,
+  repo: ElevationRepository<'a>,
+  auth: Auth,
+  settings: Elevation,
+}
+
+impl<'a> ElevationHandler<'a> {
+  pub fn new(pool: &'a PgPool, auth: Auth, settings: Elevation) -> Self {
+    debug!("Creating ElevationHandler");
+    Self {
+      pool,
+      repo: ElevationRepository::new(pool),
+      auth,
+      settings,
+    }
+  }
+
+  pub fn request(&self, requester_id: Uuid, role_id: Uuid, justification: &str, duration_minutes: Option<i64>) -> Result<ElevationRequest, AppError> {
+    info!("Requesting elevation: requester_id={}, role_id={}", requester_id, role_id);
+    let minutes = duration_minutes.unwrap_or(self.settings.default_duration_minutes);
+    if minutes < 1 || minutes > self.settings.max_duration_minutes {
+      return Err(AppError::BadRequest(format!(
+        "duration_minutes must be between 1 and {}",
+        self.settings.max_duration_minutes
+      )));
+    }
+    RoleRepository::new(self.pool).find_by_id(role_id)?;
+    let held = RoleRepository::new(self.pool).find_by_user_id(requester_id)?;
+    if held.iter().any(|role| role.id == role_id) {
+      return Err(AppError::Conflict(format!("User {} already holds role {}", requester_id, role_id)));
+    }
+    let new_request = NewElevationRequest {
+      requester_id,
+      role_id,
+      justification,
+      duration_seconds: (minutes * 60) as i32,
+    };
+    debug!("Calling ElevationRepository to create elevation request");
+    let request = self.repo.create(new_request)?;
+    info!("Elevation request created: {}", request.id);
+    Ok(request)
+  }
+
+  pub fn find_all(&self, status: Option<&str>, requester_id: Option<Uuid>) -> Result<Vec<ElevationRequest>, AppError> {
+    info!("Listing elevation requests");
+    debug!("Calling ElevationRepository to list elevation requests");
+    let requests = self.repo.find_all(status, requester_id)?;
+    info!("Found {} elevation requests", requests.len());
+    Ok(requests)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<(ElevationRequest, Vec<ElevationEvent>), AppError> {
+    info!("Looking up elevation request: {}", id);
+    debug!("Calling ElevationRepository to find elevation request: {}", id);
+    let request = self.repo.find_by_id(id)?;
+    let events = self.repo.find_events(id)?;
+    info!("Found elevation request {} with {} events", id, events.len());
+    Ok((request, events))
+  }
+
+  // Grants the role until `now + duration`, subject to separation of duties, in the same
+  // transaction that records the approval. Requesters can not approve their own requests.
+  pub fn approve(&self, id: Uuid, approver_id: Uuid, claims: &Claims, context: &RequestContext, comment: Option<&str>, audit: &AuditContext) -> Result<ElevationRequest, AppError> {
+    info!("Approving elevation request {} by approver_id={}", id, approver_id);
+    let request = self.decidable(id, approver_id, claims, context)?;
+    let expires_at = Utc::now() + Duration::seconds(i64::from(request.duration_seconds));
+    let transition = ElevationTransition {
+      status: STATUS_APPROVED,
+      approver_id: Some(approver_id),
+      decision_comment: comment,
+      decided_at: Some(Utc::now()),
+      expires_at: Some(expires_at),
+    };
+    debug!("Calling ElevationRepository to assign role {} to user_id={} until {}", request.role_id, request.requester_id, expires_at);
+    let request = self.repo.approve(id, approver_id, transition, audit)?;
+    info!("Elevation request {} approved until {}", id, expires_at);
+    Ok(request)
+  }
+
+  pub fn deny(&self, id: Uuid, approver_id: Uuid, claims: &Claims, context: &RequestContext, comment: Option<&str>) -> Result<ElevationRequest, AppError> {
+    info!("Denying elevation request {} by approver_id={}", id, approver_id);
+    self.decidable(id, approver_id, claims, context)?;
+    let request = self.decide(id, STATUS_DENIED, approver_id, comment)?;
+    info!("Elevation request {} denied", id);
+    Ok(request)
+  }
+
+  pub fn cancel(&self, id: Uuid, requester_id: Uuid) -> Result<ElevationRequest, AppError> {
+    info!("Cancelling elevation request {} by requester_id={}", id, requester_id);
+    let request = self.repo.find_by_id(id)?;
+    if request.requester_id != requester_id {
+      return Err(AppError::AccessDenied("only the requester can cancel an elevation request".into()));
+    }
+    let transition = ElevationTransition {
+      status: STATUS_CANCELLED,
+      approver_id: None,
+      decision_comment: None,
+      decided_at: Some(Utc::now()),
+      expires_at: None,
+    };
+    let request = self.repo.transition(id, STATUS_PENDING, requester_id, transition)?;
+    info!("Elevation request {} cancelled", id);
+    Ok(request)
+  }
+
+  // Records the end of approved elevations; the role assignments themselves are archived by the
+  // user_role sweep.
+  pub fn expire(&self, now: DateTime<Utc>) -> Result<(), AppError> {
+    for request in self.repo.expire_approved(now)? {
+      info!("Elevation request {} expired", request.id);
+    }
+    Ok(())
+  }
+
+  fn decidable(&self, id: Uuid, approver_id: Uuid, claims: &Claims, context: &RequestContext) -> Result<ElevationRequest, AppError> {
+    let request = self.repo.find_by_id(id)?;
+    if request.status != STATUS_PENDING {
+      return Err(AppError::Conflict(format!("Elevation request {} is not {}", id, STATUS_PENDING)));
+    }
+    if request.requester_id == approver_id {
+      return Err(AppError::AccessDenied("requesters can not decide their own elevation requests".into()));
+    }
+    let resource = ResourceRef::new("role", request.role_id.to_string());
+    let decision = AuthorizationHandler::new(self.pool, self.auth.clone()).check_token(approver_id, claims, APPROVE_PERMISSION, Some(&resource), context)?;
+    if !decision.allowed {
+      error!("User {} may not decide elevation request {}: {}", approver_id, id, decision.reason);
+      return Err(AppError::AccessDenied(format!("{} ({})", APPROVE_PERMISSION, decision.reason)));
+    }
+    Ok(request)
+  }
+
+  fn decide(&self, id: Uuid, status: &str, approver_id: Uuid, comment: Option<&str>) -> Result<ElevationRequest, AppError> {
+    let transition = ElevationTransition {
+      status,
+      approver_id: Some(approver_id),
+      decision_comment: comment,
+      decided_at: Some(Utc::now()),
+      expires_at: None,
+    };
+    debug!("Calling ElevationRepository to move elevation request {} to {}", id, status);
+    self.repo.transition(id, STATUS_PENDING, approver_id, transition)
+  }
+}
\ No newline at end of file
@@ -0,0 +1,107 @@
+use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::group::{Group, GroupMembersResponse, NewGroup, UpdateGroup};
+use crate::repositories::group::GroupRepository;
+use crate::utilities::error::AppError;
+use log::{debug, info};
+use uuid::Uuid;
+use chrono::Utc;
+
+pub struct GroupHandler<'a> {
+  repo: GroupRepository<'a>,
+}
+
+impl<'a> GroupHandler<'a> {
+  pub fn new(pool: &'a PgPool) -> Self {
+    debug!("Creating GroupHandler");
+    Self {
+      repo: GroupRepository::new(pool),
+    }
+  }
+
+  pub fn create(&self, name: &str, description: Option<&str>, context: &AuditContext) -> Result<Group, AppError> {
+    info!("Creating group: {}", name);
+    let new_group = NewGroup {
+      name,
+      description,
+    };
+    debug!("Calling GroupRepository to create group: {}", name);
+    let group = self.repo.create(new_group, context)?;
+    info!("Group created successfully: {}", name);
+    Ok(group)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<Group>, AppError> {
+    info!("Listing groups");
+    debug!("Calling GroupRepository to list groups");
+    let groups = self.repo.find_all()?;
+    info!("Found {} groups", groups.len());
+    Ok(groups)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<Group, AppError> {
+    info!("Looking up group by ID: {}", id);
+    debug!("Calling GroupRepository to find group ID: {}", id);
+    let group = self.repo.find_by_id(id)?;
+    info!("Found group by ID: {}", id);
+    Ok(group)
+  }
+
+  pub fn members(&self, id: Uuid) -> Result<GroupMembersResponse, AppError> {
+    info!("Looking up members of group {}", id);
+    self.repo.find_by_id(id)?;
+    debug!("Calling GroupRepository to find members of group {}", id);
+    self.repo.find_members(id)
+  }
+
+  pub fn update(&self, id: Uuid, name: Option<&str>, description: Option<&str>, context: &AuditContext) -> Result<Group, AppError> {
+    info!("Updating group: {}", id);
+    let update_group = UpdateGroup {
+      name,
+      description,
+      updated_at: Utc::now(),
+    };
+    debug!("Calling GroupRepository to update group: {}", id);
+    let group = self.repo.update(id, update_group, context)?;
+    info!("Group updated successfully: {}", id);
+    Ok(group)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting group: {}", id);
+    debug!("Calling GroupRepository to delete group: {}", id);
+    self.repo.delete(id, context)?;
+    info!("Group deleted successfully: {}", id);
+    Ok(())
+  }
+
+  pub fn add_user(&self, group_id: Uuid, user_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Adding user_id={} to group {}", user_id, group_id);
+    self.repo.add_user(group_id, user_id, context)
+  }
+
+  pub fn remove_user(&self, group_id: Uuid, user_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Removing user_id={} from group {}", user_id, group_id);
+    self.repo.remove_user(group_id, user_id, context)
+  }
+
+  pub fn add_subgroup(&self, parent_group_id: Uuid, child_group_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Adding group {} to group {}", child_group_id, parent_group_id);
+    self.repo.add_subgroup(parent_group_id, child_group_id, context)
+  }
+
+  pub fn remove_subgroup(&self, parent_group_id: Uuid, child_group_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Removing group {} from group {}", child_group_id, parent_group_id);
+    self.repo.remove_subgroup(parent_group_id, child_group_id, context)
+  }
+
+  pub fn add_role(&self, group_id: Uuid, role_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Assigning role_id={} to group {}", role_id, group_id);
+    self.repo.add_role(group_id, role_id, context)
+  }
+
+  pub fn remove_role(&self, group_id: Uuid, role_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Removing role_id={} from group {}", role_id, group_id);
+    self.repo.remove_role(group_id, role_id, context)
+  }
+}
\ No newline at end of file
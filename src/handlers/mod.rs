@@ -3,4 +3,16 @@ pub mod user;
 pub mod role;
 pub mod permission;
 pub mod user_role;
-pub mod role_permission;
\ No newline at end of file
+pub mod role_permission;
+pub mod oauth;
+pub mod authorization;
+pub mod resource_grant;
+pub mod relation;
+pub mod policy;
+pub mod elevation;
+pub mod sod_constraint;
+pub mod group;
+pub mod bundle;
+pub mod bootstrap;
+pub mod audit;
+pub mod webhook;
\ No newline at end of file
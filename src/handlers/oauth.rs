@@ -0,0 +1,286 @@
+use crate::config::{Auth, AuthorizationMode};
+use crate::database::PgPool;
+use crate::middlewares::jwt;
+use crate::handlers::authorization::AuthorizationHandler;
+use crate::models::audit::{AuditContext, AuditRecord, ACTION_TOKEN_EXCHANGE, TARGET_USER};
+use crate::models::authorization::{self, Effect, Grant};
+use crate::models::session::NewSession;
+use crate::repositories::role::RoleRepository;
+use crate::repositories::session::SessionRepository;
+use crate::repositories::user::UserRepository;
+use crate::utilities::error::AppError;
+use crate::utilities::permission_matcher;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, error, info};
+use uuid::Uuid;
+
+pub const GRANT_TYPE_TOKEN_EXCHANGE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+pub const TOKEN_TYPE_ACCESS_TOKEN: &str = "urn:ietf:params:oauth:token-type:access_token";
+pub const TOKEN_TYPE_JWT: &str = "urn:ietf:params:oauth:token-type:jwt";
+
+#[derive(Deserialize)]
+pub struct IntrospectionRequest {
+  pub token: String,
+  pub token_type_hint: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TokenExchangeRequest {
+  pub grant_type: String,
+  pub subject_token: String,
+  pub subject_token_type: String,
+  pub audience: String,
+  pub scope: Option<String>,
+  pub requested_token_type: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TokenExchangeResponse {
+  pub access_token: String,
+  pub issued_token_type: String,
+  pub token_type: String,
+  pub expires_in: i64,
+  pub scope: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct IntrospectionResponse {
+  pub active: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub scope: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub username: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token_type: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub exp: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub iat: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub nbf: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sub: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub iss: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub jti: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub aud: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub act: Option<jwt::ActorClaim>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub roles: Option<Vec<String>>,
+}
+
+impl IntrospectionResponse {
+  fn inactive() -> Self {
+    IntrospectionResponse::default()
+  }
+}
+
+pub struct OAuthHandler<'a> {
+  pool: &'a PgPool,
+  auth: Auth,
+}
+
+// Lets a token manage the caller's own account under /me. It names no permission, so every
+// user may request it.
+pub const PROFILE_SCOPE: &str = "profile";
+
+// Intersects the grants held by a subject with a space-separated scope request, keeping each
+// requested entry the grants may allow under deny-overrides, on any resource, and the profile
+// scope. Without a request every allowed grant is kept.
+pub fn narrow_scope(grants: &[Grant], requested: Option<&str>) -> Result<Vec<String>, AppError> {
+  match requested {
+    Some(requested) => {
+      let requested: Vec<&str> = requested.split_whitespace().collect();
+      let scope: Vec<String> = requested
+        .iter()
+        .filter(|name| **name == PROFILE_SCOPE || authorization::may_allow(grants, name))
+        .map(|name| name.to_string())
+        .collect();
+      if scope.is_empty() {
+        error!("None of the requested scopes are granted: {:?}", requested);
+        return Err(AppError::BadRequest("invalid_scope: none of the requested scopes are granted".into()));
+      }
+      Ok(scope)
+    }
+    None => Ok(allowed_permissions(grants)),
+  }
+}
+
+// The grants, allow and deny, that bear on some permission in `scope`: what a scoped stateless
+// token needs to carry.
+pub fn restrict_to_scope(grants: &[Grant], scope: &[String]) -> Vec<Grant> {
+  grants
+    .iter()
+    .filter(|g| {
+      scope
+        .iter()
+        .any(|s| permission_matcher::matches(&g.permission, s) || permission_matcher::matches(s, &g.permission))
+    })
+    .cloned()
+    .collect()
+}
+
+pub fn allowed_permissions(grants: &[Grant]) -> Vec<String> {
+  let mut permissions: Vec<String> = Vec::new();
+  for grant in grants.iter().filter(|g| g.effect == Effect::Allow) {
+    if !permissions.contains(&grant.permission) {
+      permissions.push(grant.permission.clone());
+    }
+  }
+  permissions
+}
+
+impl<'a> OAuthHandler<'a> {
+  pub fn new(pool: &'a PgPool, auth: Auth) -> Self {
+    debug!("Creating OAuthHandler");
+    OAuthHandler {
+      pool,
+      auth,
+    }
+  }
+
+  /// Reports the state of a token as described by RFC 7662. Tokens that fail to decode,
+  /// have expired, belong to a revoked session or an unknown user are reported as inactive.
+  pub fn introspect(&self, req: &IntrospectionRequest) -> Result<IntrospectionResponse, AppError> {
+    info!("Introspecting token");
+    if let Some(hint) = req.token_type_hint.as_deref() {
+      debug!("Introspection token_type_hint: {}", hint);
+    }
+    let claims = match jwt::decode_token(&req.token, &self.auth, None) {
+      Ok(data) => data.claims,
+      Err(_) => {
+        info!("Introspected token is not valid");
+        return Ok(IntrospectionResponse::inactive());
+      }
+    };
+    match jwt::check_session_active(self.pool, &claims) {
+      Ok(()) => {}
+      Err(AppError::Unauthorized(reason)) => {
+        info!("Introspected token is not active: {}", reason);
+        return Ok(IntrospectionResponse::inactive());
+      }
+      Err(e) => return Err(e),
+    }
+    let user_id = match Uuid::parse_str(&claims.sub) {
+      Ok(id) => id,
+      Err(e) => {
+        error!("Invalid user_id in introspected token: {}", e);
+        return Ok(IntrospectionResponse::inactive());
+      }
+    };
+    let user = match UserRepository::new(self.pool).find_by_id(user_id) {
+      Ok(user) => user,
+      Err(AppError::NotFound(_)) => {
+        info!("Introspected token belongs to unknown user {}", user_id);
+        return Ok(IntrospectionResponse::inactive());
+      }
+      Err(e) => return Err(e),
+    };
+    debug!("Loading roles and permissions for introspected user {}", user_id);
+    let roles = RoleRepository::new(self.pool).find_by_user_id(user_id)?;
+    let scope = match claims.scope.clone() {
+      Some(scope) => scope,
+      None => allowed_permissions(&AuthorizationHandler::new(self.pool, self.auth.clone()).grants_for_user(user_id)?).join(" "),
+    };
+    info!("Introspected token is active for user {}", user_id);
+    Ok(IntrospectionResponse {
+      active: true,
+      scope: Some(scope),
+      username: Some(user.username),
+      token_type: Some("Bearer".into()),
+      exp: Some(claims.exp),
+      iat: Some(claims.iat),
+      nbf: Some(claims.nbf),
+      sub: Some(claims.sub),
+      iss: Some(claims.iss),
+      jti: Some(claims.jti),
+      aud: Some(claims.aud),
+      act: claims.act,
+      roles: Some(roles.into_iter().map(|r| r.name).collect()),
+    })
+  }
+
+  /// Exchanges a subject token for a down-scoped token restricted to `audience`, as described
+  /// by RFC 8693. The authenticated caller is recorded as the actor of the issued token.
+  pub fn exchange(&self, actor: &jwt::Claims, req: &TokenExchangeRequest, context: &AuditContext) -> Result<TokenExchangeResponse, AppError> {
+    info!("Exchanging token on behalf of actor {} for audience {}", actor.sub, req.audience);
+    if req.grant_type != GRANT_TYPE_TOKEN_EXCHANGE {
+      error!("Unsupported grant_type: {}", req.grant_type);
+      return Err(AppError::BadRequest(format!("unsupported_grant_type: {}", req.grant_type)));
+    }
+    if req.subject_token_type != TOKEN_TYPE_ACCESS_TOKEN && req.subject_token_type != TOKEN_TYPE_JWT {
+      error!("Unsupported subject_token_type: {}", req.subject_token_type);
+      return Err(AppError::BadRequest(format!("invalid_request: unsupported subject_token_type {}", req.subject_token_type)));
+    }
+    if let Some(requested) = req.requested_token_type.as_deref()
+      && requested != TOKEN_TYPE_ACCESS_TOKEN
+      && requested != TOKEN_TYPE_JWT
+    {
+      error!("Unsupported requested_token_type: {}", requested);
+      return Err(AppError::BadRequest(format!("invalid_request: unsupported requested_token_type {}", requested)));
+    }
+    if !self.auth.is_known_audience(&req.audience) {
+      error!("Token exchange requested unknown audience {}", req.audience);
+      return Err(AppError::BadRequest(format!("invalid_target: unknown audience {}", req.audience)));
+    }
+
+    debug!("Validating subject token");
+    let subject = jwt::decode_token(&req.subject_token, &self.auth, None)
+      .map_err(|_| AppError::BadRequest("invalid_grant: subject_token is invalid or expired".into()))?
+      .claims;
+    jwt::check_session_active(self.pool, &subject)
+      .map_err(|_| AppError::BadRequest("invalid_grant: subject_token has been revoked".into()))?;
+    let user_id = Uuid::parse_str(&subject.sub)?;
+
+    debug!("Intersecting permissions of user {} with requested scope", user_id);
+    let grants = AuthorizationHandler::new(self.pool, self.auth.clone()).grants_for_user(user_id)?;
+    // Requested entries are checked against the subject's own grants, denies included, and must
+    // also fall within the subject token's scope.
+    let scope: Vec<String> = match req.scope.as_deref() {
+      Some(requested) => narrow_scope(&grants, Some(requested))?
+        .into_iter()
+        .filter(|name| subject.has_scope(name))
+        .collect(),
+      None => narrow_scope(&grants, subject.scope.as_deref())?,
+    };
+    if scope.is_empty() {
+      error!("None of the requested scopes are within the subject token's scope");
+      return Err(AppError::BadRequest("invalid_scope: none of the requested scopes are granted".into()));
+    }
+
+    let subject_expires_at = DateTime::<Utc>::from_timestamp(subject.exp as i64, 0).unwrap_or_else(Utc::now);
+    let expires_at = (Utc::now() + Duration::seconds(self.auth.expiration_seconds)).min(subject_expires_at);
+    let session_id = Uuid::new_v4();
+    let scope_claim = scope.join(" ");
+    let mut record = AuditRecord::new(ACTION_TOKEN_EXCHANGE, TARGET_USER, Some(user_id.to_string()));
+    record.after = Some(serde_json::json!({ "session_id": session_id, "audience": req.audience, "scope": scope_claim }));
+    let session = SessionRepository::new(self.pool).create(NewSession {
+      id: session_id,
+      user_id,
+      expires_at,
+    }, context, &record)?;
+    let mut claims = jwt::Claims::new(&self.auth, subject.sub, req.audience.clone(), session.id.to_string(), expires_at);
+    claims.scope = Some(scope_claim.clone());
+    claims.act = Some(jwt::ActorClaim {
+      sub: actor.sub.clone(),
+      act: subject.act.map(Box::new),
+    });
+    if self.auth.authorization_mode == AuthorizationMode::Stateless {
+      claims.roles = subject.roles;
+      claims.permissions = Some(restrict_to_scope(&grants, &scope).iter().filter_map(Grant::to_claim).collect());
+    }
+    let access_token = jwt::encode_token(&claims, &self.auth.jwt_secret)?;
+    info!("Issued delegated token {} for user {} to audience {}", session.id, user_id, req.audience);
+    Ok(TokenExchangeResponse {
+      access_token,
+      issued_token_type: TOKEN_TYPE_ACCESS_TOKEN.into(),
+      token_type: "Bearer".into(),
+      expires_in: (expires_at - Utc::now()).num_seconds().max(0),
+      scope: scope_claim,
+    })
+  }
+}
\ No newline at end of file
@@ -1,4 +1,5 @@
 use crate::database::PgPool;
+use crate::models::audit::AuditContext;
 use crate::models::permission::{Permission, NewPermission, UpdatePermission};
 use crate::repositories::permission::PermissionRepository;
 use crate::utilities::error::AppError;
@@ -18,14 +19,14 @@ impl<'a> PermissionHandler<'a> {
     }
   }
 
-  pub fn create(&self, name: &str, description: Option<&str>) -> Result<Permission, AppError> {
+  pub fn create(&self, name: &str, description: Option<&str>, context: &AuditContext) -> Result<Permission, AppError> {
     info!("Creating permission: {}", name);
     let new_permission = NewPermission {
       name,
       description,
     };
     debug!("Calling PermissionRepository to create permission: {}", name);
-    let permission = self.repo.create(new_permission)?;
+    let permission = self.repo.create(new_permission, context)?;
     info!("Permission created successfully: {}", name);
     Ok(permission)
   }
@@ -46,7 +47,7 @@ impl<'a> PermissionHandler<'a> {
     Ok(permission)
   }
 
-  pub fn update(&self, id: Uuid, name: Option<&str>, description: Option<&str>) -> Result<Permission, AppError> {
+  pub fn update(&self, id: Uuid, name: Option<&str>, description: Option<&str>, context: &AuditContext) -> Result<Permission, AppError> {
     info!("Updating permission: {}", id);
     let update_permission = UpdatePermission {
       name,
@@ -54,15 +55,15 @@ impl<'a> PermissionHandler<'a> {
       updated_at: Utc::now(),
     };
     debug!("Calling PermissionRepository to update permission: {}", id);
-    let permission = self.repo.update(id, update_permission)?;
+    let permission = self.repo.update(id, update_permission, context)?;
     info!("Permission updated successfully: {}", id);
     Ok(permission)
   }
 
-  pub fn delete(&self, id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting permission: {}", id);
     debug!("Calling PermissionRepository to delete permission: {}", id);
-    self.repo.delete(id)?;
+    self.repo.delete(id, context)?;
     info!("Permission deleted successfully: {}", id);
     Ok(())
   }
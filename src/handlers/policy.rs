@@ -0,0 +1,135 @@
+use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::policy::{Policy, NewPolicy, UpdatePolicy};
+use crate::repositories::policy::PolicyRepository;
+use crate::utilities::error::AppError;
+use crate::utilities::policy::PolicySet;
+use crate::utilities::policy_store;
+use log::{debug, info, warn};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use chrono::Utc;
+
+const POLICY_FILE_EXTENSION: &str = "cedar";
+
+pub struct PolicyHandler<'a> {
+  repo: PolicyRepository<'a>,
+}
+
+impl<'a> PolicyHandler<'a> {
+  pub fn new(pool: &'a PgPool) -> Self {
+    debug!("Creating PolicyHandler");
+    Self {
+      repo: PolicyRepository::new(pool),
+    }
+  }
+
+  pub fn create(&self, name: &str, body: &str, enabled: bool, context: &AuditContext) -> Result<Policy, AppError> {
+    info!("Creating policy: {}", name);
+    validate_body(name, body)?;
+    let new_policy = NewPolicy {
+      name,
+      body,
+      enabled,
+    };
+    debug!("Calling PolicyRepository to create policy: {}", name);
+    let policy = self.repo.create(new_policy, context)?;
+    info!("Policy created successfully: {}", name);
+    Ok(policy)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<Policy>, AppError> {
+    info!("Listing policies");
+    debug!("Calling PolicyRepository to list policies");
+    let policies = self.repo.find_all()?;
+    info!("Found {} policies", policies.len());
+    Ok(policies)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<Policy, AppError> {
+    info!("Looking up policy by ID: {}", id);
+    debug!("Calling PolicyRepository to find policy ID: {}", id);
+    let policy = self.repo.find_by_id(id)?;
+    info!("Found policy by ID: {}", id);
+    Ok(policy)
+  }
+
+  pub fn update(&self, id: Uuid, name: Option<&str>, body: Option<&str>, enabled: Option<bool>, context: &AuditContext) -> Result<Policy, AppError> {
+    info!("Updating policy: {}", id);
+    if let Some(body) = body {
+      validate_body(name.unwrap_or("policy"), body)?;
+    }
+    let update_policy = UpdatePolicy {
+      name,
+      body,
+      enabled,
+      updated_at: Utc::now(),
+    };
+    debug!("Calling PolicyRepository to update policy: {}", id);
+    let policy = self.repo.update(id, update_policy, context)?;
+    info!("Policy updated successfully: {}", id);
+    Ok(policy)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting policy: {}", id);
+    debug!("Calling PolicyRepository to delete policy: {}", id);
+    self.repo.delete(id, context)?;
+    info!("Policy deleted successfully: {}", id);
+    Ok(())
+  }
+
+  // Policies from `directory` followed by the enabled database policies, served from the
+  // policy store.
+  pub fn policy_set(&self, directory: &str, ttl: Duration) -> Result<Arc<PolicySet>, AppError> {
+    if let Some(policies) = policy_store::get(ttl) {
+      debug!("Policy store hit");
+      return Ok(policies);
+    }
+    debug!("Policy store miss; loading policies");
+    let generation = policy_store::generation();
+    let mut policies = load_directory(directory)?;
+    for policy in self.repo.find_enabled()? {
+      policies.add_source(&policy.name, &policy.body).map_err(|e| {
+        AppError::BadRequest(format!("Invalid policy {}: {}", policy.name, e))
+      })?;
+    }
+    info!("Loaded {} policies", policies.len());
+    Ok(policy_store::insert(policies, generation))
+  }
+}
+
+fn validate_body(name: &str, body: &str) -> Result<(), AppError> {
+  let count = PolicySet::new()
+    .add_source(name, body)
+    .map_err(|e| AppError::BadRequest(format!("Invalid policy: {}", e)))?;
+  if count == 0 {
+    return Err(AppError::BadRequest("Invalid policy: no policies found".into()));
+  }
+  Ok(())
+}
+
+// Files are loaded in name order so generated policy ids are stable.
+pub fn load_directory(directory: &str) -> Result<PolicySet, AppError> {
+  let mut policies = PolicySet::new();
+  if !Path::new(directory).is_dir() {
+    warn!("Policy directory {} not found; only database policies are used", directory);
+    return Ok(policies);
+  }
+  let mut paths = std::fs::read_dir(directory)?
+    .map(|entry| entry.map(|e| e.path()))
+    .collect::<Result<Vec<_>, _>>()?;
+  paths.retain(|path| path.extension().is_some_and(|ext| ext == POLICY_FILE_EXTENSION));
+  paths.sort();
+  for path in paths {
+    let source = path.display().to_string();
+    let text = std::fs::read_to_string(&path)?;
+    let count = policies
+      .add_source(&source, &text)
+      .map_err(|e| AppError::BadRequest(format!("Invalid policy file {}: {}", source, e)))?;
+    debug!("Loaded {} policies from {}", count, source);
+  }
+  Ok(policies)
+}
\ No newline at end of file
@@ -0,0 +1,367 @@
+use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::namespace::{NamespaceConfig, Rewrite};
+use crate::models::relation_tuple::{NewRelationTuple, RelationTuple, Subject};
+use crate::repositories::relation_tuple::RelationTupleRepository;
+use crate::utilities::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use validator::Validate;
+use log::{debug, error, info};
+
+// Bounds the recursion through usersets and rewrites.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Deserialize, Validate)]
+pub struct TupleRequest {
+  #[validate(length(min = 1, max = 50))]
+  pub namespace: String,
+  #[validate(length(min = 1, max = 255))]
+  pub object_id: String,
+  #[validate(length(min = 1, max = 50))]
+  pub relation: String,
+  #[validate(length(min = 3, max = 357))]
+  pub subject: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ExpandRequest {
+  #[validate(length(min = 1, max = 50))]
+  pub namespace: String,
+  #[validate(length(min = 1, max = 255))]
+  pub object_id: String,
+  #[validate(length(min = 1, max = 50))]
+  pub relation: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ListObjectsRequest {
+  #[validate(length(min = 1, max = 50))]
+  pub namespace: String,
+  #[validate(length(min = 1, max = 50))]
+  pub relation: String,
+  #[validate(length(min = 3, max = 357))]
+  pub subject: String,
+}
+
+#[derive(Serialize)]
+pub struct CheckResponse {
+  pub allowed: bool,
+}
+
+#[derive(Serialize)]
+pub struct ListObjectsResponse {
+  pub object_ids: Vec<String>,
+}
+
+// The subjects of `userset` (`namespace:object_id#relation`), mirroring the rewrite that produced them.
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UsersetTree {
+  This { userset: String, subjects: Vec<String> },
+  ComputedUserset { userset: String, child: Box<UsersetTree> },
+  TupleToUserset { userset: String, tupleset: String, children: Vec<UsersetTree> },
+  Union { userset: String, children: Vec<UsersetTree> },
+}
+
+type Userset = (String, String, String);
+
+// Per-request state of a check or expand: the usersets on the current path, for cycle
+// detection, and the results already computed. A result reached through a cycle cut short
+// depends on the path it was computed on, so only results without one are memoized.
+struct Traversal<T> {
+  visited: HashSet<Userset>,
+  memo: HashMap<Userset, T>,
+  cycles: usize,
+}
+
+impl<T> Traversal<T> {
+  fn new() -> Self {
+    Traversal {
+      visited: HashSet::new(),
+      memo: HashMap::new(),
+      cycles: 0,
+    }
+  }
+}
+
+pub struct RelationHandler<'a> {
+  repo: RelationTupleRepository<'a>,
+  namespaces: &'a NamespaceConfig,
+}
+
+impl<'a> RelationHandler<'a> {
+  pub fn new(pool: &'a PgPool, namespaces: &'a NamespaceConfig) -> Self {
+    debug!("Creating RelationHandler");
+    RelationHandler {
+      repo: RelationTupleRepository::new(pool),
+      namespaces,
+    }
+  }
+
+  pub fn write(&self, req: &TupleRequest, context: &AuditContext) -> Result<RelationTuple, AppError> {
+    info!("Writing relation tuple {}:{}#{}@{}", req.namespace, req.object_id, req.relation, req.subject);
+    let tuple = self.to_new_tuple(req)?;
+    self.repo.create(tuple, context)
+  }
+
+  pub fn delete(&self, req: &TupleRequest, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting relation tuple {}:{}#{}@{}", req.namespace, req.object_id, req.relation, req.subject);
+    let tuple = self.to_new_tuple(req)?;
+    self.repo.delete(&tuple, context)
+  }
+
+  pub fn read(&self, namespace: &str, object_id: &str) -> Result<Vec<RelationTuple>, AppError> {
+    info!("Reading relation tuples of {}:{}", namespace, object_id);
+    self.repo.find(namespace, object_id, None)
+  }
+
+  pub fn check(&self, namespace: &str, object_id: &str, relation: &str, subject: &str) -> Result<bool, AppError> {
+    info!("Checking {}:{}#{}@{}", namespace, object_id, relation, subject);
+    self.ensure_relation(namespace, relation)?;
+    let subject = parse_subject(subject)?;
+    let allowed = self.check_userset(namespace, object_id, relation, &subject, &mut Traversal::new(), 0)?;
+    info!("Check {}:{}#{}@{}: {}", namespace, object_id, relation, subject, allowed);
+    Ok(allowed)
+  }
+
+  pub fn expand(&self, req: &ExpandRequest) -> Result<UsersetTree, AppError> {
+    info!("Expanding {}:{}#{}", req.namespace, req.object_id, req.relation);
+    self.ensure_relation(&req.namespace, &req.relation)?;
+    self.expand_userset(&req.namespace, &req.object_id, &req.relation, &mut Traversal::new(), 0)
+  }
+
+  // Walks the rewrites backwards from the subject: every userset containing the subject is
+  // reached from a smaller one through a stored tuple, a computed userset or a tuple_to_userset,
+  // so only the tuples naming reached objects as their subject are read.
+  pub fn list_objects(&self, req: &ListObjectsRequest) -> Result<Vec<String>, AppError> {
+    info!("Listing {} objects with relation {} for {}", req.namespace, req.relation, req.subject);
+    self.ensure_relation(&req.namespace, &req.relation)?;
+    let subject = parse_subject(&req.subject)?;
+    let mut reached: HashSet<Subject> = HashSet::from([subject.clone()]);
+    let mut frontier = VecDeque::from([subject]);
+    let mut depth = 0;
+    while !frontier.is_empty() {
+      if depth > MAX_DEPTH {
+        error!("List objects exceeded maximum depth for {}", req.subject);
+        return Err(AppError::BadRequest("Relation graph exceeds maximum depth".into()));
+      }
+      let mut next = VecDeque::new();
+      for member in frontier {
+        for userset in self.containing_usersets(&member)? {
+          if reached.insert(userset.clone()) {
+            next.push_back(userset);
+          }
+        }
+      }
+      frontier = next;
+      depth += 1;
+    }
+    let mut object_ids: Vec<String> = reached
+      .into_iter()
+      .filter(|s| s.namespace == req.namespace && s.relation.as_deref() == Some(req.relation.as_str()))
+      .map(|s| s.id)
+      .collect();
+    object_ids.sort();
+    info!("Found {} {} objects with relation {} for {}", object_ids.len(), req.namespace, req.relation, req.subject);
+    Ok(object_ids)
+  }
+
+  // The usersets that directly contain `member` under one step of their rewrite.
+  fn containing_usersets(&self, member: &Subject) -> Result<Vec<Subject>, AppError> {
+    let userset = |namespace: &str, object_id: &str, relation: &str| Subject {
+      namespace: namespace.to_string(),
+      id: object_id.to_string(),
+      relation: Some(relation.to_string()),
+    };
+    let mut usersets = Vec::new();
+    for tuple in self.repo.find_by_subject(&member.namespace, &member.id)? {
+      if tuple.subject() == *member && self.namespaces.stores_subjects(&tuple.namespace, &tuple.relation) {
+        usersets.push(userset(&tuple.namespace, &tuple.object_id, &tuple.relation));
+      }
+      if let Some(computed) = member.relation.as_deref() {
+        for relation in self.namespaces.tuple_to_userset_sources(&tuple.namespace, &tuple.relation, computed) {
+          usersets.push(userset(&tuple.namespace, &tuple.object_id, relation));
+        }
+      }
+    }
+    if let Some(relation) = member.relation.as_deref() {
+      for computed in self.namespaces.computed_userset_sources(&member.namespace, relation) {
+        usersets.push(userset(&member.namespace, &member.id, computed));
+      }
+    }
+    Ok(usersets)
+  }
+
+  fn to_new_tuple(&self, req: &TupleRequest) -> Result<NewRelationTuple, AppError> {
+    self.ensure_relation(&req.namespace, &req.relation)?;
+    let subject = parse_subject(&req.subject)?;
+    if let Some(relation) = subject.relation.as_deref()
+      && self.namespaces.has_namespace(&subject.namespace)
+    {
+      self.ensure_relation(&subject.namespace, relation)?;
+    }
+    Ok(NewRelationTuple {
+      namespace: req.namespace.clone(),
+      object_id: req.object_id.clone(),
+      relation: req.relation.clone(),
+      subject_namespace: subject.namespace,
+      subject_id: subject.id,
+      subject_relation: subject.relation.unwrap_or_default(),
+    })
+  }
+
+  fn ensure_relation(&self, namespace: &str, relation: &str) -> Result<(), AppError> {
+    match self.namespaces.relation(namespace, relation) {
+      Some(_) => Ok(()),
+      None => {
+        error!("Unknown relation {}#{}", namespace, relation);
+        Err(AppError::BadRequest(format!("Unknown relation {}#{}", namespace, relation)))
+      }
+    }
+  }
+
+  // Relations missing from the config (e.g. of an unconfigured subject namespace) only have
+  // the subjects stored for them directly.
+  fn rewrite(&self, namespace: &str, relation: &str) -> Rewrite {
+    self.namespaces
+      .relation(namespace, relation)
+      .map(|r| r.rewrite.clone())
+      .unwrap_or_default()
+  }
+
+  fn check_userset(&self, namespace: &str, object_id: &str, relation: &str, subject: &Subject, state: &mut Traversal<bool>, depth: usize) -> Result<bool, AppError> {
+    if depth > MAX_DEPTH {
+      error!("Check exceeded maximum depth at {}:{}#{}", namespace, object_id, relation);
+      return Err(AppError::BadRequest("Relation graph exceeds maximum depth".into()));
+    }
+    // A userset is a member of itself.
+    if subject.namespace == namespace && subject.id == object_id && subject.relation.as_deref() == Some(relation) {
+      return Ok(true);
+    }
+    let userset = (namespace.to_string(), object_id.to_string(), relation.to_string());
+    if let Some(allowed) = state.memo.get(&userset) {
+      return Ok(*allowed);
+    }
+    if !state.visited.insert(userset.clone()) {
+      debug!("Cycle through {}:{}#{}", namespace, object_id, relation);
+      state.cycles += 1;
+      return Ok(false);
+    }
+    let cycles = state.cycles;
+    let rewrite = self.rewrite(namespace, relation);
+    let allowed = self.check_rewrite(&rewrite, namespace, object_id, relation, subject, state, depth)?;
+    state.visited.remove(&userset);
+    // A cycle cannot turn a found path into a missing one, so positive results always hold.
+    if allowed || state.cycles == cycles {
+      state.memo.insert(userset, allowed);
+    }
+    Ok(allowed)
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn check_rewrite(&self, rewrite: &Rewrite, namespace: &str, object_id: &str, relation: &str, subject: &Subject, state: &mut Traversal<bool>, depth: usize) -> Result<bool, AppError> {
+    match rewrite {
+      Rewrite::This => {
+        for tuple in self.repo.find(namespace, object_id, Some(relation))? {
+          let tuple_subject = tuple.subject();
+          if tuple_subject == *subject {
+            return Ok(true);
+          }
+          if let Some(tuple_relation) = tuple_subject.relation.as_deref()
+            && self.check_userset(&tuple_subject.namespace, &tuple_subject.id, tuple_relation, subject, state, depth + 1)?
+          {
+            return Ok(true);
+          }
+        }
+        Ok(false)
+      }
+      Rewrite::ComputedUserset { relation } => self.check_userset(namespace, object_id, relation, subject, state, depth + 1),
+      Rewrite::TupleToUserset { tupleset, computed_userset } => {
+        for tuple in self.repo.find(namespace, object_id, Some(tupleset))? {
+          if self.check_userset(&tuple.subject_namespace, &tuple.subject_id, computed_userset, subject, state, depth + 1)? {
+            return Ok(true);
+          }
+        }
+        Ok(false)
+      }
+      Rewrite::Union(children) => {
+        for child in children {
+          if self.check_rewrite(child, namespace, object_id, relation, subject, state, depth)? {
+            return Ok(true);
+          }
+        }
+        Ok(false)
+      }
+    }
+  }
+
+  fn expand_userset(&self, namespace: &str, object_id: &str, relation: &str, state: &mut Traversal<UsersetTree>, depth: usize) -> Result<UsersetTree, AppError> {
+    if depth > MAX_DEPTH {
+      error!("Expand exceeded maximum depth at {}:{}#{}", namespace, object_id, relation);
+      return Err(AppError::BadRequest("Relation graph exceeds maximum depth".into()));
+    }
+    let name = format!("{}:{}#{}", namespace, object_id, relation);
+    let userset = (namespace.to_string(), object_id.to_string(), relation.to_string());
+    if let Some(tree) = state.memo.get(&userset) {
+      return Ok(tree.clone());
+    }
+    if !state.visited.insert(userset.clone()) {
+      debug!("Cycle through {}", name);
+      state.cycles += 1;
+      return Ok(UsersetTree::This { userset: name, subjects: Vec::new() });
+    }
+    let cycles = state.cycles;
+    let rewrite = self.rewrite(namespace, relation);
+    let tree = self.expand_rewrite(&rewrite, namespace, object_id, relation, state, depth)?;
+    state.visited.remove(&userset);
+    if state.cycles == cycles {
+      state.memo.insert(userset, tree.clone());
+    }
+    Ok(tree)
+  }
+
+  // Usersets stored as subjects are returned as-is rather than expanded, as in Zanzibar.
+  fn expand_rewrite(&self, rewrite: &Rewrite, namespace: &str, object_id: &str, relation: &str, state: &mut Traversal<UsersetTree>, depth: usize) -> Result<UsersetTree, AppError> {
+    let name = format!("{}:{}#{}", namespace, object_id, relation);
+    match rewrite {
+      Rewrite::This => {
+        let subjects = self.repo
+          .find(namespace, object_id, Some(relation))?
+          .iter()
+          .map(|t| t.subject().to_string())
+          .collect();
+        Ok(UsersetTree::This { userset: name, subjects })
+      }
+      Rewrite::ComputedUserset { relation } => Ok(UsersetTree::ComputedUserset {
+        userset: name,
+        child: Box::new(self.expand_userset(namespace, object_id, relation, state, depth + 1)?),
+      }),
+      Rewrite::TupleToUserset { tupleset, computed_userset } => {
+        let mut children = Vec::new();
+        for tuple in self.repo.find(namespace, object_id, Some(tupleset))? {
+          children.push(self.expand_userset(&tuple.subject_namespace, &tuple.subject_id, computed_userset, state, depth + 1)?);
+        }
+        Ok(UsersetTree::TupleToUserset {
+          userset: name,
+          tupleset: format!("{}:{}#{}", namespace, object_id, tupleset),
+          children,
+        })
+      }
+      Rewrite::Union(rewrites) => {
+        let mut children = Vec::new();
+        for child in rewrites {
+          children.push(self.expand_rewrite(child, namespace, object_id, relation, state, depth)?);
+        }
+        Ok(UsersetTree::Union { userset: name, children })
+      }
+    }
+  }
+}
+
+fn parse_subject(subject: &str) -> Result<Subject, AppError> {
+  Subject::parse(subject).ok_or_else(|| {
+    error!("Invalid subject: {}", subject);
+    AppError::BadRequest(format!("Invalid subject '{}': expected namespace:id or namespace:id#relation", subject))
+  })
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::resource_grant::{ResourceGrant, NewResourceGrant};
+use crate::repositories::resource_grant::ResourceGrantRepository;
+use crate::utilities::error::AppError;
+use log::{debug, info};
+use uuid::Uuid;
+
+pub struct ResourceGrantHandler<'a> {
+  repo: ResourceGrantRepository<'a>,
+}
+
+impl<'a> ResourceGrantHandler<'a> {
+  pub fn new(pool: &'a PgPool) -> Self {
+    debug!("Creating ResourceGrantHandler");
+    Self {
+      repo: ResourceGrantRepository::new(pool),
+    }
+  }
+
+  pub fn create(&self, user_id: Uuid, role_id: Uuid, resource_type: &str, resource_id: &str, context: &AuditContext) -> Result<ResourceGrant, AppError> {
+    info!("Creating resource_grant: user_id={}, role_id={}, resource={}:{}", user_id, role_id, resource_type, resource_id);
+    let new_resource_grant = NewResourceGrant {
+      user_id,
+      role_id,
+      resource_type: resource_type.to_string(),
+      resource_id: resource_id.to_string(),
+    };
+    debug!("Calling ResourceGrantRepository to create resource_grant for user_id={}", user_id);
+    let resource_grant = self.repo.create(new_resource_grant, context)?;
+    info!("ResourceGrant created successfully: {}", resource_grant.id);
+    Ok(resource_grant)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<ResourceGrant, AppError> {
+    info!("Looking up resource_grant: {}", id);
+    debug!("Calling ResourceGrantRepository to find resource_grant: {}", id);
+    let resource_grant = self.repo.find_by_id(id)?;
+    info!("Found resource_grant: {}", id);
+    Ok(resource_grant)
+  }
+
+  pub fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<ResourceGrant>, AppError> {
+    info!("Looking up resource_grants for user_id={}", user_id);
+    debug!("Calling ResourceGrantRepository to find resource_grants for user_id={}", user_id);
+    let resource_grants = self.repo.find_by_user_id(user_id)?;
+    info!("Found {} resource_grants for user_id={}", resource_grants.len(), user_id);
+    Ok(resource_grants)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting resource_grant: {}", id);
+    debug!("Calling ResourceGrantRepository to delete resource_grant: {}", id);
+    self.repo.delete(id, context)?;
+    info!("ResourceGrant deleted successfully: {}", id);
+    Ok(())
+  }
+}
\ No newline at end of file
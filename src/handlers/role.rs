@@ -1,8 +1,9 @@
 use crate::database::PgPool;
-use crate::models::role::{Role, NewRole, UpdateRole, RoleResponse};
+use crate::models::audit::AuditContext;
+use crate::models::role::{Role, NewRole, UpdateRole};
 use crate::repositories::role::RoleRepository;
 use crate::utilities::error::AppError;
-use log::{debug, error, info};
+use log::{debug, info};
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -18,14 +19,14 @@ impl<'a> RoleHandler<'a> {
     }
   }
 
-  pub fn create(&self, name: &str, description: Option<&str>) -> Result<Role, AppError> {
+  pub fn create(&self, name: &str, description: Option<&str>, context: &AuditContext) -> Result<Role, AppError> {
     info!("Creating role: {}", name);
     let new_role = NewRole {
       name,
       description,
     };
     debug!("Calling RoleRepository to create role: {}", name);
-    let role = self.repo.create(new_role)?;
+    let role = self.repo.create(new_role, context)?;
     info!("Role created successfully: {}", name);
     Ok(role)
   }
@@ -46,7 +47,7 @@ impl<'a> RoleHandler<'a> {
     Ok(role)
   }
 
-  pub fn update(&self, id: Uuid, name: Option<&str>, description: Option<&str>) -> Result<Role, AppError> {
+  pub fn update(&self, id: Uuid, name: Option<&str>, description: Option<&str>, context: &AuditContext) -> Result<Role, AppError> {
     info!("Updating role: {}", id);
     let update_role = UpdateRole {
       name,
@@ -54,15 +55,15 @@ impl<'a> RoleHandler<'a> {
       updated_at: Utc::now(),
     };
     debug!("Calling RoleRepository to update role: {}", id);
-    let role = self.repo.update(id, update_role)?;
+    let role = self.repo.update(id, update_role, context)?;
     info!("Role updated successfully: {}", id);
     Ok(role)
   }
 
-  pub fn delete(&self, id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting role: {}", id);
     debug!("Calling RoleRepository to delete role: {}", id);
-    self.repo.delete(id)?;
+    self.repo.delete(id, context)?;
     info!("Role deleted successfully: {}", id);
     Ok(())
   }
@@ -1,4 +1,6 @@
 use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::condition::Conditions;
 use crate::models::role_permission::RolePermission;
 use crate::repositories::role_permission::RolePermissionRepository;
 use crate::utilities::error::AppError;
@@ -17,10 +19,10 @@ impl<'a> RolePermissionHandler<'a> {
     }
   }
 
-  pub fn create(&self, role_id: Uuid, permission_id: Uuid) -> Result<RolePermission, AppError> {
-    info!("Creating role_permission: role_id={}, permission_id={}", role_id, permission_id);
+  pub fn create(&self, role_id: Uuid, permission_id: Uuid, effect: &str, conditions: Option<&Conditions>, context: &AuditContext) -> Result<RolePermission, AppError> {
+    info!("Creating role_permission: role_id={}, permission_id={}, effect={}", role_id, permission_id, effect);
     debug!("Calling RolePermissionRepository to create role_permission: role_id={}, permission_id={}", role_id, permission_id);
-    let role_permission = self.repo.create(role_id, permission_id)?;
+    let role_permission = self.repo.create(role_id, permission_id, effect, conditions.map(|c| serde_json::json!(c)), context)?;
     info!("RolePermission created successfully: role_id={}, permission_id={}", role_id, permission_id);
     Ok(role_permission)
   }
@@ -41,10 +43,10 @@ impl<'a> RolePermissionHandler<'a> {
     Ok(role_permissions)
   }
 
-  pub fn delete(&self, role_id: Uuid, permission_id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, role_id: Uuid, permission_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting role_permission: role_id={}, permission_id={}", role_id, permission_id);
     debug!("Calling RolePermissionRepository to delete role_permission: role_id={}, permission_id={}", role_id, permission_id);
-    self.repo.delete(role_id, permission_id)?;
+    self.repo.delete(role_id, permission_id, context)?;
     info!("RolePermission deleted successfully: role_id={}, permission_id={}", role_id, permission_id);
     Ok(())
   }
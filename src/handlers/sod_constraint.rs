@@ -0,0 +1,70 @@
+use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::sod_constraint::{self, NewSodConstraint, SodConstraintWithRoles, SodViolation};
+use crate::repositories::sod_constraint::SodConstraintRepository;
+use crate::utilities::error::AppError;
+use log::{debug, info};
+use uuid::Uuid;
+
+pub struct SodConstraintHandler<'a> {
+  repo: SodConstraintRepository<'a>,
+}
+
+impl<'a> SodConstraintHandler<'a> {
+  pub fn new(pool: &'a PgPool) -> Self {
+    debug!("Creating SodConstraintHandler");
+    Self {
+      repo: SodConstraintRepository::new(pool),
+    }
+  }
+
+  // Existing holders of several roles are not rejected; they show up in `violations`.
+  pub fn create(&self, name: &str, description: Option<&str>, role_ids: &[Uuid], context: &AuditContext) -> Result<SodConstraintWithRoles, AppError> {
+    info!("Creating SoD constraint: {}", name);
+    let mut role_ids = role_ids.to_vec();
+    role_ids.sort();
+    role_ids.dedup();
+    if role_ids.len() < 2 {
+      return Err(AppError::BadRequest("A SoD constraint needs at least two distinct roles".into()));
+    }
+    let new_constraint = NewSodConstraint {
+      name,
+      description,
+    };
+    debug!("Calling SodConstraintRepository to create SoD constraint: {}", name);
+    let constraint = self.repo.create(new_constraint, &role_ids, context)?;
+    info!("SoD constraint created successfully: {}", name);
+    Ok(constraint)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<SodConstraintWithRoles>, AppError> {
+    info!("Listing SoD constraints");
+    debug!("Calling SodConstraintRepository to list SoD constraints");
+    let constraints = self.repo.find_all()?;
+    info!("Found {} SoD constraints", constraints.len());
+    Ok(constraints)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<SodConstraintWithRoles, AppError> {
+    info!("Looking up SoD constraint by ID: {}", id);
+    debug!("Calling SodConstraintRepository to find SoD constraint ID: {}", id);
+    let constraint = self.repo.find_by_id(id)?;
+    info!("Found SoD constraint by ID: {}", id);
+    Ok(constraint)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting SoD constraint: {}", id);
+    debug!("Calling SodConstraintRepository to delete SoD constraint: {}", id);
+    self.repo.delete(id, context)?;
+    info!("SoD constraint deleted successfully: {}", id);
+    Ok(())
+  }
+
+  pub fn violations(&self) -> Result<Vec<SodViolation>, AppError> {
+    info!("Building SoD violation report");
+    let violations = sod_constraint::group_violations(self.repo.find_violation_rows()?);
+    info!("Found {} SoD violations", violations.len());
+    Ok(violations)
+  }
+}
\ No newline at end of file
@@ -1,14 +1,19 @@
 use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::session::SessionResponse;
 use crate::models::user::{User, NewUser, UpdateUser};
+use crate::repositories::session::SessionRepository;
 use crate::repositories::user::UserRepository;
 use crate::utilities::error::AppError;
 use crate::utilities::encryption::Encryption;
 use log::{debug, error, info};
 use uuid::Uuid;
 use chrono::Utc;
+use std::collections::HashMap;
 
 pub struct UserHandler<'a> {
   repo: UserRepository<'a>,
+  sessions: SessionRepository<'a>,
 }
 
 impl<'a> UserHandler<'a> {
@@ -16,10 +21,11 @@ impl<'a> UserHandler<'a> {
     debug!("Creating UserHandler");
     Self {
       repo: UserRepository::new(pool),
+      sessions: SessionRepository::new(pool),
     }
   }
 
-  pub fn create(&self, username: &str, email: &str, password: &str) -> Result<User, AppError> {
+  pub fn create(&self, username: &str, email: &str, password: &str, context: &AuditContext) -> Result<User, AppError> {
     info!("Creating user: {}", username);
     debug!("Hashing password for user: {}", username);
     let password_hash = Encryption::hash_password(password)?;
@@ -29,7 +35,7 @@ impl<'a> UserHandler<'a> {
       password_hash: &password_hash,
     };
     debug!("Calling UserRepository to create user: {}", username);
-    let user = self.repo.create(new_user)?;
+    let user = self.repo.create(new_user, context)?;
     info!("User created successfully: {}", username);
     Ok(user)
   }
@@ -50,7 +56,7 @@ impl<'a> UserHandler<'a> {
     Ok(user)
   }
 
-  pub fn update(&self, id: Uuid, username: Option<&str>, email: Option<&str>, password: Option<&str>) -> Result<User, AppError> {
+  pub fn update(&self, id: Uuid, username: Option<&str>, email: Option<&str>, password: Option<&str>, context: &AuditContext) -> Result<User, AppError> {
     info!("Updating user: {}", id);
     let password_hash = password.map(|p| {
       debug!("Hashing new password for user: {}", id);
@@ -60,19 +66,60 @@ impl<'a> UserHandler<'a> {
       username,
       email,
       password_hash: password_hash.as_deref(),
+      attributes: None,
       updated_at: Utc::now(),
     };
     debug!("Calling UserRepository to update user: {}", id);
-    let user = self.repo.update(id, update_user)?;
+    let user = self.repo.update(id, update_user, context)?;
     info!("User updated successfully: {}", id);
     Ok(user)
   }
 
-  pub fn delete(&self, id: Uuid) -> Result<(), AppError> {
+  pub fn update_attributes(&self, id: Uuid, attributes: &HashMap<String, String>, context: &AuditContext) -> Result<User, AppError> {
+    info!("Updating attributes of user: {}", id);
+    let update_user = UpdateUser {
+      username: None,
+      email: None,
+      password_hash: None,
+      attributes: Some(serde_json::json!(attributes)),
+      updated_at: Utc::now(),
+    };
+    debug!("Calling UserRepository to update attributes of user: {}", id);
+    let user = self.repo.update(id, update_user, context)?;
+    info!("User attributes updated successfully: {}", id);
+    Ok(user)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting user: {}", id);
     debug!("Calling UserRepository to delete user: {}", id);
-    self.repo.delete(id)?;
+    self.repo.delete(id, context)?;
     info!("User deleted successfully: {}", id);
     Ok(())
   }
+
+  // Requires the current password. Every other session of the user is revoked so a stolen
+  // session cannot outlive the password it was opened with.
+  pub fn change_password(&self, id: Uuid, current_password: &str, new_password: &str, session_id: Option<Uuid>, context: &AuditContext) -> Result<(), AppError> {
+    info!("Changing password of user: {}", id);
+    let user = self.repo.find_by_id(id)?;
+    if !Encryption::verify_password(current_password, &user.password_hash)? {
+      error!("Current password did not match for user: {}", id);
+      return Err(AppError::AccessDenied("Current password is incorrect".into()));
+    }
+    if current_password == new_password {
+      return Err(AppError::BadRequest("New password must differ from the current password".into()));
+    }
+    debug!("Hashing new password for user: {}", id);
+    let password_hash = Encryption::hash_password(new_password)?;
+    self.repo.change_password(id, &password_hash, session_id, context)?;
+    info!("Password changed successfully for user: {}", id);
+    Ok(())
+  }
+
+  pub fn sessions(&self, id: Uuid, current_session_id: Option<Uuid>) -> Result<Vec<SessionResponse>, AppError> {
+    info!("Listing active sessions of user: {}", id);
+    let sessions = self.sessions.find_active_by_user_id(id)?;
+    Ok(sessions.into_iter().map(|session| SessionResponse::new(session, current_session_id)).collect())
+  }
 }
\ No newline at end of file
@@ -1,9 +1,11 @@
 use crate::database::PgPool;
+use crate::models::audit::AuditContext;
 use crate::models::user_role::UserRole;
 use crate::repositories::user_role::UserRoleRepository;
 use crate::utilities::error::AppError;
 use log::{debug, info};
 use uuid::Uuid;
+use chrono::{DateTime, Utc};
 
 pub struct UserRoleHandler<'a> {
   repo: UserRoleRepository<'a>,
@@ -17,10 +19,18 @@ impl<'a> UserRoleHandler<'a> {
     }
   }
 
-  pub fn create(&self, user_id: Uuid, role_id: Uuid) -> Result<UserRole, AppError> {
+  pub fn create(&self, user_id: Uuid, role_id: Uuid, valid_from: Option<DateTime<Utc>>, valid_until: Option<DateTime<Utc>>, context: &AuditContext) -> Result<UserRole, AppError> {
     info!("Creating user_role: user_id={}, role_id={}", user_id, role_id);
+    if let Some(until) = valid_until {
+      if until <= Utc::now() {
+        return Err(AppError::BadRequest("valid_until must be in the future".into()));
+      }
+      if valid_from.is_some_and(|from| from >= until) {
+        return Err(AppError::BadRequest("valid_until must be after valid_from".into()));
+      }
+    }
     debug!("Calling UserRoleRepository to create user_role: user_id={}, role_id={}", user_id, role_id);
-    let user_role = self.repo.create(user_id, role_id)?;
+    let user_role = self.repo.create(user_id, role_id, valid_from, valid_until, context)?;
     info!("UserRole created successfully: user_id={}, role_id={}", user_id, role_id);
     Ok(user_role)
   }
@@ -41,11 +51,26 @@ impl<'a> UserRoleHandler<'a> {
     Ok(user_roles)
   }
 
-  pub fn delete(&self, user_id: Uuid, role_id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, user_id: Uuid, role_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting user_role: user_id={}, role_id={}", user_id, role_id);
     debug!("Calling UserRoleRepository to delete user_role: user_id={}, role_id={}", user_id, role_id);
-    self.repo.delete(user_id, role_id)?;
+    self.repo.delete(user_id, role_id, context)?;
     info!("UserRole deleted successfully: user_id={}, role_id={}", user_id, role_id);
     Ok(())
   }
+
+  // Archives assignments that expired by `now` and announces those that became valid since the
+  // previous sweep at `since`.
+  pub fn sweep(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> Result<(), AppError> {
+    debug!("Sweeping user_roles between {} and {}", since, now);
+    let expired = self.repo.archive_expired(now)?;
+    for user_role in &expired {
+      info!("UserRole expired: user_id={}, role_id={}", user_role.user_id, user_role.role_id);
+    }
+    let activated = self.repo.announce_activated(since, now)?;
+    for user_role in &activated {
+      info!("UserRole activated: user_id={}, role_id={}", user_role.user_id, user_role.role_id);
+    }
+    Ok(())
+  }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+use crate::config::Webhooks;
+use crate::database::PgPool;
+use crate::models::audit::AuditContext;
+use crate::models::webhook::{NewWebhook, UpdateWebhook, Webhook, WebhookDeliveryResponse};
+use crate::repositories::webhook::WebhookRepository;
+use crate::utilities::encryption::Encryption;
+use crate::utilities::error::AppError;
+use crate::utilities::events::EVENT_TYPES;
+use crate::utilities::webhooks;
+use log::{debug, info, warn};
+use uuid::Uuid;
+use chrono::Utc;
+
+const SECRET_LENGTH: usize = 32;
+const FAN_OUT_BATCH_SIZE: i64 = 500;
+const DELIVERY_BATCH_SIZE: i64 = 20;
+
+pub struct WebhookHandler<'a> {
+  repo: WebhookRepository<'a>,
+}
+
+impl<'a> WebhookHandler<'a> {
+  pub fn new(pool: &'a PgPool) -> Self {
+    debug!("Creating WebhookHandler");
+    Self {
+      repo: WebhookRepository::new(pool),
+    }
+  }
+
+  // Without a secret one is generated; either way it is only returned here.
+  pub fn create(&self, url: &str, secret: Option<&str>, event_types: &[String], active: bool, context: &AuditContext) -> Result<Webhook, AppError> {
+    info!("Creating webhook: {}", url);
+    validate_event_types(event_types)?;
+    let secret = secret.map(str::to_string).unwrap_or_else(|| Encryption::generate_secret(SECRET_LENGTH));
+    let new_webhook = NewWebhook {
+      url,
+      secret: &secret,
+      event_types,
+      active,
+    };
+    debug!("Calling WebhookRepository to create webhook: {}", url);
+    let webhook = self.repo.create(new_webhook, context)?;
+    info!("Webhook created successfully: {}", webhook.id);
+    Ok(webhook)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<Webhook>, AppError> {
+    info!("Listing webhooks");
+    debug!("Calling WebhookRepository to list webhooks");
+    let webhooks = self.repo.find_all()?;
+    info!("Found {} webhooks", webhooks.len());
+    Ok(webhooks)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<Webhook, AppError> {
+    info!("Looking up webhook by ID: {}", id);
+    debug!("Calling WebhookRepository to find webhook ID: {}", id);
+    let webhook = self.repo.find_by_id(id)?;
+    info!("Found webhook by ID: {}", id);
+    Ok(webhook)
+  }
+
+  pub fn update(&self, id: Uuid, url: Option<&str>, secret: Option<&str>, event_types: Option<&[String]>, active: Option<bool>, context: &AuditContext) -> Result<Webhook, AppError> {
+    info!("Updating webhook: {}", id);
+    if let Some(event_types) = event_types {
+      validate_event_types(event_types)?;
+    }
+    let update_webhook = UpdateWebhook {
+      url,
+      secret,
+      event_types,
+      active,
+      updated_at: Utc::now(),
+    };
+    debug!("Calling WebhookRepository to update webhook: {}", id);
+    let webhook = self.repo.update(id, update_webhook, context)?;
+    info!("Webhook updated successfully: {}", id);
+    Ok(webhook)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting webhook: {}", id);
+    debug!("Calling WebhookRepository to delete webhook: {}", id);
+    self.repo.delete(id, context)?;
+    info!("Webhook deleted successfully: {}", id);
+    Ok(())
+  }
+
+  pub fn dead_letters(&self, webhook_id: Option<Uuid>) -> Result<Vec<WebhookDeliveryResponse>, AppError> {
+    info!("Listing dead webhook deliveries (webhook_id={:?})", webhook_id);
+    let deliveries = self.repo.find_dead(webhook_id)?;
+    Ok(deliveries.into_iter().map(WebhookDeliveryResponse::from).collect())
+  }
+
+  pub fn retry(&self, delivery_id: Uuid) -> Result<WebhookDeliveryResponse, AppError> {
+    info!("Retrying webhook delivery: {}", delivery_id);
+    let delivery = self.repo.retry(delivery_id)?;
+    Ok(WebhookDeliveryResponse::from(delivery))
+  }
+
+  // One dispatcher pass: fan out new outbox events, then send the deliveries that are due.
+  // Returns how many deliveries were attempted.
+  pub fn dispatch(&self, settings: &Webhooks, agent: &ureq::Agent) -> Result<usize, AppError> {
+    self.repo.fan_out(FAN_OUT_BATCH_SIZE)?;
+    // Deliveries are sent one after another, so the lease covers a batch of timeouts.
+    let lease = chrono::Duration::seconds(settings.timeout_seconds as i64 * (DELIVERY_BATCH_SIZE + 1));
+    let due = self.repo.claim_due(DELIVERY_BATCH_SIZE, lease)?;
+    for delivery in &due {
+      let id = delivery.delivery.id;
+      match webhooks::deliver(agent, delivery) {
+        Ok(status) => {
+          debug!("Delivered webhook delivery {} to {} ({})", id, delivery.webhook.url, status);
+          self.repo.record_success(id, i32::from(status))?;
+        }
+        Err((status, error)) => {
+          let attempts = delivery.delivery.attempts + 1;
+          let next_attempt_at = (attempts < settings.max_attempts).then(|| Utc::now() + webhooks::retry_delay(settings.retry_base_seconds, attempts));
+          if next_attempt_at.is_none() {
+            warn!("Webhook delivery {} to {} is dead after {} attempts: {}", id, delivery.webhook.url, attempts, error);
+          } else {
+            debug!("Webhook delivery {} to {} failed (attempt {}): {}", id, delivery.webhook.url, attempts, error);
+          }
+          self.repo.record_failure(id, status.map(i32::from), &error, next_attempt_at)?;
+        }
+      }
+    }
+    if !due.is_empty() {
+      info!("Attempted {} webhook deliveries", due.len());
+    }
+    Ok(due.len())
+  }
+}
+
+fn validate_event_types(event_types: &[String]) -> Result<(), AppError> {
+  if let Some(unknown) = event_types.iter().find(|t| !EVENT_TYPES.contains(&t.as_str())) {
+    return Err(AppError::BadRequest(format!(
+      "Unknown event type '{}'; expected one of {}",
+      unknown,
+      EVENT_TYPES.join(", ")
+    )));
+  }
+  Ok(())
+}
\ No newline at end of file
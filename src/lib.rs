@@ -0,0 +1,9 @@
+pub mod config;
+pub mod database;
+pub mod handlers;
+pub mod middlewares;
+pub mod models;
+pub mod repositories;
+pub mod routes;
+pub mod schema;
+pub mod utilities;
\ No newline at end of file
@@ -1,23 +1,44 @@
-mod config;
-mod database;
-mod handlers;
-mod middlewares;
-mod models;
-mod repositories;
-mod routes;
-mod schema;
-mod utilities;
-
 use actix_web::{App, web, HttpServer};
-use config::Config;
-use database::DatabasePool;
+use rust_authen_service::config::{Config, DecisionBackend};
+use rust_authen_service::database::DatabasePool;
+use rust_authen_service::models::namespace::NamespaceConfig;
+use rust_authen_service::{handlers, routes, utilities};
+use log::warn;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
   dotenvy::dotenv().ok();
   env_logger::init();
+  // `--migrate-only` applies pending migrations and exits, for release pipelines. It only needs
+  // DATABASE_URL, so it runs before the rest of the configuration is loaded.
+  if std::env::args().skip(1).any(|arg| arg == "--migrate-only") {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = DatabasePool::new(&database_url);
+    let applied = DatabasePool::run_pending_migrations(&pool).expect("Failed to run database migrations");
+    println!("Applied {} pending migrations", applied.len());
+    return Ok(());
+  }
   let config = Config::load().unwrap();  // ✅ Config loads successfully
   let pool = DatabasePool::new(&config.database.url);
+  if config.database.run_migrations {
+    let applied = DatabasePool::run_pending_migrations(&pool).expect("Failed to run database migrations");
+    println!("Applied {} pending migrations", applied.len());
+  }
+  handlers::bootstrap::BootstrapHandler::new(&pool, config.bootstrap.clone()).run().expect("Failed to bootstrap the database");
+  utilities::permission_cache::spawn_listener(config.database.url.clone());
+  utilities::event_stream::spawn_heartbeat();
+  utilities::role_expiry::spawn_sweeper(pool.clone(), &config);
+  utilities::audit_checkpoint::spawn_checkpointer(pool.clone(), &config);
+  utilities::webhooks::spawn_dispatcher(pool.clone(), &config);
+  let namespaces = web::Data::new(
+    NamespaceConfig::load(&config.relations.namespace_config_path).expect("Failed to load namespace config"),
+  );
+  if config.auth.decision_backend == DecisionBackend::Policy {
+    let policies = handlers::policy::load_directory(&config.auth.policy_directory).expect("Failed to load policy files");
+    if policies.is_empty() {
+      warn!("Policy decision backend enabled without policy files; requests are only permitted by database policies");
+    }
+  }
 
   println!("Server starting at {}:{}", config.server.host, config.server.port);
 
@@ -29,6 +50,7 @@ async fn main() -> std::io::Result<()> {
     App::new()
       .app_data(web::Data::new(pool.clone()))
       .app_data(web::Data::new(config_for_app.clone()))
+      .app_data(namespaces.clone())
       .configure(routes::configure)
   })
   .bind((config.server.host, config.server.port))?
@@ -1,19 +1,133 @@
-use actix_web::{web, dev::{Service, ServiceRequest, ServiceResponse, Transform}, Error, HttpMessage};
+use actix_web::{web, dev::{Service, ServiceRequest, ServiceResponse, Transform}, Error, HttpMessage, HttpRequest};
 use futures::future::{self, LocalBoxFuture, Ready};
+use crate::config::{Auth, AuthorizationMode};
 use crate::database::PgPool;
 use crate::utilities::error::AppError;
-use jsonwebtoken::{decode, DecodingKey, Validation};
+use crate::repositories::session::SessionRepository;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 use log::{info, debug, error};
-use crate::schema::{user_roles, roles, role_permissions, permissions};
-use diesel::prelude::*;
+use crate::handlers::authorization::AuthorizationHandler;
+use crate::models::authorization::ResourceRef;
+use crate::models::condition::RequestContext;
+use crate::models::audit::AuditContext;
+use std::net::{IpAddr, SocketAddr};
+use crate::utilities::permission_matcher;
 use uuid::Uuid;
+use chrono::{DateTime, Utc};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Claims {
+  pub iss: String,
   pub sub: String,
+  pub aud: String,
   pub exp: usize,
+  pub nbf: usize,
+  pub iat: usize,
+  pub jti: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub scope: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub act: Option<ActorClaim>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub roles: Option<Vec<String>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub permissions: Option<Vec<String>>,
+}
+
+// RFC 8693 actor claim; nested when a delegated token is exchanged again.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActorClaim {
+  pub sub: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub act: Option<Box<ActorClaim>>,
+}
+
+impl Claims {
+  pub fn new(auth: &Auth, sub: String, aud: String, jti: String, expires_at: DateTime<Utc>) -> Self {
+    let now = Utc::now().timestamp() as usize;
+    Claims {
+      iss: auth.issuer.clone(),
+      sub,
+      aud,
+      exp: expires_at.timestamp() as usize,
+      nbf: now,
+      iat: now,
+      jti,
+      scope: None,
+      act: None,
+      roles: None,
+      permissions: None,
+    }
+  }
+
+  // Tokens without a scope claim carry the full permissions of their subject.
+  pub fn scopes(&self) -> Option<Vec<&str>> {
+    self.scope.as_deref().map(|s| s.split_whitespace().collect())
+  }
+
+  pub fn has_scope(&self, scope: &str) -> bool {
+    match self.scopes() {
+      Some(scopes) => permission_matcher::any_matches(scopes, scope),
+      None => true,
+    }
+  }
+}
+
+pub fn encode_token(claims: &Claims, jwt_secret: &str) -> Result<String, AppError> {
+  encode(
+    &Header::default(),
+    claims,
+    &EncodingKey::from_secret(jwt_secret.as_ref()),
+  )
+  .map_err(|e| {
+    error!("Failed to generate JWT for subject {}: {:?}", claims.sub, e);
+    AppError::JwtError(format!("Failed to generate JWT: {}", e))
+  })
+}
+
+// Decodes a token issued by this service. When `audience` is None the token may target any
+// audience known to the configuration, which is what introspection and token exchange need.
+pub fn decode_token(token: &str, auth: &Auth, audience: Option<&str>) -> Result<TokenData<Claims>, AppError> {
+  let mut validation = Validation::default();
+  validation.set_issuer(&[auth.issuer.as_str()]);
+  match audience {
+    Some(audience) => validation.set_audience(&[audience]),
+    None => validation.set_audience(&auth.known_audiences()),
+  }
+  validation.validate_nbf = true;
+  validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud", "sub"]);
+  decode::<Claims>(
+    token,
+    &DecodingKey::from_secret(auth.jwt_secret.as_ref()),
+    &validation,
+  )
+  .map_err(|e| {
+    error!("Token validation failed: {}", e);
+    AppError::Unauthorized("Invalid or expired token".into())
+  })
+}
+
+pub fn check_session_active(pool: &PgPool, claims: &Claims) -> Result<(), AppError> {
+  let session_id = Uuid::parse_str(&claims.jti).map_err(|e| {
+    error!("Invalid jti in token: {}", e);
+    AppError::Unauthorized("Invalid token ID".into())
+  })?;
+  let session = match SessionRepository::new(pool).find_by_id(session_id) {
+    Ok(session) => session,
+    Err(AppError::NotFound(_)) => {
+      error!("No session found for jti={}", session_id);
+      return Err(AppError::Unauthorized("Unknown session".into()));
+    }
+    Err(e) => return Err(e),
+  };
+  if session.is_active() {
+    Ok(())
+  } else {
+    error!("Session {} has been revoked or expired", session_id);
+    Err(AppError::Unauthorized("Token has been revoked".into()))
+  }
 }
 
 pub struct JwtMiddleware;
@@ -98,20 +212,12 @@ where
       },
     };
 
-    let validation = Validation::default();
-    let token_data = match decode::<Claims>(
-      token,
-      &DecodingKey::from_secret(config.auth.jwt_secret.as_ref()),
-      &validation,
-    ) {
+    let token_data = match decode_token(token, &config.auth, Some(&config.auth.audience)) {
       Ok(data) => {
         debug!("Token decoded successfully, user_id: {}", data.claims.sub);
         data
       },
-      Err(e) => {
-        error!("Token validation failed: {}", e);
-        return Box::pin(future::err(AppError::Unauthorized("Invalid or expired token".into()).into()));
-      },
+      Err(e) => return Box::pin(future::err(e.into())),
     };
 
     let user_id = match Uuid::parse_str(&token_data.claims.sub) {
@@ -125,7 +231,16 @@ where
       },
     };
 
-    let has_permission = match check_user_permission(&pool, user_id, &req) {
+    let mode = config.auth.authorization_mode;
+    if mode == AuthorizationMode::Stateless && token_data.claims.permissions.is_some() {
+      debug!("Stateless mode: skipping session lookup for user_id={}", user_id);
+    } else if let Err(e) = check_session_active(&pool, &token_data.claims) {
+      error!("Session check failed for user_id={}: {}", user_id, e);
+      return Box::pin(future::err(e.into()));
+    }
+
+    let context = request_context(req.request(), &config.auth);
+    let has_permission = match check_user_permission(&pool, user_id, &token_data.claims, &config.auth, &req, &context) {
       Ok(_) => true,
       Err(e) => {
         error!("Permission check failed for user_id={} on {}: {}", user_id, req.path(), e);
@@ -153,44 +268,163 @@ where
   }
 }
 
-fn check_user_permission(pool: &PgPool, user_id: Uuid, req: &ServiceRequest) -> Result<(), AppError> {
-  let required_permission = match req.path() {
-    path if path.starts_with("/api/user_roles") => match req.method().as_str() {
-      "POST" => Some("admin.create_user_role"),
-      "DELETE" => Some("admin.delete_user_role"),
+pub fn request_context(req: &HttpRequest, auth: &Auth) -> RequestContext {
+  let client_ip = if auth.trust_forwarded_for {
+    req.connection_info().realip_remote_addr().and_then(|addr| {
+      addr.parse::<IpAddr>().ok().or_else(|| addr.parse::<SocketAddr>().ok().map(|a| a.ip()))
+    })
+  } else {
+    req.peer_addr().map(|addr| addr.ip())
+  };
+  debug!("Request context client_ip={:?}", client_ip);
+  RequestContext::new(client_ip)
+}
+
+// Request ids from a proxy are kept so audit records can be correlated with its logs.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LENGTH: usize = 255;
+
+// Audit details of a request; the actor is only known on routes behind JwtMiddleware.
+pub fn audit_context(req: &HttpRequest, auth: &Auth) -> AuditContext {
+  let claims = req.extensions().get::<Claims>().cloned();
+  let request_id = req
+    .headers()
+    .get(REQUEST_ID_HEADER)
+    .and_then(|v| v.to_str().ok())
+    .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH)
+    .map(str::to_string)
+    .unwrap_or_else(|| Uuid::new_v4().to_string());
+  AuditContext {
+    actor_id: claims.as_ref().and_then(|c| Uuid::parse_str(&c.sub).ok()),
+    actor: claims.and_then(|c| c.act).map(|act| act.sub),
+    ip: request_context(req, auth).client_ip.map(|ip| ip.to_string()),
+    request_id: Some(request_id),
+  }
+}
+
+// The permission a request needs and, when the route targets a single resource, that resource.
+fn required_permission(req: &ServiceRequest) -> Option<(&'static str, Option<ResourceRef>)> {
+  let path = req.path();
+  let method = req.method().as_str();
+  match path {
+    path if path.starts_with("/api/user_roles") => match method {
+      "POST" => Some(("admin.create_user_role", None)),
+      // DELETE /api/user_roles/{user_id}/{role_id}: scoped to the role being revoked.
+      "DELETE" => Some(("admin.delete_user_role", path.rsplit('/').next().map(|role_id| ResourceRef::new("role", role_id)))),
+      _ => None,
+    },
+    path if path.starts_with("/api/resource_grants") => match method {
+      "POST" => Some(("admin.create_resource_grant", None)),
+      "DELETE" => Some(("admin.delete_resource_grant", None)),
+      _ => None,
+    },
+    "/api/oauth/introspect" => match method {
+      "POST" => Some(("oauth.introspect", None)),
+      _ => None,
+    },
+    "/api/oauth/token" => match method {
+      "POST" => Some(("oauth.token_exchange", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/relations/tuples") => match method {
+      "POST" | "DELETE" => Some(("relations.write", None)),
+      "GET" => Some(("relations.read", None)),
+      _ => None,
+    },
+    "/api/relations/check" | "/api/relations/expand" | "/api/relations/list_objects" => match method {
+      "POST" => Some(("relations.read", None)),
+      _ => None,
+    },
+    "/api/authorization/check" => match method {
+      "POST" => Some(("authorization.check", None)),
+      _ => None,
+    },
+    // Approvers are checked against the requested role by the handler; requesters may list and
+    // cancel their own requests.
+    path if path.starts_with("/api/elevations") => match method {
+      "POST" if path == "/api/elevations" => Some(("elevation.request", None)),
+      "GET" if path != "/api/elevations/mine" => Some(("elevation.view", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/sod_constraints") => match method {
+      "POST" => Some(("admin.create_sod_constraint", None)),
+      "GET" => Some(("admin.view_sod_constraint", None)),
+      "DELETE" => Some(("admin.delete_sod_constraint", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/users/") && path.ends_with("/attributes") => match method {
+      "PUT" => Some(("admin.update_user_attributes", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/users/") && path.ends_with("/effective_permissions") => match method {
+      "GET" => Some(("admin.view_effective_permissions", None)),
+      _ => None,
+    },
+    // /api/users/{id}: updating another user includes resetting their password.
+    path if path.starts_with("/api/users/") && path.matches('/').count() == 3 => match method {
+      "PUT" => Some(("admin.update_user", None)),
+      "DELETE" => Some(("admin.delete_user", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/groups") => match method {
+      "POST" if path == "/api/groups" => Some(("admin.create_group", None)),
+      "PUT" => Some(("admin.update_group", None)),
+      "DELETE" if path.matches('/').count() == 3 => Some(("admin.delete_group", None)),
+      // /api/groups/{id}/roles[/{role_id}]
+      "POST" | "DELETE" if path.split('/').nth(4) == Some("roles") => Some(("admin.assign_group_role", None)),
+      "POST" | "DELETE" => Some(("admin.manage_group_members", None)),
+      "GET" => Some(("admin.view_group", None)),
+      _ => None,
+    },
+    "/api/rbac/export" => match method {
+      "GET" => Some(("admin.export_rbac", None)),
+      _ => None,
+    },
+    "/api/rbac/import" => match method {
+      "POST" => Some(("admin.import_rbac", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/audit_events") => match method {
+      "GET" => Some(("admin.view_audit_log", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/permission_events") => match method {
+      "GET" => Some(("admin.stream_permission_events", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/webhooks/deliveries/") && path.ends_with("/retry") => match method {
+      "POST" => Some(("admin.retry_webhook_delivery", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/webhooks") => match method {
+      "POST" => Some(("admin.create_webhook", None)),
+      "GET" => Some(("admin.view_webhook", None)),
+      "PUT" => Some(("admin.update_webhook", None)),
+      "DELETE" => Some(("admin.delete_webhook", None)),
+      _ => None,
+    },
+    path if path.starts_with("/api/policies") => match method {
+      "POST" => Some(("admin.create_policy", None)),
+      "GET" => Some(("admin.view_policy", None)),
+      "PUT" => Some(("admin.update_policy", None)),
+      "DELETE" => Some(("admin.delete_policy", None)),
       _ => None,
     },
     // Add other routes here as needed
     _ => None,
-  };
+  }
+}
 
-  if let Some(permission_name) = required_permission {
-    debug!("Checking permission {} for user_id={}", permission_name, user_id);
-    let mut conn = pool.get().map_err(|e| {
-      error!("Failed to get database connection: {}", e);
-      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
-    })?;
-    let has_permission = user_roles::table
-      .inner_join(roles::table)
-      .inner_join(role_permissions::table.on(role_permissions::role_id.eq(roles::id)))
-      .inner_join(permissions::table.on(permissions::id.eq(role_permissions::permission_id)))
-      .filter(user_roles::user_id.eq(user_id))
-      .filter(permissions::name.eq(permission_name))
-      .select(permissions::name)
-      .first::<String>(&mut conn)
-      .optional()
-      .map_err(|e| {
-        error!("Failed to check permission {} for user_id={}: {:?}", permission_name, user_id, e);
-        AppError::from(e)
-      })?
-      .is_some();
-
-    if has_permission {
-      info!("User {} has permission {}", user_id, permission_name);
+fn check_user_permission(pool: &PgPool, user_id: Uuid, claims: &Claims, auth: &Auth, req: &ServiceRequest, context: &RequestContext) -> Result<(), AppError> {
+  if let Some((permission_name, resource)) = required_permission(req) {
+    debug!("Checking permission {} on {:?} for user_id={}", permission_name, resource, user_id);
+    let decision = AuthorizationHandler::new(pool, auth.clone()).check_token(user_id, claims, permission_name, resource.as_ref(), context)?;
+    if decision.allowed {
+      info!("User {} has permission {}: {}", user_id, permission_name, decision.reason);
       Ok(())
     } else {
-      error!("User {} lacks permission {}", user_id, permission_name);
-      Err(AppError::Forbidden)
+      error!("User {} lacks permission {}: {}", user_id, permission_name, decision.reason);
+      Err(AppError::AccessDenied(format!("{} ({})", permission_name, decision.reason)))
     }
   } else {
     debug!("No specific permission required for {}", req.path());
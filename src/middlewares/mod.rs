@@ -1 +1,2 @@
-pub mod jwt;
\ No newline at end of file
+pub mod jwt;
+pub mod scope;
\ No newline at end of file
@@ -0,0 +1,71 @@
+use actix_web::{dev::{Service, ServiceRequest, ServiceResponse, Transform}, Error, HttpMessage};
+use futures::future::{self, LocalBoxFuture, Ready};
+use crate::middlewares::jwt::Claims;
+use crate::utilities::error::AppError;
+use std::rc::Rc;
+use log::{debug, error};
+
+// Requires the token placed in the request extensions by JwtMiddleware to carry `scope`.
+// It must therefore be registered inside a scope or resource wrapped by JwtMiddleware.
+pub struct RequireScope {
+  scope: &'static str,
+}
+
+impl RequireScope {
+  pub fn new(scope: &'static str) -> Self {
+    RequireScope { scope }
+  }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireScope
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type InitError = ();
+  type Transform = RequireScopeService<S>;
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    debug!("Initializing RequireScope for scope {}", self.scope);
+    future::ok(RequireScopeService { service: Rc::new(service), scope: self.scope })
+  }
+}
+
+pub struct RequireScopeService<S> {
+  service: Rc<S>,
+  scope: &'static str,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireScopeService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  actix_web::dev::forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let has_scope = match req.extensions().get::<Claims>() {
+      Some(claims) => claims.has_scope(self.scope),
+      None => {
+        error!("RequireScope used without JwtMiddleware on {}", req.path());
+        return Box::pin(future::err(AppError::Unauthorized("Missing token claims".into()).into()));
+      }
+    };
+    if !has_scope {
+      error!("Token is missing required scope {} for {}", self.scope, req.path());
+      return Box::pin(future::err(AppError::Forbidden.into()));
+    }
+    debug!("Token carries required scope {} for {}", self.scope, req.path());
+    let service = Rc::clone(&self.service);
+    Box::pin(async move { service.call(req).await })
+  }
+}
\ No newline at end of file
@@ -0,0 +1,271 @@
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+use chrono::{DateTime, SecondsFormat, Utc};
+use crate::schema::{audit_checkpoints, audit_events};
+use crate::utilities::audit_chain;
+
+pub const ACTION_LOGIN: &str = "auth.login";
+pub const ACTION_LOGIN_FAILED: &str = "auth.login_failed";
+pub const ACTION_TOKEN_EXCHANGE: &str = "auth.token_exchange";
+pub const ACTION_PASSWORD_CHANGE: &str = "user.password_change";
+pub const ACTION_IMPORT: &str = "rbac.import";
+
+pub const TARGET_USER: &str = "user";
+pub const TARGET_ROLE: &str = "role";
+pub const TARGET_PERMISSION: &str = "permission";
+pub const TARGET_USER_ROLE: &str = "user_role";
+pub const TARGET_ROLE_PERMISSION: &str = "role_permission";
+pub const TARGET_RBAC: &str = "rbac";
+pub const TARGET_WEBHOOK: &str = "webhook";
+pub const TARGET_GROUP: &str = "group";
+pub const TARGET_GROUP_USER: &str = "group_user";
+pub const TARGET_GROUP_SUBGROUP: &str = "group_subgroup";
+pub const TARGET_GROUP_ROLE: &str = "group_role";
+pub const TARGET_RESOURCE_GRANT: &str = "resource_grant";
+pub const TARGET_SOD_CONSTRAINT: &str = "sod_constraint";
+pub const TARGET_POLICY: &str = "policy";
+pub const TARGET_RELATION_TUPLE: &str = "relation_tuple";
+
+pub const MAX_PAGE_SIZE: i64 = 1000;
+
+// Who caused an audited event and the request it came from. `actor_id` is the authenticated
+// user; `actor` names whoever acted without one (authctl, a login attempt) or on their behalf
+// (the `act` subject of an exchanged token).
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+  pub actor_id: Option<Uuid>,
+  pub actor: Option<String>,
+  pub ip: Option<String>,
+  pub request_id: Option<String>,
+}
+
+impl AuditContext {
+  // Changes made outside of an HTTP request.
+  pub fn system(actor: &str) -> Self {
+    AuditContext {
+      actor: Some(actor.to_string()),
+      ..Default::default()
+    }
+  }
+}
+
+// What happened; `before` and `after` hold the target as the API would return it.
+pub struct AuditRecord {
+  pub action: String,
+  pub target_type: &'static str,
+  pub target_id: Option<String>,
+  pub before: Option<Value>,
+  pub after: Option<Value>,
+}
+
+impl AuditRecord {
+  pub fn new(action: &str, target_type: &'static str, target_id: Option<String>) -> Self {
+    AuditRecord {
+      action: action.to_string(),
+      target_type,
+      target_id,
+      before: None,
+      after: None,
+    }
+  }
+
+  pub fn created<T: Serialize>(target_type: &'static str, target_id: impl ToString, after: &T) -> Self {
+    AuditRecord {
+      after: snapshot(after),
+      ..Self::new(&format!("{}.create", target_type), target_type, Some(target_id.to_string()))
+    }
+  }
+
+  pub fn updated<B: Serialize, A: Serialize>(target_type: &'static str, target_id: impl ToString, before: &B, after: &A) -> Self {
+    AuditRecord {
+      before: snapshot(before),
+      after: snapshot(after),
+      ..Self::new(&format!("{}.update", target_type), target_type, Some(target_id.to_string()))
+    }
+  }
+
+  pub fn deleted<T: Serialize>(target_type: &'static str, target_id: impl ToString, before: &T) -> Self {
+    AuditRecord {
+      before: snapshot(before),
+      ..Self::new(&format!("{}.delete", target_type), target_type, Some(target_id.to_string()))
+    }
+  }
+}
+
+fn snapshot<T: Serialize>(value: &T) -> Option<Value> {
+  serde_json::to_value(value).ok()
+}
+
+// Chained events have `prev_hash` and `hash` set; events recorded before chaining was
+// introduced have neither.
+#[derive(Queryable, Insertable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = audit_events)]
+pub struct AuditEvent {
+  pub id: Uuid,
+  pub actor_id: Option<Uuid>,
+  pub actor: Option<String>,
+  pub action: String,
+  pub target_type: String,
+  pub target_id: Option<String>,
+  pub before: Option<Value>,
+  pub after: Option<Value>,
+  pub ip: Option<String>,
+  pub request_id: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub seq: i64,
+  pub prev_hash: Option<String>,
+  pub hash: Option<String>,
+}
+
+impl AuditEvent {
+  // Hash of everything but the hash itself. `created_at` must already be truncated to the
+  // microseconds Postgres stores.
+  pub fn compute_hash(&self) -> String {
+    let payload = serde_json::json!({
+      "id": self.id,
+      "seq": self.seq,
+      "actor_id": self.actor_id,
+      "actor": self.actor,
+      "action": self.action,
+      "target_type": self.target_type,
+      "target_id": self.target_id,
+      "before": self.before,
+      "after": self.after,
+      "ip": self.ip,
+      "request_id": self.request_id,
+      "created_at": self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+    });
+    audit_chain::hash(self.prev_hash.as_deref().unwrap_or(audit_chain::GENESIS_HASH), &payload)
+  }
+}
+
+// Numbered and chained onto the latest event when it is recorded.
+pub struct NewAuditEvent<'a> {
+  pub actor_id: Option<Uuid>,
+  pub actor: Option<&'a str>,
+  pub action: &'a str,
+  pub target_type: &'a str,
+  pub target_id: Option<&'a str>,
+  pub before: Option<&'a Value>,
+  pub after: Option<&'a Value>,
+  pub ip: Option<&'a str>,
+  pub request_id: Option<&'a str>,
+}
+
+impl<'a> NewAuditEvent<'a> {
+  pub fn new(context: &'a AuditContext, record: &'a AuditRecord) -> Self {
+    NewAuditEvent {
+      actor_id: context.actor_id,
+      actor: context.actor.as_deref(),
+      action: &record.action,
+      target_type: record.target_type,
+      target_id: record.target_id.as_deref(),
+      before: record.before.as_ref(),
+      after: record.after.as_ref(),
+      ip: context.ip.as_deref(),
+      request_id: context.request_id.as_deref(),
+    }
+  }
+}
+
+pub struct AuditFilter {
+  pub actor_id: Option<Uuid>,
+  pub target_type: Option<String>,
+  pub target_id: Option<String>,
+  pub from: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+  pub limit: i64,
+}
+
+#[derive(Serialize)]
+pub struct AuditEventResponse {
+  pub id: Uuid,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub actor_id: Option<Uuid>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub actor: Option<String>,
+  pub action: String,
+  pub target_type: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub target_id: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub before: Option<Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub after: Option<Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ip: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub request_id: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub seq: i64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub prev_hash: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub hash: Option<String>,
+}
+
+impl From<AuditEvent> for AuditEventResponse {
+  fn from(event: AuditEvent) -> Self {
+    AuditEventResponse {
+      id: event.id,
+      actor_id: event.actor_id,
+      actor: event.actor,
+      action: event.action,
+      target_type: event.target_type,
+      target_id: event.target_id,
+      before: event.before,
+      after: event.after,
+      ip: event.ip,
+      request_id: event.request_id,
+      created_at: event.created_at,
+      seq: event.seq,
+      prev_hash: event.prev_hash,
+      hash: event.hash,
+    }
+  }
+}
+
+// Signed hash of the chain up to and including event `seq`.
+#[derive(Queryable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = audit_checkpoints)]
+pub struct AuditCheckpoint {
+  pub id: Uuid,
+  pub seq: i64,
+  pub hash: String,
+  pub signature: String,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = audit_checkpoints)]
+pub struct NewAuditCheckpoint<'a> {
+  pub seq: i64,
+  pub hash: &'a str,
+  pub signature: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrokenLink {
+  pub seq: i64,
+  // Missing when the event itself is gone.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub id: Option<Uuid>,
+  pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ChainVerification {
+  pub valid: bool,
+  pub events_checked: u64,
+  // Recorded before chaining was introduced; they precede the chain and cannot be verified.
+  pub unchained_events: u64,
+  pub checkpoints_checked: u64,
+  // False when no checkpoint secret is configured; checkpoint hashes are still compared.
+  pub signatures_checked: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_seq: Option<i64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub first_broken_link: Option<BrokenLink>,
+}
\ No newline at end of file
@@ -0,0 +1,441 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::models::condition::{Conditions, RequestContext};
+use crate::utilities::permission_matcher;
+use log::error;
+use uuid::Uuid;
+
+pub const EFFECT_ALLOW: &str = "allow";
+pub const EFFECT_DENY: &str = "deny";
+
+// Token claims encode deny grants with this prefix, e.g. `!tickets.delete`, and resource-scoped
+// grants with a suffix, e.g. `tickets.edit@project:42`.
+const DENY_CLAIM_PREFIX: char = '!';
+const RESOURCE_CLAIM_SEPARATOR: char = '@';
+
+// Matches every resource of a type when used as the resource_id of a grant.
+pub const ANY_RESOURCE: &str = "*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Effect {
+  Allow,
+  Deny,
+}
+
+impl Effect {
+  pub fn parse(effect: &str) -> Option<Effect> {
+    match effect {
+      EFFECT_ALLOW => Some(Effect::Allow),
+      EFFECT_DENY => Some(Effect::Deny),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceRef {
+  pub resource_type: String,
+  pub resource_id: String,
+}
+
+impl ResourceRef {
+  pub fn new(resource_type: impl Into<String>, resource_id: impl Into<String>) -> Self {
+    ResourceRef {
+      resource_type: resource_type.into(),
+      resource_id: resource_id.into(),
+    }
+  }
+
+  // Whether a grant bound to this resource applies to `other`.
+  pub fn covers(&self, other: &ResourceRef) -> bool {
+    self.resource_type == other.resource_type
+      && (self.resource_id == ANY_RESOURCE || self.resource_id == other.resource_id)
+  }
+}
+
+impl std::fmt::Display for ResourceRef {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}:{}", self.resource_type, self.resource_id)
+  }
+}
+
+// A permission (possibly a wildcard) granted or denied to a user through one of their roles.
+#[derive(Queryable, Debug, Clone, Serialize)]
+pub struct GrantRow {
+  pub permission: String,
+  pub effect: String,
+  pub conditions: Option<serde_json::Value>,
+  pub role: String,
+}
+
+// Same as GrantRow, for roles assigned through resource_grants.
+#[derive(Queryable, Debug, Clone, Serialize)]
+pub struct ResourceGrantRow {
+  pub permission: String,
+  pub effect: String,
+  pub conditions: Option<serde_json::Value>,
+  pub role: String,
+  pub resource_type: String,
+  pub resource_id: String,
+}
+
+// Same as GrantRow, for roles held through group membership.
+#[derive(Queryable, Debug, Clone, Serialize)]
+pub struct GroupGrantRow {
+  pub permission: String,
+  pub effect: String,
+  pub conditions: Option<serde_json::Value>,
+  pub role: String,
+  pub group: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Grant {
+  pub permission: String,
+  pub effect: Effect,
+  pub role: Option<String>,
+  // The group the role is held through, for group-derived grants.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub group: Option<String>,
+  // None for grants that apply to every resource.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub resource: Option<ResourceRef>,
+  // Raw role_permissions.conditions, parsed as Conditions when evaluated.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub conditions: Option<serde_json::Value>,
+}
+
+// The check constraint on role_permissions.effect only admits the two known values.
+fn parse_effect(effect: &str) -> Effect {
+  Effect::parse(effect).unwrap_or(Effect::Deny)
+}
+
+impl From<GrantRow> for Grant {
+  fn from(row: GrantRow) -> Self {
+    Grant {
+      effect: parse_effect(&row.effect),
+      permission: row.permission,
+      role: Some(row.role),
+      group: None,
+      resource: None,
+      conditions: row.conditions,
+    }
+  }
+}
+
+impl From<GroupGrantRow> for Grant {
+  fn from(row: GroupGrantRow) -> Self {
+    Grant {
+      effect: parse_effect(&row.effect),
+      permission: row.permission,
+      role: Some(row.role),
+      group: Some(row.group),
+      resource: None,
+      conditions: row.conditions,
+    }
+  }
+}
+
+impl From<ResourceGrantRow> for Grant {
+  fn from(row: ResourceGrantRow) -> Self {
+    Grant {
+      effect: parse_effect(&row.effect),
+      permission: row.permission,
+      role: Some(row.role),
+      group: None,
+      resource: Some(ResourceRef::new(row.resource_type, row.resource_id)),
+      conditions: row.conditions,
+    }
+  }
+}
+
+impl Grant {
+  // Tokens cannot carry conditions, so a stateless token fails closed: conditional allow grants
+  // are left out and conditional deny grants are embedded as unconditional denies.
+  pub fn to_claim(&self) -> Option<String> {
+    if self.conditions.is_some() && self.effect == Effect::Allow {
+      return None;
+    }
+    let mut claim = match self.effect {
+      Effect::Allow => self.permission.clone(),
+      Effect::Deny => format!("{}{}", DENY_CLAIM_PREFIX, self.permission),
+    };
+    if let Some(resource) = &self.resource {
+      claim.push(RESOURCE_CLAIM_SEPARATOR);
+      claim.push_str(&resource.to_string());
+    }
+    Some(claim)
+  }
+
+  pub fn from_claim(claim: &str) -> Grant {
+    let (claim, resource) = match claim.split_once(RESOURCE_CLAIM_SEPARATOR) {
+      Some((claim, resource)) => (claim, resource.split_once(':').map(|(t, id)| ResourceRef::new(t, id))),
+      None => (claim, None),
+    };
+    let (permission, effect) = match claim.strip_prefix(DENY_CLAIM_PREFIX) {
+      Some(permission) => (permission, Effect::Deny),
+      None => (claim, Effect::Allow),
+    };
+    Grant { permission: permission.to_string(), effect, role: None, group: None, resource, conditions: None }
+  }
+
+  pub fn uses_user_attributes(&self) -> bool {
+    self.parsed_conditions().is_some_and(|c| c.is_ok_and(|c| c.uses_user_attributes()))
+  }
+
+  fn parsed_conditions(&self) -> Option<Result<Conditions, String>> {
+    self.conditions
+      .as_ref()
+      .map(|value| serde_json::from_value::<Conditions>(value.clone()).map_err(|e| e.to_string()))
+  }
+
+  // None when the stored conditions are malformed.
+  fn conditions_hold(&self, context: &RequestContext) -> Option<bool> {
+    match self.parsed_conditions() {
+      None => Some(true),
+      Some(Ok(conditions)) => match conditions.holds(context) {
+        Ok(holds) => Some(holds),
+        Err(e) => {
+          error!("Malformed conditions on grant '{}': {}", self.permission, e);
+          None
+        }
+      },
+      Some(Err(e)) => {
+        error!("Malformed conditions on grant '{}': {}", self.permission, e);
+        None
+      }
+    }
+  }
+
+  fn applies_to(&self, permission: &str, resource: Option<&ResourceRef>) -> bool {
+    if !permission_matcher::matches(&self.permission, permission) {
+      return false;
+    }
+    match (&self.resource, resource) {
+      (None, _) => true,
+      (Some(bound), Some(requested)) => bound.covers(requested),
+      (Some(_), None) => false,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Decision {
+  pub allowed: bool,
+  pub permission: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub resource: Option<ResourceRef>,
+  pub reason: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub matched_grant: Option<Grant>,
+  // Set by the policy decision backend to the policy that decided the request.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub policy: Option<String>,
+}
+
+impl Decision {
+  pub fn denied(permission: &str, resource: Option<&ResourceRef>, reason: impl Into<String>) -> Self {
+    Decision {
+      allowed: false,
+      permission: permission.to_string(),
+      resource: resource.cloned(),
+      reason: reason.into(),
+      matched_grant: None,
+      policy: None,
+    }
+  }
+}
+
+// What a user is granted and where it comes from: direct, resource-scoped and group-derived roles.
+#[derive(Debug, Serialize)]
+pub struct EffectivePermissions {
+  pub user_id: Uuid,
+  pub groups: Vec<String>,
+  pub roles: Vec<String>,
+  pub grants: Vec<Grant>,
+}
+
+// Deny-overrides: any matching deny grant wins, otherwise any matching allow grant permits,
+// otherwise the permission is denied by default. Grants bound to a resource only match when
+// that resource is requested; unbound grants match every resource. Conditional grants match
+// when their conditions hold for `context`; malformed conditions fail closed, so such a deny
+// still applies and such an allow does not.
+pub fn evaluate(grants: &[Grant], permission: &str, resource: Option<&ResourceRef>, context: &RequestContext) -> Decision {
+  let deny = grants.iter().find(|g| {
+    g.effect == Effect::Deny && g.applies_to(permission, resource) && g.conditions_hold(context) != Some(false)
+  });
+  let allow = || {
+    grants.iter().find(|g| {
+      g.effect == Effect::Allow && g.applies_to(permission, resource) && g.conditions_hold(context) == Some(true)
+    })
+  };
+  if let Some(grant) = deny {
+    return Decision {
+      allowed: false,
+      permission: permission.to_string(),
+      resource: resource.cloned(),
+      reason: format!("denied by '{}'{}", grant.permission, describe_role(grant)),
+      matched_grant: Some(grant.clone()),
+      policy: None,
+    };
+  }
+  if let Some(grant) = allow() {
+    return Decision {
+      allowed: true,
+      permission: permission.to_string(),
+      resource: resource.cloned(),
+      reason: format!("allowed by '{}'{}", grant.permission, describe_role(grant)),
+      matched_grant: Some(grant.clone()),
+      policy: None,
+    };
+  }
+  Decision::denied(permission, resource, "no matching grant")
+}
+
+// Whether some resource could be allowed `permission`: used to decide which permissions a token
+// may be scoped to, with the resource itself checked on each request.
+pub fn may_allow(grants: &[Grant], permission: &str) -> bool {
+  let denied_everywhere = grants
+    .iter()
+    .any(|g| {
+      g.effect == Effect::Deny
+        && g.resource.is_none()
+        && g.conditions.is_none()
+        && permission_matcher::matches(&g.permission, permission)
+    });
+  !denied_everywhere
+    && grants
+      .iter()
+      .any(|g| g.effect == Effect::Allow && permission_matcher::matches(&g.permission, permission))
+}
+
+fn describe_role(grant: &Grant) -> String {
+  let source = match (grant.role.as_deref(), grant.group.as_deref()) {
+    (Some(role), Some(group)) => format!(" on role '{}' via group '{}'", role, group),
+    (Some(role), None) => format!(" on role '{}'", role),
+    (None, _) => " in token".into(),
+  };
+  let source = match &grant.resource {
+    Some(resource) => format!("{} for {}", source, resource),
+    None => source,
+  };
+  match grant.conditions {
+    Some(_) => format!("{} with conditions", source),
+    None => source,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn grant(permission: &str, effect: Effect) -> Grant {
+    Grant {
+      permission: permission.to_string(),
+      effect,
+      role: Some("support".into()),
+      group: None,
+      resource: None,
+      conditions: None,
+    }
+  }
+
+  fn context(ip: &str) -> RequestContext {
+    RequestContext::new(Some(ip.parse().unwrap()))
+  }
+
+  #[test]
+  fn allow_grants_permit_matching_permissions() {
+    let grants = vec![grant("tickets.*", Effect::Allow)];
+    let decision = evaluate(&grants, "tickets.update", None, &context("10.0.0.1"));
+    assert!(decision.allowed);
+    assert_eq!(decision.reason, "allowed by 'tickets.*' on role 'support'");
+  }
+
+  #[test]
+  fn no_matching_grant_denies_by_default() {
+    let grants = vec![grant("tickets.*", Effect::Allow)];
+    let decision = evaluate(&grants, "billing.read", None, &context("10.0.0.1"));
+    assert!(!decision.allowed);
+    assert_eq!(decision.reason, "no matching grant");
+  }
+
+  #[test]
+  fn deny_overrides_allow_regardless_of_order() {
+    let allow_first = vec![grant("tickets.*", Effect::Allow), grant("tickets.delete", Effect::Deny)];
+    let deny_first = vec![grant("tickets.delete", Effect::Deny), grant("tickets.*", Effect::Allow)];
+    for grants in [allow_first, deny_first] {
+      let decision = evaluate(&grants, "tickets.delete", None, &context("10.0.0.1"));
+      assert!(!decision.allowed);
+      assert_eq!(decision.reason, "denied by 'tickets.delete' on role 'support'");
+      assert!(evaluate(&grants, "tickets.update", None, &context("10.0.0.1")).allowed);
+    }
+  }
+
+  #[test]
+  fn deny_with_failing_condition_does_not_apply() {
+    let mut deny = grant("tickets.delete", Effect::Deny);
+    deny.conditions = Some(json!({ "ip_ranges": ["192.168.0.0/16"] }));
+    let grants = vec![grant("tickets.*", Effect::Allow), deny];
+    assert!(evaluate(&grants, "tickets.delete", None, &context("10.0.0.1")).allowed);
+    assert!(!evaluate(&grants, "tickets.delete", None, &context("192.168.1.1")).allowed);
+  }
+
+  #[test]
+  fn malformed_conditions_fail_closed() {
+    let mut deny = grant("tickets.delete", Effect::Deny);
+    deny.conditions = Some(json!({ "unknown": true }));
+    let grants = vec![grant("tickets.*", Effect::Allow), deny];
+    assert!(!evaluate(&grants, "tickets.delete", None, &context("10.0.0.1")).allowed);
+
+    let mut allow = grant("tickets.*", Effect::Allow);
+    allow.conditions = Some(json!({ "ip_ranges": ["not a range"] }));
+    assert!(!evaluate(&[allow], "tickets.update", None, &context("10.0.0.1")).allowed);
+  }
+
+  #[test]
+  fn allow_with_failing_condition_does_not_permit() {
+    let mut allow = grant("reports.view", Effect::Allow);
+    allow.conditions = Some(json!({ "ip_ranges": ["127.0.0.0/8"] }));
+    let grants = vec![allow];
+    assert!(evaluate(&grants, "reports.view", None, &context("127.0.0.1")).allowed);
+    assert!(!evaluate(&grants, "reports.view", None, &context("10.0.0.1")).allowed);
+  }
+
+  #[test]
+  fn resource_scoped_grants_only_match_their_resource() {
+    let mut allow = grant("projects.edit", Effect::Allow);
+    allow.resource = Some(ResourceRef::new("project", "42"));
+    let grants = vec![allow];
+    let project_42 = ResourceRef::new("project", "42");
+    let project_7 = ResourceRef::new("project", "7");
+    assert!(evaluate(&grants, "projects.edit", Some(&project_42), &context("10.0.0.1")).allowed);
+    assert!(!evaluate(&grants, "projects.edit", Some(&project_7), &context("10.0.0.1")).allowed);
+    assert!(!evaluate(&grants, "projects.edit", None, &context("10.0.0.1")).allowed);
+  }
+
+  #[test]
+  fn any_resource_grants_and_unbound_denies() {
+    let mut allow = grant("projects.edit", Effect::Allow);
+    allow.resource = Some(ResourceRef::new("project", ANY_RESOURCE));
+    let mut deny = grant("projects.edit", Effect::Deny);
+    deny.resource = Some(ResourceRef::new("project", "7"));
+    let grants = vec![allow, deny];
+    assert!(evaluate(&grants, "projects.edit", Some(&ResourceRef::new("project", "42")), &context("10.0.0.1")).allowed);
+    assert!(!evaluate(&grants, "projects.edit", Some(&ResourceRef::new("project", "7")), &context("10.0.0.1")).allowed);
+    assert!(!evaluate(&grants, "projects.edit", Some(&ResourceRef::new("folder", "42")), &context("10.0.0.1")).allowed);
+
+    let grants = vec![grant("projects.edit", Effect::Deny), grant("projects.*", Effect::Allow)];
+    assert!(!evaluate(&grants, "projects.edit", Some(&ResourceRef::new("project", "42")), &context("10.0.0.1")).allowed);
+  }
+
+  #[test]
+  fn may_allow_respects_unconditional_denies() {
+    let grants = vec![grant("tickets.*", Effect::Allow), grant("tickets.delete", Effect::Deny)];
+    assert!(may_allow(&grants, "tickets.update"));
+    assert!(!may_allow(&grants, "tickets.delete"));
+    assert!(!may_allow(&grants, "billing.read"));
+  }
+}
\ No newline at end of file
@@ -0,0 +1,71 @@
+use crate::models::user::User;
+
+pub const ADMIN_ROLE: &str = "admin";
+// Holding this unconditionally is what makes a user an admin for the last-admin safeguard.
+pub const ADMIN_PERMISSION: &str = "admin.*";
+pub const ADMIN_ROLE_DESCRIPTION: &str = "Administrator role with full privileges";
+
+pub struct BuiltinPermission {
+  pub name: &'static str,
+  pub description: &'static str,
+  // Granted to the admin role when it is bootstrapped.
+  pub admin: bool,
+}
+
+const fn permission(name: &'static str, description: &'static str, admin: bool) -> BuiltinPermission {
+  BuiltinPermission { name, description, admin }
+}
+
+// Permissions the service checks itself; created at startup when missing and protected as
+// system permissions.
+pub const BUILTIN_PERMISSIONS: &[BuiltinPermission] = &[
+  permission(ADMIN_PERMISSION, "Grants every admin permission", true),
+  permission("admin.create_user_role", "Allows creating user-role assignments", true),
+  permission("admin.delete_user_role", "Allows deleting user-role assignments", true),
+  permission("admin.view_user_role", "Allows viewing user-role assignments", true),
+  permission("admin.create_role", "Allows creating roles", true),
+  permission("admin.delete_role", "Allows deleting roles", true),
+  permission("admin.create_permission", "Allows creating permissions", true),
+  permission("admin.delete_permission", "Allows deleting permissions", true),
+  permission("admin.create_user", "Allows creating users", true),
+  permission("admin.update_user", "Allows updating users, including resetting their passwords", true),
+  permission("admin.delete_user", "Allows deleting users", true),
+  permission("admin.update_user_attributes", "Allows setting the attributes matched by conditional grants", true),
+  permission("oauth.introspect", "Allows introspecting access tokens", true),
+  permission("oauth.token_exchange", "Allows exchanging user tokens for delegated tokens", true),
+  permission("authorization.check", "Allows checking the effective permissions of users", true),
+  permission("admin.create_resource_grant", "Allows assigning roles on individual resources", true),
+  permission("admin.delete_resource_grant", "Allows revoking roles on individual resources", true),
+  permission("relations.read", "Allows checking, expanding and listing relation tuples", true),
+  permission("relations.write", "Allows writing and deleting relation tuples", true),
+  permission("admin.create_policy", "Allows creating authorization policies", true),
+  permission("admin.view_policy", "Allows viewing authorization policies", true),
+  permission("admin.update_policy", "Allows updating authorization policies", true),
+  permission("admin.delete_policy", "Allows deleting authorization policies", true),
+  permission("elevation.request", "Allows requesting temporary role elevation", false),
+  permission("elevation.view", "Allows viewing all elevation requests", true),
+  permission("elevation.approve", "Allows approving or denying elevation requests", true),
+  permission("admin.create_sod_constraint", "Allows creating separation-of-duties constraints", true),
+  permission("admin.view_sod_constraint", "Allows viewing separation-of-duties constraints and violations", true),
+  permission("admin.delete_sod_constraint", "Allows deleting separation-of-duties constraints", true),
+  permission("admin.create_group", "Allows creating groups", true),
+  permission("admin.view_group", "Allows viewing groups and their members", true),
+  permission("admin.update_group", "Allows updating groups", true),
+  permission("admin.delete_group", "Allows deleting groups", true),
+  permission("admin.manage_group_members", "Allows adding and removing group members", true),
+  permission("admin.assign_group_role", "Allows assigning roles to groups", true),
+  permission("admin.view_effective_permissions", "Allows viewing the effective permissions of users", true),
+  permission("admin.export_rbac", "Allows exporting roles, permissions and assignments as a bundle", true),
+  permission("admin.import_rbac", "Allows importing roles, permissions and assignments from a bundle", true),
+  permission("admin.view_audit_log", "Allows querying the audit log", true),
+  permission("admin.create_webhook", "Allows registering webhook endpoints", true),
+  permission("admin.view_webhook", "Allows viewing webhooks and their dead-lettered deliveries", true),
+  permission("admin.update_webhook", "Allows updating webhook endpoints", true),
+  permission("admin.delete_webhook", "Allows deleting webhook endpoints", true),
+  permission("admin.retry_webhook_delivery", "Allows retrying dead-lettered webhook deliveries", true),
+  permission("admin.stream_permission_events", "Allows streaming permission changes as server-sent events", true),
+];
+
+pub struct BootstrappedAdmin {
+  pub user: User,
+}
\ No newline at end of file
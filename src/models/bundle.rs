@@ -0,0 +1,534 @@
+use crate::models::authorization::{Effect, EFFECT_ALLOW};
+use crate::models::condition::Conditions;
+use crate::utilities::error::AppError;
+use crate::utilities::permission_matcher;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+  Json,
+  Yaml,
+}
+
+impl BundleFormat {
+  pub fn parse(format: &str) -> Option<BundleFormat> {
+    match format.to_ascii_lowercase().as_str() {
+      "json" => Some(BundleFormat::Json),
+      "yaml" | "yml" => Some(BundleFormat::Yaml),
+      _ => None,
+    }
+  }
+
+  pub fn content_type(self) -> &'static str {
+    match self {
+      BundleFormat::Json => "application/json",
+      BundleFormat::Yaml => "application/yaml",
+    }
+  }
+
+  pub fn serialize(self, bundle: &RbacBundle) -> Result<String, AppError> {
+    match self {
+      BundleFormat::Json => serde_json::to_string_pretty(bundle).map_err(|e| AppError::BadRequest(format!("Failed to serialize bundle: {}", e))),
+      BundleFormat::Yaml => serde_yaml::to_string(bundle).map_err(|e| AppError::BadRequest(format!("Failed to serialize bundle: {}", e))),
+    }
+  }
+
+  pub fn deserialize(self, body: &str) -> Result<RbacBundle, AppError> {
+    match self {
+      BundleFormat::Json => serde_json::from_str(body).map_err(|e| AppError::BadRequest(format!("Invalid JSON bundle: {}", e))),
+      BundleFormat::Yaml => serde_yaml::from_str(body).map_err(|e| AppError::BadRequest(format!("Invalid YAML bundle: {}", e))),
+    }
+  }
+}
+
+// The declarative RBAC state. Role permissions are nested under their role and everything is
+// referenced by name so a bundle can be promoted between environments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RbacBundle {
+  pub version: u32,
+  #[serde(default)]
+  pub permissions: Vec<PermissionDefinition>,
+  #[serde(default)]
+  pub roles: Vec<BundleRole>,
+  // Only reconciled when present, so bundles without assignments never touch user_roles.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub user_roles: Option<Vec<BundleUserRole>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PermissionDefinition {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BundleRole {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub permissions: Vec<BundleRolePermission>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BundleRolePermission {
+  pub permission: String,
+  #[serde(default = "default_effect")]
+  pub effect: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub conditions: Option<Conditions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BundleUserRole {
+  pub username: String,
+  pub role: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub valid_from: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub valid_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoleDefinition {
+  pub name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RolePermissionDefinition {
+  pub role: String,
+  pub permission: String,
+  pub effect: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub conditions: Option<Conditions>,
+}
+
+fn default_effect() -> String {
+  EFFECT_ALLOW.to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+  Create,
+  Update,
+  Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Change<T> {
+  pub action: ChangeAction,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub before: Option<T>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub after: Option<T>,
+}
+
+impl<T> Change<T> {
+  fn create(after: T) -> Self {
+    Change { action: ChangeAction::Create, before: None, after: Some(after) }
+  }
+
+  fn update(before: T, after: T) -> Self {
+    Change { action: ChangeAction::Update, before: Some(before), after: Some(after) }
+  }
+
+  fn delete(before: T) -> Self {
+    Change { action: ChangeAction::Delete, before: Some(before), after: None }
+  }
+}
+
+// What an import changes, in the order it is applied.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BundlePlan {
+  pub permissions: Vec<Change<PermissionDefinition>>,
+  pub roles: Vec<Change<RoleDefinition>>,
+  pub role_permissions: Vec<Change<RolePermissionDefinition>>,
+  pub user_roles: Vec<Change<BundleUserRole>>,
+}
+
+impl BundlePlan {
+  pub fn len(&self) -> usize {
+    self.permissions.len() + self.roles.len() + self.role_permissions.len() + self.user_roles.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+  pub dry_run: bool,
+  pub prune: bool,
+  pub changes: usize,
+  #[serde(flatten)]
+  pub plan: BundlePlan,
+}
+
+impl RbacBundle {
+  pub fn validate(&self) -> Result<(), String> {
+    if self.version != BUNDLE_VERSION {
+      return Err(format!("Unsupported bundle version {}, expected {}", self.version, BUNDLE_VERSION));
+    }
+    let mut permissions = BTreeSet::new();
+    for permission in &self.permissions {
+      if permission.name.len() > 50 || !permission_matcher::is_valid_name(&permission.name) {
+        return Err(format!("Invalid permission name '{}'", permission.name));
+      }
+      if !permissions.insert(permission.name.as_str()) {
+        return Err(format!("Permission '{}' is defined more than once", permission.name));
+      }
+    }
+    let mut roles = BTreeSet::new();
+    for role in &self.roles {
+      if role.name.len() < 2 || role.name.len() > 50 {
+        return Err(format!("Role name '{}' must be between 2 and 50 characters", role.name));
+      }
+      if !roles.insert(role.name.as_str()) {
+        return Err(format!("Role '{}' is defined more than once", role.name));
+      }
+      let mut granted = BTreeSet::new();
+      for role_permission in &role.permissions {
+        if !granted.insert(role_permission.permission.as_str()) {
+          return Err(format!("Role '{}' lists permission '{}' more than once", role.name, role_permission.permission));
+        }
+        if Effect::parse(&role_permission.effect).is_none() {
+          return Err(format!("Role '{}' uses invalid effect '{}' for '{}'", role.name, role_permission.effect, role_permission.permission));
+        }
+        if let Some(conditions) = &role_permission.conditions {
+          conditions
+            .validate()
+            .map_err(|e| format!("Role '{}' has invalid conditions for '{}': {}", role.name, role_permission.permission, e))?;
+        }
+      }
+    }
+    let mut assignments = BTreeSet::new();
+    for user_role in self.user_roles.iter().flatten() {
+      if !assignments.insert((user_role.username.as_str(), user_role.role.as_str())) {
+        return Err(format!("Role '{}' is assigned to '{}' more than once", user_role.role, user_role.username));
+      }
+      if let (Some(from), Some(until)) = (user_role.valid_from, user_role.valid_until)
+        && until <= from
+      {
+        return Err(format!("Assignment of '{}' to '{}' ends before it starts", user_role.role, user_role.username));
+      }
+    }
+    Ok(())
+  }
+
+  fn role_permissions(&self) -> BTreeMap<(&str, &str), RolePermissionDefinition> {
+    self
+      .roles
+      .iter()
+      .flat_map(|role| {
+        role.permissions.iter().map(move |rp| {
+          ((role.name.as_str(), rp.permission.as_str()), RolePermissionDefinition {
+            role: role.name.clone(),
+            permission: rp.permission.clone(),
+            effect: rp.effect.clone(),
+            conditions: rp.conditions.clone(),
+          })
+        })
+      })
+      .collect()
+  }
+}
+
+impl From<&BundleRole> for RoleDefinition {
+  fn from(role: &BundleRole) -> Self {
+    RoleDefinition {
+      name: role.name.clone(),
+      description: role.description.clone(),
+    }
+  }
+}
+
+// Diffs the desired bundle against the current state. Without `prune` an import only creates and
+// updates; with it, anything missing from the bundle is deleted.
+pub fn plan(current: &RbacBundle, desired: &RbacBundle, prune: bool) -> Result<BundlePlan, AppError> {
+  let mut plan = BundlePlan::default();
+
+  let existing: BTreeMap<&str, &PermissionDefinition> = current.permissions.iter().map(|p| (p.name.as_str(), p)).collect();
+  for permission in &desired.permissions {
+    match existing.get(permission.name.as_str()) {
+      None => plan.permissions.push(Change::create(permission.clone())),
+      Some(&before) if before != permission => plan.permissions.push(Change::update(before.clone(), permission.clone())),
+      Some(_) => {}
+    }
+  }
+  let wanted: BTreeSet<&str> = desired.permissions.iter().map(|p| p.name.as_str()).collect();
+  if prune {
+    for permission in current.permissions.iter().filter(|p| !wanted.contains(p.name.as_str())) {
+      plan.permissions.push(Change::delete(permission.clone()));
+    }
+  }
+
+  let existing: BTreeMap<&str, &BundleRole> = current.roles.iter().map(|r| (r.name.as_str(), r)).collect();
+  for role in &desired.roles {
+    match existing.get(role.name.as_str()) {
+      None => plan.roles.push(Change::create(RoleDefinition::from(role))),
+      Some(&before) if before.description != role.description => {
+        plan.roles.push(Change::update(RoleDefinition::from(before), RoleDefinition::from(role)))
+      }
+      Some(_) => {}
+    }
+  }
+  let wanted_roles: BTreeSet<&str> = desired.roles.iter().map(|r| r.name.as_str()).collect();
+  if prune {
+    for role in current.roles.iter().filter(|r| !wanted_roles.contains(r.name.as_str())) {
+      plan.roles.push(Change::delete(RoleDefinition::from(role)));
+    }
+  }
+
+  let existing = current.role_permissions();
+  let desired_role_permissions = desired.role_permissions();
+  for (key, role_permission) in &desired_role_permissions {
+    let known = wanted.contains(key.1) || (!prune && current.permissions.iter().any(|p| p.name == key.1));
+    if !known {
+      return Err(AppError::BadRequest(format!("Role '{}' references unknown permission '{}'", key.0, key.1)));
+    }
+    match existing.get(key) {
+      None => plan.role_permissions.push(Change::create(role_permission.clone())),
+      Some(before) if before != role_permission => plan.role_permissions.push(Change::update(before.clone(), role_permission.clone())),
+      Some(_) => {}
+    }
+  }
+  if prune {
+    // Grants of pruned roles or permissions go with them.
+    for (key, role_permission) in &existing {
+      if !desired_role_permissions.contains_key(key) && wanted_roles.contains(key.0) && wanted.contains(key.1) {
+        plan.role_permissions.push(Change::delete(role_permission.clone()));
+      }
+    }
+  }
+
+  if let Some(desired_user_roles) = &desired.user_roles {
+    let existing: BTreeMap<(&str, &str), &BundleUserRole> = current
+      .user_roles
+      .iter()
+      .flatten()
+      .map(|ur| ((ur.username.as_str(), ur.role.as_str()), ur))
+      .collect();
+    for user_role in desired_user_roles {
+      let known = wanted_roles.contains(user_role.role.as_str()) || (!prune && current.roles.iter().any(|r| r.name == user_role.role));
+      if !known {
+        return Err(AppError::BadRequest(format!("Assignment for '{}' references unknown role '{}'", user_role.username, user_role.role)));
+      }
+      match existing.get(&(user_role.username.as_str(), user_role.role.as_str())) {
+        None => plan.user_roles.push(Change::create(user_role.clone())),
+        Some(&before) if before != user_role => plan.user_roles.push(Change::update(before.clone(), user_role.clone())),
+        Some(_) => {}
+      }
+    }
+    if prune {
+      let wanted_user_roles: BTreeSet<(&str, &str)> = desired_user_roles.iter().map(|ur| (ur.username.as_str(), ur.role.as_str())).collect();
+      for (key, &user_role) in &existing {
+        if !wanted_user_roles.contains(key) && wanted_roles.contains(key.1) {
+          plan.user_roles.push(Change::delete(user_role.clone()));
+        }
+      }
+    }
+  }
+
+  Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn bundle(value: serde_json::Value) -> RbacBundle {
+    serde_json::from_value(value).unwrap()
+  }
+
+  fn current() -> RbacBundle {
+    bundle(json!({
+      "version": 1,
+      "permissions": [
+        { "name": "tickets.read", "description": "Read tickets" },
+        { "name": "tickets.update" }
+      ],
+      "roles": [
+        { "name": "support", "permissions": [{ "permission": "tickets.read" }] },
+        { "name": "triage", "permissions": [{ "permission": "tickets.update" }] }
+      ],
+      "user_roles": [
+        { "username": "alice", "role": "support" },
+        { "username": "bob", "role": "triage" }
+      ]
+    }))
+  }
+
+  fn actions<T>(changes: &[Change<T>]) -> Vec<ChangeAction> {
+    changes.iter().map(|change| change.action).collect()
+  }
+
+  #[test]
+  fn identical_bundles_plan_nothing() {
+    for prune in [false, true] {
+      let plan = plan(&current(), &current(), prune).unwrap();
+      assert!(plan.is_empty(), "prune={}: {:?}", prune, plan);
+    }
+  }
+
+  #[test]
+  fn reimporting_an_export_plans_nothing() {
+    let exported = current();
+    for format in [BundleFormat::Json, BundleFormat::Yaml] {
+      let reimported = format.deserialize(&format.serialize(&exported).unwrap()).unwrap();
+      assert!(reimported.validate().is_ok());
+      assert!(plan(&exported, &reimported, true).unwrap().is_empty());
+    }
+  }
+
+  #[test]
+  fn new_entries_are_created() {
+    let mut desired = current();
+    desired.permissions.push(PermissionDefinition { name: "tickets.delete".into(), description: None });
+    desired.roles.push(BundleRole {
+      name: "ops".into(),
+      description: Some("Operations".into()),
+      permissions: vec![BundleRolePermission { permission: "tickets.delete".into(), effect: default_effect(), conditions: None }],
+    });
+    desired.user_roles.as_mut().unwrap().push(BundleUserRole { username: "carol".into(), role: "ops".into(), valid_from: None, valid_until: None });
+    let plan = plan(&current(), &desired, false).unwrap();
+    assert_eq!(actions(&plan.permissions), vec![ChangeAction::Create]);
+    assert_eq!(plan.permissions[0].after.as_ref().unwrap().name, "tickets.delete");
+    assert_eq!(actions(&plan.roles), vec![ChangeAction::Create]);
+    assert_eq!(actions(&plan.role_permissions), vec![ChangeAction::Create]);
+    assert_eq!(plan.role_permissions[0].after.as_ref().unwrap().role, "ops");
+    assert_eq!(actions(&plan.user_roles), vec![ChangeAction::Create]);
+    assert_eq!(plan.user_roles[0].after.as_ref().unwrap().username, "carol");
+  }
+
+  #[test]
+  fn changed_entries_are_updated_with_their_previous_state() {
+    let mut desired = current();
+    desired.permissions[0].description = Some("Read any ticket".into());
+    desired.roles[0].description = Some("First line".into());
+    desired.roles[1].permissions[0].effect = "deny".into();
+    desired.user_roles.as_mut().unwrap()[0].valid_until = Some("2030-01-01T00:00:00Z".parse().unwrap());
+    let plan = plan(&current(), &desired, false).unwrap();
+    assert_eq!(actions(&plan.permissions), vec![ChangeAction::Update]);
+    assert_eq!(plan.permissions[0].before.as_ref().unwrap().description.as_deref(), Some("Read tickets"));
+    assert_eq!(actions(&plan.roles), vec![ChangeAction::Update]);
+    assert_eq!(actions(&plan.role_permissions), vec![ChangeAction::Update]);
+    assert_eq!(plan.role_permissions[0].before.as_ref().unwrap().effect, "allow");
+    assert_eq!(plan.role_permissions[0].after.as_ref().unwrap().effect, "deny");
+    assert_eq!(actions(&plan.user_roles), vec![ChangeAction::Update]);
+  }
+
+  #[test]
+  fn missing_entries_are_kept_without_prune() {
+    let desired = bundle(json!({
+      "version": 1,
+      "roles": [{ "name": "support", "permissions": [{ "permission": "tickets.read" }] }],
+      "user_roles": []
+    }));
+    assert!(plan(&current(), &desired, false).unwrap().is_empty());
+  }
+
+  #[test]
+  fn missing_entries_are_deleted_with_prune() {
+    let desired = bundle(json!({
+      "version": 1,
+      "permissions": [
+        { "name": "tickets.read", "description": "Read tickets" },
+        { "name": "tickets.update" }
+      ],
+      "roles": [
+        { "name": "support", "permissions": [] },
+        { "name": "triage", "permissions": [{ "permission": "tickets.update" }] }
+      ],
+      "user_roles": [{ "username": "bob", "role": "triage" }]
+    }));
+    let plan = plan(&current(), &desired, true).unwrap();
+    assert!(plan.permissions.is_empty());
+    assert!(plan.roles.is_empty());
+    assert_eq!(actions(&plan.role_permissions), vec![ChangeAction::Delete]);
+    assert_eq!(plan.role_permissions[0].before.as_ref().unwrap().permission, "tickets.read");
+    assert_eq!(actions(&plan.user_roles), vec![ChangeAction::Delete]);
+    assert_eq!(plan.user_roles[0].before.as_ref().unwrap().username, "alice");
+  }
+
+  #[test]
+  fn pruned_roles_take_their_grants_and_assignments_with_them() {
+    let desired = bundle(json!({
+      "version": 1,
+      "permissions": [{ "name": "tickets.read", "description": "Read tickets" }],
+      "roles": [{ "name": "support", "permissions": [{ "permission": "tickets.read" }] }],
+      "user_roles": [{ "username": "alice", "role": "support" }]
+    }));
+    let plan = plan(&current(), &desired, true).unwrap();
+    assert_eq!(actions(&plan.permissions), vec![ChangeAction::Delete]);
+    assert_eq!(actions(&plan.roles), vec![ChangeAction::Delete]);
+    assert!(plan.role_permissions.is_empty());
+    assert!(plan.user_roles.is_empty());
+  }
+
+  #[test]
+  fn bundles_without_user_roles_leave_assignments_alone() {
+    let mut desired = current();
+    desired.user_roles = None;
+    assert!(plan(&current(), &desired, true).unwrap().user_roles.is_empty());
+  }
+
+  #[test]
+  fn references_must_resolve_in_the_resulting_state() {
+    let desired = bundle(json!({
+      "version": 1,
+      "roles": [{ "name": "support", "permissions": [{ "permission": "tickets.read" }] }],
+      "user_roles": [{ "username": "bob", "role": "triage" }]
+    }));
+    assert!(plan(&current(), &desired, false).is_ok());
+    // With prune the permission and role are deleted, so nothing may still refer to them.
+    assert!(matches!(plan(&current(), &desired, true), Err(AppError::BadRequest(_))));
+    let unknown = bundle(json!({
+      "version": 1,
+      "roles": [{ "name": "support", "permissions": [{ "permission": "billing.read" }] }]
+    }));
+    assert!(matches!(plan(&current(), &unknown, false), Err(AppError::BadRequest(_))));
+  }
+
+  #[test]
+  fn validate_accepts_a_well_formed_bundle() {
+    assert!(current().validate().is_ok());
+  }
+
+  #[test]
+  fn validate_rejects_malformed_bundles() {
+    let cases = [
+      json!({ "version": 2 }),
+      json!({ "version": 1, "permissions": [{ "name": "Tickets Read" }] }),
+      json!({ "version": 1, "permissions": [{ "name": "tickets.read" }, { "name": "tickets.read" }] }),
+      json!({ "version": 1, "roles": [{ "name": "x" }] }),
+      json!({ "version": 1, "roles": [{ "name": "support" }, { "name": "support" }] }),
+      json!({ "version": 1, "roles": [{ "name": "support", "permissions": [{ "permission": "tickets.read" }, { "permission": "tickets.read" }] }] }),
+      json!({ "version": 1, "roles": [{ "name": "support", "permissions": [{ "permission": "tickets.read", "effect": "maybe" }] }] }),
+      json!({ "version": 1, "user_roles": [{ "username": "alice", "role": "support" }, { "username": "alice", "role": "support" }] }),
+      json!({ "version": 1, "user_roles": [{
+        "username": "alice",
+        "role": "support",
+        "valid_from": "2030-01-02T00:00:00Z",
+        "valid_until": "2030-01-01T00:00:00Z"
+      }] }),
+    ];
+    for case in cases {
+      assert!(bundle(case.clone()).validate().is_err(), "{}", case);
+    }
+  }
+}
\ No newline at end of file
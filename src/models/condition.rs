@@ -0,0 +1,136 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveTime, Utc, Weekday};
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+// Attribute-based conditions attached to a role_permission. A grant with conditions only applies
+// when every condition holds for the request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Conditions {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub business_hours: Option<BusinessHours>,
+  // CIDR blocks, e.g. `10.0.0.0/8`; the client must be in one of them.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub ip_ranges: Vec<String>,
+  // Attribute name to accepted values, e.g. `{"department": ["finance"]}`.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub user_attributes: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BusinessHours {
+  // `mon` .. `sun`; every day when empty.
+  #[serde(default)]
+  pub days: Vec<String>,
+  // `HH:MM` in the given offset; windows with start after end wrap past midnight.
+  pub start: String,
+  pub end: String,
+  #[serde(default)]
+  pub utc_offset_minutes: i32,
+}
+
+// What conditions are evaluated against, built per request by JwtMiddleware.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+  pub client_ip: Option<IpAddr>,
+  pub time: DateTime<Utc>,
+  pub user_attributes: HashMap<String, String>,
+}
+
+impl RequestContext {
+  pub fn new(client_ip: Option<IpAddr>) -> Self {
+    RequestContext {
+      client_ip,
+      time: Utc::now(),
+      user_attributes: HashMap::new(),
+    }
+  }
+}
+
+impl Conditions {
+  pub fn validate(&self) -> Result<(), String> {
+    if let Some(hours) = &self.business_hours {
+      parse_time(&hours.start)?;
+      parse_time(&hours.end)?;
+      for day in &hours.days {
+        parse_day(day)?;
+      }
+      offset(hours.utc_offset_minutes)?;
+    }
+    for range in &self.ip_ranges {
+      range.parse::<IpNet>().map_err(|_| format!("invalid IP range '{}'", range))?;
+    }
+    Ok(())
+  }
+
+  pub fn uses_user_attributes(&self) -> bool {
+    !self.user_attributes.is_empty()
+  }
+
+  // Malformed conditions never hold; callers decide what that means for the grant's effect.
+  pub fn holds(&self, context: &RequestContext) -> Result<bool, String> {
+    if let Some(hours) = &self.business_hours
+      && !hours.contains(context.time)?
+    {
+      return Ok(false);
+    }
+    if !self.ip_ranges.is_empty() {
+      let Some(ip) = context.client_ip else {
+        return Ok(false);
+      };
+      let mut in_range = false;
+      for range in &self.ip_ranges {
+        let net: IpNet = range.parse().map_err(|_| format!("invalid IP range '{}'", range))?;
+        in_range |= net.contains(&ip);
+      }
+      if !in_range {
+        return Ok(false);
+      }
+    }
+    for (name, accepted) in &self.user_attributes {
+      match context.user_attributes.get(name) {
+        Some(value) if accepted.contains(value) => {}
+        _ => return Ok(false),
+      }
+    }
+    Ok(true)
+  }
+}
+
+impl BusinessHours {
+  fn contains(&self, time: DateTime<Utc>) -> Result<bool, String> {
+    let local = time.with_timezone(&offset(self.utc_offset_minutes)?);
+    if !self.days.is_empty() {
+      let mut on_day = false;
+      for day in &self.days {
+        on_day |= parse_day(day)? == local.weekday();
+      }
+      if !on_day {
+        return Ok(false);
+      }
+    }
+    let (start, end, now) = (parse_time(&self.start)?, parse_time(&self.end)?, local.time());
+    Ok(if start <= end {
+      start <= now && now < end
+    } else {
+      now >= start || now < end
+    })
+  }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+  NaiveTime::parse_from_str(time, TIME_FORMAT).map_err(|_| format!("invalid time '{}', expected HH:MM", time))
+}
+
+fn parse_day(day: &str) -> Result<Weekday, String> {
+  day.parse::<Weekday>().map_err(|_| format!("invalid day '{}'", day))
+}
+
+fn offset(minutes: i32) -> Result<FixedOffset, String> {
+  FixedOffset::east_opt(minutes * 60).ok_or_else(|| format!("invalid UTC offset {} minutes", minutes))
+}
\ No newline at end of file
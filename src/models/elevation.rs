@@ -0,0 +1,141 @@
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::schema::{elevation_request_events, elevation_requests};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_APPROVED: &str = "approved";
+pub const STATUS_DENIED: &str = "denied";
+pub const STATUS_CANCELLED: &str = "cancelled";
+pub const STATUS_EXPIRED: &str = "expired";
+pub const STATUSES: [&str; 5] = [STATUS_PENDING, STATUS_APPROVED, STATUS_DENIED, STATUS_CANCELLED, STATUS_EXPIRED];
+
+// Audit trail actions; `requested` has no matching status.
+pub const ACTION_REQUESTED: &str = "requested";
+
+#[derive(Queryable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = elevation_requests)]
+pub struct ElevationRequest {
+  pub id: Uuid,
+  pub requester_id: Uuid,
+  pub role_id: Uuid,
+  pub justification: String,
+  pub duration_seconds: i32,
+  pub status: String,
+  pub approver_id: Option<Uuid>,
+  pub decision_comment: Option<String>,
+  pub decided_at: Option<DateTime<Utc>>,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = elevation_requests)]
+pub struct NewElevationRequest<'a> {
+  pub requester_id: Uuid,
+  pub role_id: Uuid,
+  pub justification: &'a str,
+  pub duration_seconds: i32,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = elevation_requests)]
+pub struct ElevationTransition<'a> {
+  pub status: &'a str,
+  pub approver_id: Option<Uuid>,
+  pub decision_comment: Option<&'a str>,
+  pub decided_at: Option<DateTime<Utc>>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = elevation_request_events)]
+pub struct ElevationEvent {
+  pub id: Uuid,
+  pub elevation_request_id: Uuid,
+  pub action: String,
+  pub actor_id: Option<Uuid>,
+  pub comment: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = elevation_request_events)]
+pub struct NewElevationEvent<'a> {
+  pub elevation_request_id: Uuid,
+  pub action: &'a str,
+  pub actor_id: Option<Uuid>,
+  pub comment: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub struct ElevationEventResponse {
+  pub action: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub actor_id: Option<Uuid>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub comment: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ElevationRequestResponse {
+  pub id: Uuid,
+  pub requester_id: Uuid,
+  pub role_id: Uuid,
+  pub justification: String,
+  pub duration_seconds: i32,
+  pub status: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub approver_id: Option<Uuid>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub decision_comment: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub decided_at: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub expires_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  // Only included when a single request is fetched.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub events: Option<Vec<ElevationEventResponse>>,
+}
+
+impl From<ElevationEvent> for ElevationEventResponse {
+  fn from(event: ElevationEvent) -> Self {
+    ElevationEventResponse {
+      action: event.action,
+      actor_id: event.actor_id,
+      comment: event.comment,
+      created_at: event.created_at,
+    }
+  }
+}
+
+impl From<ElevationRequest> for ElevationRequestResponse {
+  fn from(request: ElevationRequest) -> Self {
+    ElevationRequestResponse {
+      id: request.id,
+      requester_id: request.requester_id,
+      role_id: request.role_id,
+      justification: request.justification,
+      duration_seconds: request.duration_seconds,
+      status: request.status,
+      approver_id: request.approver_id,
+      decision_comment: request.decision_comment,
+      decided_at: request.decided_at,
+      expires_at: request.expires_at,
+      created_at: request.created_at,
+      events: None,
+    }
+  }
+}
+
+impl ElevationRequestResponse {
+  pub fn with_events(request: ElevationRequest, events: Vec<ElevationEvent>) -> Self {
+    ElevationRequestResponse {
+      events: Some(events.into_iter().map(ElevationEventResponse::from).collect()),
+      ..ElevationRequestResponse::from(request)
+    }
+  }
+}
\ No newline at end of file
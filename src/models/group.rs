@@ -0,0 +1,87 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::schema::{group_roles, group_subgroups, group_users};
+
+#[derive(Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = crate::schema::groups)]
+pub struct Group {
+  pub id: Uuid,
+  pub name: String,
+  pub description: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::groups)]
+pub struct NewGroup<'a> {
+  pub name: &'a str,
+  pub description: Option<&'a str>,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = crate::schema::groups)]
+pub struct UpdateGroup<'a> {
+  pub name: Option<&'a str>,
+  pub description: Option<&'a str>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Serialize)]
+#[diesel(table_name = group_users)]
+pub struct NewGroupUser {
+  pub group_id: Uuid,
+  pub user_id: Uuid,
+}
+
+#[derive(Insertable, Serialize)]
+#[diesel(table_name = group_subgroups)]
+pub struct NewGroupSubgroup {
+  pub parent_group_id: Uuid,
+  pub child_group_id: Uuid,
+}
+
+#[derive(Insertable, Serialize)]
+#[diesel(table_name = group_roles)]
+pub struct NewGroupRole {
+  pub group_id: Uuid,
+  pub role_id: Uuid,
+}
+
+// A group a user belongs to, directly or through nested groups.
+#[derive(QueryableByName, Debug)]
+pub struct GroupMembership {
+  #[diesel(sql_type = diesel::sql_types::Uuid)]
+  pub group_id: Uuid,
+}
+
+#[derive(Serialize)]
+pub struct GroupResponse {
+  pub id: Uuid,
+  pub name: String,
+  pub description: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+// Direct members and roles of a group; nested members are not expanded.
+#[derive(Serialize)]
+pub struct GroupMembersResponse {
+  pub users: Vec<Uuid>,
+  pub groups: Vec<Uuid>,
+  pub roles: Vec<Uuid>,
+}
+
+impl From<Group> for GroupResponse {
+  fn from(group: Group) -> Self {
+    GroupResponse {
+      id: group.id,
+      name: group.name,
+      description: group.description,
+      created_at: group.created_at,
+      updated_at: group.updated_at,
+    }
+  }
+}
\ No newline at end of file
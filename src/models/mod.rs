@@ -2,4 +2,18 @@ pub mod user;
 pub mod role;
 pub mod permission;
 pub mod role_permission;
-pub mod user_role;
\ No newline at end of file
+pub mod user_role;
+pub mod session;
+pub mod authorization;
+pub mod resource_grant;
+pub mod relation_tuple;
+pub mod namespace;
+pub mod condition;
+pub mod policy;
+pub mod elevation;
+pub mod sod_constraint;
+pub mod group;
+pub mod bundle;
+pub mod bootstrap;
+pub mod audit;
+pub mod webhook;
\ No newline at end of file
@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::utilities::error::AppError;
+use log::{info, warn};
+
+// How the subjects of a relation are computed, following Zanzibar's userset rewrites.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rewrite {
+  // Subjects stored directly as tuples for this relation.
+  #[default]
+  This,
+  // Subjects of another relation on the same object, e.g. every owner is an editor.
+  ComputedUserset { relation: String },
+  // Follows the objects referenced by `tupleset` and takes their `computed_userset` relation,
+  // e.g. viewers of a document's parent folder.
+  TupleToUserset { tupleset: String, computed_userset: String },
+  Union(Vec<Rewrite>),
+}
+
+impl Rewrite {
+  // The non-union rewrites this one is made of.
+  fn leaves(&self) -> Vec<&Rewrite> {
+    match self {
+      Rewrite::Union(children) => children.iter().flat_map(|c| c.leaves()).collect(),
+      leaf => vec![leaf],
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Relation {
+  #[serde(default)]
+  pub rewrite: Rewrite,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Namespace {
+  pub name: String,
+  #[serde(default)]
+  pub relations: HashMap<String, Relation>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NamespaceConfig {
+  #[serde(default)]
+  pub namespaces: Vec<Namespace>,
+}
+
+impl NamespaceConfig {
+  pub fn load(path: &str) -> Result<Self, AppError> {
+    if !Path::new(path).exists() {
+      warn!("Namespace config {} not found; relation tuples are disabled", path);
+      return Ok(NamespaceConfig::default());
+    }
+    let contents = std::fs::read_to_string(path)?;
+    let config: NamespaceConfig = serde_json::from_str(&contents)
+      .map_err(|e| AppError::BadRequest(format!("Invalid namespace config {}: {}", path, e)))?;
+    config.validate()?;
+    info!("Loaded {} namespaces from {}", config.namespaces.len(), path);
+    Ok(config)
+  }
+
+  pub fn relation(&self, namespace: &str, relation: &str) -> Option<&Relation> {
+    self.namespaces
+      .iter()
+      .find(|n| n.name == namespace)
+      .and_then(|n| n.relations.get(relation))
+  }
+
+  pub fn has_namespace(&self, namespace: &str) -> bool {
+    self.namespaces.iter().any(|n| n.name == namespace)
+  }
+
+  // Whether tuples stored for the relation count towards its subjects. Relations missing from
+  // the config only have their stored subjects.
+  pub fn stores_subjects(&self, namespace: &str, relation: &str) -> bool {
+    self.relation(namespace, relation)
+      .is_none_or(|r| r.rewrite.leaves().iter().any(|l| matches!(l, Rewrite::This)))
+  }
+
+  // Relations of `namespace` that include every subject of `relation` on the same object.
+  pub fn computed_userset_sources(&self, namespace: &str, relation: &str) -> Vec<&str> {
+    self.relations_with(namespace, |leaf| {
+      matches!(leaf, Rewrite::ComputedUserset { relation: r } if r == relation)
+    })
+  }
+
+  // Relations of `namespace` that follow `tupleset` and take the `computed_userset` relation of
+  // the objects it references.
+  pub fn tuple_to_userset_sources(&self, namespace: &str, tupleset: &str, computed_userset: &str) -> Vec<&str> {
+    self.relations_with(namespace, |leaf| {
+      matches!(leaf, Rewrite::TupleToUserset { tupleset: t, computed_userset: c } if t == tupleset && c == computed_userset)
+    })
+  }
+
+  fn relations_with(&self, namespace: &str, predicate: impl Fn(&Rewrite) -> bool) -> Vec<&str> {
+    self.namespaces
+      .iter()
+      .filter(|n| n.name == namespace)
+      .flat_map(|n| n.relations.iter())
+      .filter(|(_, r)| r.rewrite.leaves().into_iter().any(&predicate))
+      .map(|(name, _)| name.as_str())
+      .collect()
+  }
+
+  // Rewrites may only point at relations of their own namespace that exist; tuple_to_userset
+  // targets live in other namespaces and are resolved when evaluated.
+  fn validate(&self) -> Result<(), AppError> {
+    for namespace in &self.namespaces {
+      for (name, relation) in &namespace.relations {
+        let mut pending = vec![&relation.rewrite];
+        while let Some(rewrite) = pending.pop() {
+          let referenced = match rewrite {
+            Rewrite::This => None,
+            Rewrite::ComputedUserset { relation } => Some(relation),
+            Rewrite::TupleToUserset { tupleset, .. } => Some(tupleset),
+            Rewrite::Union(children) => {
+              pending.extend(children.iter());
+              None
+            }
+          };
+          if let Some(referenced) = referenced
+            && !namespace.relations.contains_key(referenced)
+          {
+            return Err(AppError::BadRequest(format!(
+              "Relation {}#{} references unknown relation {}",
+              namespace.name, name, referenced
+            )));
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
\ No newline at end of file
@@ -12,6 +12,7 @@ pub struct Permission {
   pub description: Option<String>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  pub system: bool,
 }
 
 #[derive(Insertable, Debug, Deserialize)]
@@ -36,6 +37,7 @@ pub struct PermissionResponse {
   pub description: Option<String>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  pub system: bool,
 }
 
 impl From<Permission> for PermissionResponse {
@@ -46,6 +48,7 @@ impl From<Permission> for PermissionResponse {
       description: permission.description,
       created_at: permission.created_at,
       updated_at: permission.updated_at,
+      system: permission.system,
     }
   }
 }
\ No newline at end of file
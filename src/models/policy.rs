@@ -0,0 +1,55 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = crate::schema::policies)]
+pub struct Policy {
+  pub id: Uuid,
+  pub name: String,
+  pub body: String,
+  pub enabled: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::policies)]
+pub struct NewPolicy<'a> {
+  pub name: &'a str,
+  pub body: &'a str,
+  pub enabled: bool,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = crate::schema::policies)]
+pub struct UpdatePolicy<'a> {
+  pub name: Option<&'a str>,
+  pub body: Option<&'a str>,
+  pub enabled: Option<bool>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct PolicyResponse {
+  pub id: Uuid,
+  pub name: String,
+  pub body: String,
+  pub enabled: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl From<Policy> for PolicyResponse {
+  fn from(policy: Policy) -> Self {
+    PolicyResponse {
+      id: policy.id,
+      name: policy.name,
+      body: policy.body,
+      enabled: policy.enabled,
+      created_at: policy.created_at,
+      updated_at: policy.updated_at,
+    }
+  }
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::schema::relation_tuples;
+
+#[derive(Queryable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = relation_tuples)]
+pub struct RelationTuple {
+  pub id: Uuid,
+  pub namespace: String,
+  pub object_id: String,
+  pub relation: String,
+  pub subject_namespace: String,
+  pub subject_id: String,
+  pub subject_relation: String,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug, Deserialize)]
+#[diesel(table_name = relation_tuples)]
+pub struct NewRelationTuple {
+  pub namespace: String,
+  pub object_id: String,
+  pub relation: String,
+  pub subject_namespace: String,
+  pub subject_id: String,
+  pub subject_relation: String,
+}
+
+// `namespace:id` for a direct subject such as a user, or `namespace:id#relation` for a userset.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Subject {
+  pub namespace: String,
+  pub id: String,
+  pub relation: Option<String>,
+}
+
+impl Subject {
+  pub fn parse(subject: &str) -> Option<Subject> {
+    let (object, relation) = match subject.split_once('#') {
+      Some((object, relation)) if !relation.is_empty() => (object, Some(relation.to_string())),
+      Some(_) => return None,
+      None => (subject, None),
+    };
+    let (namespace, id) = object.split_once(':')?;
+    if namespace.is_empty() || id.is_empty() {
+      return None;
+    }
+    Some(Subject {
+      namespace: namespace.to_string(),
+      id: id.to_string(),
+      relation,
+    })
+  }
+}
+
+impl fmt::Display for Subject {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.relation {
+      Some(relation) => write!(f, "{}:{}#{}", self.namespace, self.id, relation),
+      None => write!(f, "{}:{}", self.namespace, self.id),
+    }
+  }
+}
+
+impl RelationTuple {
+  pub fn subject(&self) -> Subject {
+    Subject {
+      namespace: self.subject_namespace.clone(),
+      id: self.subject_id.clone(),
+      relation: (!self.subject_relation.is_empty()).then(|| self.subject_relation.clone()),
+    }
+  }
+}
+
+#[derive(Serialize)]
+pub struct RelationTupleResponse {
+  pub id: Uuid,
+  pub namespace: String,
+  pub object_id: String,
+  pub relation: String,
+  pub subject: String,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<RelationTuple> for RelationTupleResponse {
+  fn from(tuple: RelationTuple) -> Self {
+    RelationTupleResponse {
+      subject: tuple.subject().to_string(),
+      id: tuple.id,
+      namespace: tuple.namespace,
+      object_id: tuple.object_id,
+      relation: tuple.relation,
+      created_at: tuple.created_at,
+    }
+  }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::schema::resource_grants;
+
+#[derive(Queryable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = resource_grants)]
+pub struct ResourceGrant {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub role_id: Uuid,
+  pub resource_type: String,
+  pub resource_id: String,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug, Deserialize)]
+#[diesel(table_name = resource_grants)]
+pub struct NewResourceGrant {
+  pub user_id: Uuid,
+  pub role_id: Uuid,
+  pub resource_type: String,
+  pub resource_id: String,
+}
+
+#[derive(Serialize)]
+pub struct ResourceGrantResponse {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub role_id: Uuid,
+  pub resource_type: String,
+  pub resource_id: String,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<ResourceGrant> for ResourceGrantResponse {
+  fn from(resource_grant: ResourceGrant) -> Self {
+    ResourceGrantResponse {
+      id: resource_grant.id,
+      user_id: resource_grant.user_id,
+      role_id: resource_grant.role_id,
+      resource_type: resource_grant.resource_type,
+      resource_id: resource_grant.resource_id,
+      created_at: resource_grant.created_at,
+    }
+  }
+}
\ No newline at end of file
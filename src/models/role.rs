@@ -11,6 +11,7 @@ pub struct Role {
   pub description: Option<String>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  pub system: bool,
 }
 
 #[derive(Insertable)]
@@ -35,6 +36,7 @@ pub struct RoleResponse {
   pub description: Option<String>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  pub system: bool,
 }
 
 impl From<Role> for RoleResponse {
@@ -45,6 +47,7 @@ impl From<Role> for RoleResponse {
       description: role.description,
       created_at: role.created_at,
       updated_at: role.updated_at,
+      system: role.system,
     }
   }
 }
\ No newline at end of file
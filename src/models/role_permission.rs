@@ -11,19 +11,26 @@ pub struct RolePermission {
   pub role_id: Uuid,
   pub permission_id: Uuid,
   pub created_at: DateTime<Utc>,
+  pub effect: String,
+  pub conditions: Option<serde_json::Value>,
 }
 
 #[derive(Insertable, Debug, Deserialize)]
 #[diesel(table_name = role_permissions)]
-pub struct NewRolePermission {
+pub struct NewRolePermission<'a> {
   pub role_id: Uuid,
   pub permission_id: Uuid,
+  pub effect: &'a str,
+  pub conditions: Option<serde_json::Value>,
 }
 
 #[derive(Serialize)]
 pub struct RolePermissionResponse {
   pub role_id: Uuid,
   pub permission_id: Uuid,
+  pub effect: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub conditions: Option<serde_json::Value>,
   pub created_at: DateTime<Utc>,
 }
 
@@ -32,6 +39,8 @@ impl From<RolePermission> for RolePermissionResponse {
     RolePermissionResponse {
       role_id: role_permission.role_id,
       permission_id: role_permission.permission_id,
+      effect: role_permission.effect,
+      conditions: role_permission.conditions,
       created_at: role_permission.created_at,
     }
   }
@@ -0,0 +1,49 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::schema::sessions;
+
+#[derive(Queryable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = sessions)]
+pub struct Session {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub expires_at: DateTime<Utc>,
+  pub revoked_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl Session {
+  pub fn is_active(&self) -> bool {
+    self.revoked_at.is_none() && self.expires_at > Utc::now()
+  }
+}
+
+#[derive(Insertable, Debug, Deserialize)]
+#[diesel(table_name = sessions)]
+pub struct NewSession {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct SessionResponse {
+  pub id: Uuid,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+  // Whether this is the session of the token making the request.
+  pub current: bool,
+}
+
+impl SessionResponse {
+  pub fn new(session: Session, current_id: Option<Uuid>) -> Self {
+    SessionResponse {
+      current: current_id == Some(session.id),
+      id: session.id,
+      created_at: session.created_at,
+      expires_at: session.expires_at,
+    }
+  }
+}
\ No newline at end of file
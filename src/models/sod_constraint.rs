@@ -0,0 +1,108 @@
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::schema::{sod_constraint_roles, sod_constraints};
+
+#[derive(Queryable, Identifiable, Debug, Serialize)]
+#[diesel(table_name = sod_constraints)]
+pub struct SodConstraint {
+  pub id: Uuid,
+  pub name: String,
+  pub description: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = sod_constraints)]
+pub struct NewSodConstraint<'a> {
+  pub name: &'a str,
+  pub description: Option<&'a str>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = sod_constraint_roles)]
+pub struct NewSodConstraintRole {
+  pub constraint_id: Uuid,
+  pub role_id: Uuid,
+}
+
+// A constraint with the roles it makes mutually exclusive.
+#[derive(Serialize)]
+pub struct SodConstraintWithRoles {
+  #[serde(flatten)]
+  pub constraint: SodConstraint,
+  pub role_ids: Vec<Uuid>,
+}
+
+// One row per role a user holds out of a constraint's set; grouped into violations.
+#[derive(QueryableByName, Debug)]
+pub struct SodViolationRow {
+  #[diesel(sql_type = diesel::sql_types::Uuid)]
+  pub constraint_id: Uuid,
+  #[diesel(sql_type = diesel::sql_types::Text)]
+  pub constraint_name: String,
+  #[diesel(sql_type = diesel::sql_types::Uuid)]
+  pub user_id: Uuid,
+  #[diesel(sql_type = diesel::sql_types::Text)]
+  pub username: String,
+  #[diesel(sql_type = diesel::sql_types::Uuid)]
+  pub role_id: Uuid,
+  #[diesel(sql_type = diesel::sql_types::Text)]
+  pub role_name: String,
+}
+
+#[derive(Serialize)]
+pub struct SodConstraintResponse {
+  pub id: Uuid,
+  pub name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  pub role_ids: Vec<Uuid>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct SodViolation {
+  pub constraint_id: Uuid,
+  pub constraint_name: String,
+  pub user_id: Uuid,
+  pub username: String,
+  pub role_ids: Vec<Uuid>,
+  pub role_names: Vec<String>,
+}
+
+impl From<SodConstraintWithRoles> for SodConstraintResponse {
+  fn from(constraint: SodConstraintWithRoles) -> Self {
+    SodConstraintResponse {
+      id: constraint.constraint.id,
+      name: constraint.constraint.name,
+      description: constraint.constraint.description,
+      role_ids: constraint.role_ids,
+      created_at: constraint.constraint.created_at,
+    }
+  }
+}
+
+// Users holding two or more roles of the same constraint.
+pub fn group_violations(rows: Vec<SodViolationRow>) -> Vec<SodViolation> {
+  let mut violations: Vec<SodViolation> = Vec::new();
+  for row in rows {
+    match violations.iter_mut().find(|v| v.constraint_id == row.constraint_id && v.user_id == row.user_id) {
+      Some(violation) => {
+        violation.role_ids.push(row.role_id);
+        violation.role_names.push(row.role_name);
+      }
+      None => violations.push(SodViolation {
+        constraint_id: row.constraint_id,
+        constraint_name: row.constraint_name,
+        user_id: row.user_id,
+        username: row.username,
+        role_ids: vec![row.role_id],
+        role_names: vec![row.role_name],
+      }),
+    }
+  }
+  violations.retain(|v| v.role_ids.len() > 1);
+  violations
+}
\ No newline at end of file
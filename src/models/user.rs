@@ -2,6 +2,7 @@ use diesel::prelude::*;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use crate::schema::users;
 
 #[derive(Queryable, Identifiable, Debug, Serialize)]
@@ -10,9 +11,26 @@ pub struct User {
   pub id: Uuid,
   pub username: String,
   pub email: String,
+  #[serde(skip_serializing)]
   pub password_hash: String,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  pub attributes: serde_json::Value,
+}
+
+impl User {
+  // String-valued attributes, as matched by role_permission conditions.
+  pub fn attribute_map(&self) -> HashMap<String, String> {
+    self.attributes
+      .as_object()
+      .map(|attributes| {
+        attributes
+          .iter()
+          .filter_map(|(name, value)| value.as_str().map(|v| (name.clone(), v.to_string())))
+          .collect()
+      })
+      .unwrap_or_default()
+  }
 }
 
 #[derive(Insertable, Debug, Deserialize)]
@@ -29,6 +47,7 @@ pub struct UpdateUser<'a> {
   pub username: Option<&'a str>,
   pub email: Option<&'a str>,
   pub password_hash: Option<&'a str>,
+  pub attributes: Option<serde_json::Value>,
   pub updated_at: DateTime<Utc>,
 }
 
@@ -37,6 +56,7 @@ pub struct UserResponse {
   pub id: Uuid,
   pub username: String,
   pub email: String,
+  pub attributes: HashMap<String, String>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
 }
@@ -44,6 +64,7 @@ pub struct UserResponse {
 impl From<User> for UserResponse {
   fn from(user: User) -> Self {
     UserResponse {
+      attributes: user.attribute_map(),
       id: user.id,
       username: user.username,
       email: user.email,
@@ -2,7 +2,7 @@ use diesel::prelude::*;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::schema::user_roles;
+use crate::schema::{expired_user_roles, user_roles};
 
 #[derive(Queryable, Identifiable, Debug, Serialize)]
 #[diesel(table_name = user_roles)]
@@ -11,6 +11,8 @@ pub struct UserRole {
   pub user_id: Uuid,
   pub role_id: Uuid,
   pub created_at: DateTime<Utc>,
+  pub valid_from: Option<DateTime<Utc>>,
+  pub valid_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Insertable, Debug, Deserialize)]
@@ -18,6 +20,18 @@ pub struct UserRole {
 pub struct NewUserRole {
   pub user_id: Uuid,
   pub role_id: Uuid,
+  pub valid_from: Option<DateTime<Utc>>,
+  pub valid_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = expired_user_roles)]
+pub struct NewExpiredUserRole {
+  pub user_id: Uuid,
+  pub role_id: Uuid,
+  pub created_at: DateTime<Utc>,
+  pub valid_from: Option<DateTime<Utc>>,
+  pub valid_until: DateTime<Utc>,
 }
 
 #[derive(Serialize)]
@@ -25,14 +39,28 @@ pub struct UserRoleResponse {
   pub user_id: Uuid,
   pub role_id: Uuid,
   pub created_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub valid_from: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub valid_until: Option<DateTime<Utc>>,
+  pub active: bool,
+}
+
+impl UserRole {
+  pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+    self.valid_from.is_none_or(|from| from <= now) && self.valid_until.is_none_or(|until| until > now)
+  }
 }
 
 impl From<UserRole> for UserRoleResponse {
   fn from(user_role: UserRole) -> Self {
     UserRoleResponse {
+      active: user_role.is_active_at(Utc::now()),
       user_id: user_role.user_id,
       role_id: user_role.role_id,
       created_at: user_role.created_at,
+      valid_from: user_role.valid_from,
+      valid_until: user_role.valid_until,
     }
   }
-}
\ No newline at end of file
+}
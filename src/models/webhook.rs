@@ -0,0 +1,145 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DELIVERED: &str = "delivered";
+pub const STATUS_DEAD: &str = "dead";
+
+#[derive(Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = crate::schema::webhooks)]
+pub struct Webhook {
+  pub id: Uuid,
+  pub url: String,
+  #[serde(skip_serializing)]
+  pub secret: String,
+  // Empty subscribes to every event type.
+  pub event_types: Vec<String>,
+  pub active: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+  pub fn subscribes_to(&self, event_type: &str) -> bool {
+    self.event_types.is_empty() || self.event_types.iter().any(|t| t == event_type)
+  }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::webhooks)]
+pub struct NewWebhook<'a> {
+  pub url: &'a str,
+  pub secret: &'a str,
+  pub event_types: &'a [String],
+  pub active: bool,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = crate::schema::webhooks)]
+pub struct UpdateWebhook<'a> {
+  pub url: Option<&'a str>,
+  pub secret: Option<&'a str>,
+  pub event_types: Option<&'a [String]>,
+  pub active: Option<bool>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct WebhookResponse {
+  pub id: Uuid,
+  pub url: String,
+  // Only returned when the webhook is created.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub secret: Option<String>,
+  pub event_types: Vec<String>,
+  pub active: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+  fn from(webhook: Webhook) -> Self {
+    WebhookResponse {
+      id: webhook.id,
+      url: webhook.url,
+      secret: None,
+      event_types: webhook.event_types,
+      active: webhook.active,
+      created_at: webhook.created_at,
+      updated_at: webhook.updated_at,
+    }
+  }
+}
+
+#[derive(Queryable, Identifiable)]
+#[diesel(table_name = crate::schema::outbox_events)]
+pub struct OutboxEvent {
+  pub id: Uuid,
+  pub event_type: String,
+  pub payload: Value,
+  pub created_at: DateTime<Utc>,
+  pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::webhook_deliveries)]
+pub struct NewWebhookDelivery {
+  pub webhook_id: Uuid,
+  pub outbox_event_id: Uuid,
+}
+
+#[derive(Queryable, Identifiable)]
+#[diesel(table_name = crate::schema::webhook_deliveries)]
+pub struct WebhookDelivery {
+  pub id: Uuid,
+  pub webhook_id: Uuid,
+  pub outbox_event_id: Uuid,
+  pub status: String,
+  pub attempts: i32,
+  pub next_attempt_at: DateTime<Utc>,
+  pub last_status_code: Option<i32>,
+  pub last_error: Option<String>,
+  pub delivered_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+// A claimed delivery together with what is needed to send it.
+pub struct DueDelivery {
+  pub delivery: WebhookDelivery,
+  pub webhook: Webhook,
+  pub event: OutboxEvent,
+}
+
+#[derive(Serialize)]
+pub struct WebhookDeliveryResponse {
+  pub id: Uuid,
+  pub webhook_id: Uuid,
+  pub event_id: Uuid,
+  pub event_type: String,
+  pub payload: Value,
+  pub status: String,
+  pub attempts: i32,
+  pub last_status_code: Option<i32>,
+  pub last_error: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<(WebhookDelivery, OutboxEvent)> for WebhookDeliveryResponse {
+  fn from((delivery, event): (WebhookDelivery, OutboxEvent)) -> Self {
+    WebhookDeliveryResponse {
+      id: delivery.id,
+      webhook_id: delivery.webhook_id,
+      event_id: event.id,
+      event_type: event.event_type,
+      payload: event.payload,
+      status: delivery.status,
+      attempts: delivery.attempts,
+      last_status_code: delivery.last_status_code,
+      last_error: delivery.last_error,
+      created_at: delivery.created_at,
+    }
+  }
+}
\ No newline at end of file
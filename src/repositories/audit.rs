@@ -0,0 +1,207 @@
+use diesel::prelude::*;
+use diesel::sql_types;
+use chrono::{SubsecRound, Utc};
+use uuid::Uuid;
+use crate::schema::{audit_checkpoints, audit_events};
+use crate::models::audit::{AuditCheckpoint, AuditContext, AuditEvent, AuditFilter, AuditRecord, NewAuditCheckpoint, NewAuditEvent};
+use crate::database::PgPool;
+use crate::utilities::audit_chain::GENESIS_HASH;
+use crate::utilities::error::AppError;
+use log::{debug, error, info};
+
+// Serialises appends so every event is chained onto the one before it.
+const CHAIN_LOCK_KEY: i64 = 0x6175_6469_7463_6861;
+
+pub struct AuditRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> AuditRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating AuditRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_event: NewAuditEvent) -> Result<AuditEvent, AppError> {
+    debug!("Recording audit event in repository: {}", new_event.action);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let event = conn
+      .transaction(|conn| append(conn, &new_event))
+      .map_err(|e| {
+        error!("Failed to record audit event {}: {:?}", new_event.action, e);
+        AppError::from(e)
+      })?;
+    debug!("Audit event recorded in repository: {} (seq {})", event.id, event.seq);
+    Ok(event)
+  }
+
+  pub fn find_all(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>, AppError> {
+    info!("Listing audit events in repository: actor_id={:?}, target_type={:?}, target_id={:?}", filter.actor_id, filter.target_type, filter.target_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let mut query = audit_events::table.into_boxed();
+    if let Some(actor_id) = filter.actor_id {
+      query = query.filter(audit_events::actor_id.eq(actor_id));
+    }
+    if let Some(target_type) = &filter.target_type {
+      query = query.filter(audit_events::target_type.eq(target_type));
+    }
+    if let Some(target_id) = &filter.target_id {
+      query = query.filter(audit_events::target_id.eq(target_id));
+    }
+    if let Some(from) = filter.from {
+      query = query.filter(audit_events::created_at.ge(from));
+    }
+    if let Some(until) = filter.until {
+      query = query.filter(audit_events::created_at.lt(until));
+    }
+    debug!("Querying database for audit events");
+    let events = query
+      .order(audit_events::seq.desc())
+      .limit(filter.limit)
+      .load::<AuditEvent>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list audit events: {:?}", e);
+        AppError::from(e)
+      })?;
+    info!("Found {} audit events", events.len());
+    Ok(events)
+  }
+
+  // Events after `after_seq` in chain order, for walking the chain in pages.
+  pub fn find_chain_page(&self, after_seq: i64, limit: i64) -> Result<Vec<AuditEvent>, AppError> {
+    debug!("Loading audit events after seq {} in repository", after_seq);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    audit_events::table
+      .filter(audit_events::seq.gt(after_seq))
+      .order(audit_events::seq.asc())
+      .limit(limit)
+      .load::<AuditEvent>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to load audit events after seq {}: {:?}", after_seq, e);
+        AppError::from(e)
+      })
+  }
+
+  // The latest chained event as (seq, hash).
+  pub fn find_chain_head(&self) -> Result<Option<(i64, String)>, AppError> {
+    debug!("Looking up the head of the audit chain in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    audit_events::table
+      .filter(audit_events::hash.is_not_null())
+      .select((audit_events::seq, audit_events::hash.assume_not_null()))
+      .order(audit_events::seq.desc())
+      .first(&mut conn)
+      .optional()
+      .map_err(|e| {
+        error!("Failed to look up the head of the audit chain: {:?}", e);
+        AppError::from(e)
+      })
+  }
+
+  pub fn find_checkpoints(&self) -> Result<Vec<AuditCheckpoint>, AppError> {
+    debug!("Listing audit checkpoints in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    audit_checkpoints::table
+      .order(audit_checkpoints::seq.asc())
+      .load::<AuditCheckpoint>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list audit checkpoints: {:?}", e);
+        AppError::from(e)
+      })
+  }
+
+  pub fn find_latest_checkpoint_seq(&self) -> Result<Option<i64>, AppError> {
+    debug!("Looking up the latest audit checkpoint in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    audit_checkpoints::table
+      .select(diesel::dsl::max(audit_checkpoints::seq))
+      .first(&mut conn)
+      .map_err(|e| {
+        error!("Failed to look up the latest audit checkpoint: {:?}", e);
+        AppError::from(e)
+      })
+  }
+
+  // Returns false when another instance already checkpointed `seq`.
+  pub fn create_checkpoint(&self, new_checkpoint: NewAuditCheckpoint) -> Result<bool, AppError> {
+    info!("Creating audit checkpoint in repository at seq {}", new_checkpoint.seq);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let inserted = diesel::insert_into(audit_checkpoints::table)
+      .values(&new_checkpoint)
+      .on_conflict(audit_checkpoints::seq)
+      .do_nothing()
+      .execute(&mut conn)
+      .map_err(|e| {
+        error!("Failed to create audit checkpoint at seq {}: {:?}", new_checkpoint.seq, e);
+        AppError::from(e)
+      })?;
+    Ok(inserted > 0)
+  }
+}
+
+// Records an event within the caller's transaction, so it is committed or rolled back together
+// with the change it describes.
+pub fn record(conn: &mut PgConnection, context: &AuditContext, record: &AuditRecord) -> Result<AuditEvent, AppError> {
+  debug!("Recording audit event {} on {} {:?}", record.action, record.target_type, record.target_id);
+  let new_event = NewAuditEvent::new(context, record);
+  append(conn, &new_event).map_err(|e| {
+    error!("Failed to record audit event {} on {} {:?}: {:?}", record.action, record.target_type, record.target_id, e);
+    AppError::from(e)
+  })
+}
+
+// Numbers the event and chains it onto the latest one.
+fn append(conn: &mut PgConnection, new_event: &NewAuditEvent) -> QueryResult<AuditEvent> {
+  diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+    .bind::<sql_types::BigInt, _>(CHAIN_LOCK_KEY)
+    .execute(conn)?;
+  let head: Option<(i64, Option<String>)> = audit_events::table
+    .select((audit_events::seq, audit_events::hash))
+    .order(audit_events::seq.desc())
+    .first(conn)
+    .optional()?;
+  let (seq, prev_hash) = match head {
+    Some((seq, hash)) => (seq + 1, hash.unwrap_or_else(|| GENESIS_HASH.to_string())),
+    None => (1, GENESIS_HASH.to_string()),
+  };
+  let mut event = AuditEvent {
+    id: Uuid::new_v4(),
+    actor_id: new_event.actor_id,
+    actor: new_event.actor.map(str::to_string),
+    action: new_event.action.to_string(),
+    target_type: new_event.target_type.to_string(),
+    target_id: new_event.target_id.map(str::to_string),
+    before: new_event.before.cloned(),
+    after: new_event.after.cloned(),
+    ip: new_event.ip.map(str::to_string),
+    request_id: new_event.request_id.map(str::to_string),
+    created_at: Utc::now().trunc_subsecs(6),
+    seq,
+    prev_hash: Some(prev_hash),
+    hash: None,
+  };
+  event.hash = Some(event.compute_hash());
+  diesel::insert_into(audit_events::table).values(&event).execute(conn)?;
+  Ok(event)
+}
\ No newline at end of file
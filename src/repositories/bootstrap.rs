@@ -0,0 +1,253 @@
+use diesel::prelude::*;
+use diesel::sql_types;
+use chrono::Utc;
+use uuid::Uuid;
+use crate::schema::{permissions, role_permissions, roles, user_roles, users};
+use crate::models::bootstrap::{BootstrappedAdmin, BuiltinPermission, ADMIN_PERMISSION, ADMIN_ROLE, ADMIN_ROLE_DESCRIPTION};
+use crate::models::permission::NewPermission;
+use crate::models::role::NewRole;
+use crate::models::role_permission::NewRolePermission;
+use crate::models::user::{NewUser, User};
+use crate::models::user_role::NewUserRole;
+use crate::models::authorization::{self, Effect, Grant, EFFECT_ALLOW};
+use crate::models::condition::RequestContext;
+use crate::database::PgPool;
+use crate::repositories::permission::find_grants;
+use crate::repositories::user_role::active_at;
+use crate::utilities::error::AppError;
+use crate::utilities::{permission_cache, permission_matcher};
+use log::{debug, error, info};
+
+// Serialises changes to who holds admin permissions, including bootstrapping by instances
+// starting at the same time.
+const ADMIN_LOCK_KEY: i64 = 0x6175_7468_626f_6f74;
+
+// Allow grants of the roles each user holds for good, directly or through (nested) groups: the
+// candidates for holding the admin permission. Assignments with an end date are left out, since
+// the expiry sweeper archives them later without asking.
+const ALLOW_GRANTS_QUERY: &str = "\
+  WITH RECURSIVE memberships(user_id, group_id) AS (
+    SELECT user_id, group_id FROM group_users
+    UNION
+    SELECT m.user_id, gs.parent_group_id FROM group_subgroups gs JOIN memberships m ON gs.child_group_id = m.group_id
+  ),
+  holders(user_id, role_id) AS (
+    SELECT user_id, role_id FROM user_roles
+    WHERE (valid_from IS NULL OR valid_from <= now()) AND valid_until IS NULL
+    UNION
+    SELECT m.user_id, gr.role_id FROM memberships m JOIN group_roles gr ON gr.group_id = m.group_id
+  )
+  SELECT DISTINCT h.user_id, p.name AS permission
+  FROM holders h
+  JOIN role_permissions rp ON rp.role_id = h.role_id
+  JOIN permissions p ON p.id = rp.permission_id
+  WHERE rp.effect = 'allow' AND rp.conditions IS NULL";
+
+#[derive(QueryableByName)]
+struct AllowGrant {
+  #[diesel(sql_type = sql_types::Uuid)]
+  user_id: Uuid,
+  #[diesel(sql_type = sql_types::Text)]
+  permission: String,
+}
+
+pub struct BootstrapRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> BootstrapRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating BootstrapRepository");
+    Self { conn }
+  }
+
+  // Creates missing built-in permissions, marks them and the admin role as system rows and
+  // grants the admin role the built-ins meant for it that it does not have yet, so permissions
+  // added by an upgrade reach existing admins. Grants the role already has, including denies,
+  // are left as they are.
+  pub fn ensure_builtins(&self, builtin: &[BuiltinPermission]) -> Result<usize, AppError> {
+    info!("Ensuring {} built-in permissions exist", builtin.len());
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let new_permissions: Vec<NewPermission> = builtin
+      .iter()
+      .map(|p| NewPermission { name: p.name, description: Some(p.description) })
+      .collect();
+    let names: Vec<&str> = builtin.iter().map(|p| p.name).collect();
+    let admin_names: Vec<&str> = builtin.iter().filter(|p| p.admin).map(|p| p.name).collect();
+    let (inserted, attached) = conn
+      .transaction(|conn| {
+        let inserted = diesel::insert_into(permissions::table)
+          .values(&new_permissions)
+          .on_conflict(permissions::name)
+          .do_nothing()
+          .execute(conn)?;
+        diesel::update(permissions::table.filter(permissions::name.eq_any(&names)).filter(permissions::system.eq(false)))
+          .set(permissions::system.eq(true))
+          .execute(conn)?;
+        diesel::update(roles::table.filter(roles::name.eq(ADMIN_ROLE)).filter(roles::system.eq(false)))
+          .set(roles::system.eq(true))
+          .execute(conn)?;
+        let Some(role_id) = roles::table.filter(roles::name.eq(ADMIN_ROLE)).select(roles::id).first::<Uuid>(conn).optional()? else {
+          return Ok::<_, diesel::result::Error>((inserted, 0));
+        };
+        let grants: Vec<NewRolePermission> = permissions::table
+          .filter(permissions::name.eq_any(&admin_names))
+          .select(permissions::id)
+          .load::<Uuid>(conn)?
+          .into_iter()
+          .map(|permission_id| NewRolePermission { role_id, permission_id, effect: EFFECT_ALLOW, conditions: None })
+          .collect();
+        let attached = diesel::insert_into(role_permissions::table)
+          .values(&grants)
+          .on_conflict((role_permissions::role_id, role_permissions::permission_id))
+          .do_nothing()
+          .execute(conn)?;
+        if attached > 0 {
+          permission_cache::notify_all(conn)?;
+        }
+        Ok((inserted, attached))
+      })
+      .map_err(|e| {
+        error!("Failed to create built-in permissions: {:?}", e);
+        AppError::from(e)
+      })?;
+    if attached > 0 {
+      permission_cache::invalidate_all();
+    }
+    info!("Created {} missing built-in permissions and granted {} to the {} role", inserted, attached, ADMIN_ROLE);
+    Ok(inserted)
+  }
+
+  pub fn admin_exists(&self) -> Result<bool, AppError> {
+    debug!("Checking whether any user holds the {} role", ADMIN_ROLE);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    admin_exists(&mut conn).map_err(|e| {
+      error!("Failed to check for an admin user: {:?}", e);
+      AppError::from(e)
+    })
+  }
+
+  // Creates the admin role with `admin_permissions` and assigns it to `new_user`, which must not
+  // exist yet. Returns None when another instance got there first.
+  pub fn create_admin(&self, new_user: NewUser, admin_permissions: &[&str]) -> Result<Option<BootstrappedAdmin>, AppError> {
+    info!("Bootstrapping admin user {}", new_user.username);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let admin = conn.transaction(|conn| {
+      diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+        .bind::<sql_types::BigInt, _>(ADMIN_LOCK_KEY)
+        .execute(conn)?;
+      if admin_exists(conn)? {
+        debug!("Admin user appeared while waiting for the bootstrap lock");
+        return Ok::<_, AppError>(None);
+      }
+
+      diesel::insert_into(roles::table)
+        .values(&NewRole { name: ADMIN_ROLE, description: Some(ADMIN_ROLE_DESCRIPTION) })
+        .on_conflict(roles::name)
+        .do_nothing()
+        .execute(conn)?;
+      diesel::update(roles::table.filter(roles::name.eq(ADMIN_ROLE))).set(roles::system.eq(true)).execute(conn)?;
+      let role_id: Uuid = roles::table.filter(roles::name.eq(ADMIN_ROLE)).select(roles::id).first(conn)?;
+
+      let permission_ids: Vec<Uuid> = permissions::table
+        .filter(permissions::name.eq_any(admin_permissions))
+        .select(permissions::id)
+        .load(conn)?;
+      let grants: Vec<NewRolePermission> = permission_ids
+        .into_iter()
+        .map(|permission_id| NewRolePermission { role_id, permission_id, effect: EFFECT_ALLOW, conditions: None })
+        .collect();
+      diesel::insert_into(role_permissions::table)
+        .values(&grants)
+        .on_conflict((role_permissions::role_id, role_permissions::permission_id))
+        .do_nothing()
+        .execute(conn)?;
+
+      // Registration is open, so an existing account with the admin username may belong to
+      // anyone and is never promoted here.
+      let taken = diesel::select(diesel::dsl::exists(users::table.filter(users::username.eq(new_user.username)))).get_result::<bool>(conn)?;
+      if taken {
+        error!("Bootstrap admin username {} is taken by a user without the {} role", new_user.username, ADMIN_ROLE);
+        return Err(AppError::Conflict(format!(
+          "User '{}' already exists but does not hold the {} role; choose another BOOTSTRAP__ADMIN_USERNAME or grant the role with `authctl role assign {} {}`",
+          new_user.username, ADMIN_ROLE, new_user.username, ADMIN_ROLE
+        )));
+      }
+      let user: User = diesel::insert_into(users::table).values(&new_user).get_result(conn)?;
+      diesel::insert_into(user_roles::table)
+        .values(&NewUserRole { user_id: user.id, role_id, valid_from: None, valid_until: None })
+        .execute(conn)?;
+      permission_cache::notify_all(conn)?;
+      Ok(Some(BootstrappedAdmin { user }))
+    })
+    .map_err(|e| {
+      error!("Failed to bootstrap admin user: {}", e);
+      e
+    })?;
+    if admin.is_some() {
+      permission_cache::invalidate_all();
+    }
+    Ok(admin)
+  }
+}
+
+fn admin_exists(conn: &mut PgConnection) -> QueryResult<bool> {
+  diesel::select(diesel::dsl::exists(
+    user_roles::table
+      .inner_join(roles::table)
+      .filter(roles::name.eq(ADMIN_ROLE))
+      .filter(active_at(Utc::now())),
+  ))
+  .get_result(conn)
+}
+
+// Runs `change` inside the caller's transaction and rolls it back if afterwards no user holds
+// the admin permission.
+pub fn guard_last_admin<T>(conn: &mut PgConnection, change: impl FnOnce(&mut PgConnection) -> Result<T, AppError>) -> Result<T, AppError> {
+  diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+    .bind::<sql_types::BigInt, _>(ADMIN_LOCK_KEY)
+    .execute(conn)?;
+  let result = change(conn)?;
+  if !admin_principal_exists(conn)? {
+    error!("Refusing change that would leave no user holding {}", ADMIN_PERMISSION);
+    return Err(AppError::Conflict(format!("This change would leave no user holding {}", ADMIN_PERMISSION)));
+  }
+  Ok(result)
+}
+
+// Whether some user's effective grants allow the admin permission. Evaluated without a request,
+// so it fails closed: only the permanent, unconditional allows of ALLOW_GRANTS_QUERY count, and
+// every deny applies without its conditions.
+fn admin_principal_exists(conn: &mut PgConnection) -> QueryResult<bool> {
+  let allows = diesel::sql_query(ALLOW_GRANTS_QUERY).load::<AllowGrant>(conn)?;
+  let mut candidates: Vec<Uuid> = allows
+    .iter()
+    .filter(|grant| permission_matcher::matches(&grant.permission, ADMIN_PERMISSION))
+    .map(|grant| grant.user_id)
+    .collect();
+  candidates.sort();
+  candidates.dedup();
+  let context = RequestContext::new(None);
+  for user_id in candidates {
+    let grants: Vec<Grant> = find_grants(conn, user_id)?
+      .into_iter()
+      .filter(|grant| {
+        grant.effect == Effect::Deny || allows.iter().any(|allow| allow.user_id == user_id && allow.permission == grant.permission)
+      })
+      .map(|grant| Grant { conditions: None, ..grant })
+      .collect();
+    if authorization::evaluate(&grants, ADMIN_PERMISSION, None, &context).allowed {
+      return Ok(true);
+    }
+  }
+  Ok(false)
+}
\ No newline at end of file
@@ -0,0 +1,316 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use chrono::Utc;
+use std::collections::HashMap;
+use crate::schema::{permissions, role_permissions, roles, user_roles, users};
+use crate::models::bundle::{
+  self, BundlePlan, BundleRole, BundleRolePermission, BundleUserRole, ChangeAction, PermissionDefinition, RbacBundle, BUNDLE_VERSION,
+};
+use crate::models::permission::NewPermission;
+use crate::models::role::NewRole;
+use crate::models::role_permission::NewRolePermission;
+use crate::models::user_role::NewUserRole;
+use crate::models::audit::{AuditContext, AuditRecord, ACTION_IMPORT, TARGET_RBAC};
+use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::repositories::bootstrap::guard_last_admin;
+use crate::repositories::sod_constraint;
+use crate::utilities::error::AppError;
+use crate::utilities::events::{self, Event};
+use crate::utilities::permission_cache;
+use log::{debug, error, info};
+
+pub struct BundleRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> BundleRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating BundleRepository");
+    Self { conn }
+  }
+
+  pub fn export(&self, include_user_roles: bool) -> Result<RbacBundle, AppError> {
+    info!("Exporting RBAC bundle (include_user_roles={})", include_user_roles);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let bundle = conn.transaction(|conn| load(conn, include_user_roles))?;
+    info!("Exported {} permissions and {} roles", bundle.permissions.len(), bundle.roles.len());
+    Ok(bundle)
+  }
+
+  // Diffs `desired` against the database and, unless `dry_run`, applies and audits the
+  // difference. All of it happens in one transaction so the plan reported is the one applied.
+  pub fn import(&self, desired: &RbacBundle, prune: bool, dry_run: bool, context: &AuditContext) -> Result<BundlePlan, AppError> {
+    info!("Importing RBAC bundle (prune={}, dry_run={})", prune, dry_run);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let plan = conn.transaction(|conn| {
+      let current = load(conn, desired.user_roles.is_some())?;
+      let plan = bundle::plan(&current, desired, prune)?;
+      reject_system_deletes(conn, &plan)?;
+      if dry_run || plan.is_empty() {
+        return Ok::<_, AppError>(plan);
+      }
+      guard_last_admin(conn, |conn| apply(conn, &plan))?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for bundle import: {:?}", e);
+        AppError::from(e)
+      })?;
+      // The applied plan already holds the before and after of every change.
+      let record = AuditRecord {
+        after: serde_json::to_value(&plan).ok(),
+        ..AuditRecord::new(ACTION_IMPORT, TARGET_RBAC, None)
+      };
+      audit::record(conn, context, &record)?;
+      Ok(plan)
+    })?;
+    if !dry_run && !plan.is_empty() {
+      permission_cache::invalidate_all();
+    }
+    info!("RBAC bundle import planned {} changes (dry_run={})", plan.len(), dry_run);
+    Ok(plan)
+  }
+}
+
+// Pruning never removes system roles or permissions, so a bundle exported before they existed
+// cannot be used to delete them.
+fn reject_system_deletes(conn: &mut PgConnection, plan: &BundlePlan) -> Result<(), AppError> {
+  let role_names: Vec<&str> = plan.roles.iter().filter(|c| c.action == ChangeAction::Delete).filter_map(|c| c.before.as_ref()).map(|r| r.name.as_str()).collect();
+  let permission_names: Vec<&str> = plan.permissions.iter().filter(|c| c.action == ChangeAction::Delete).filter_map(|c| c.before.as_ref()).map(|p| p.name.as_str()).collect();
+  if let Some(name) = roles::table
+    .filter(roles::system.eq(true))
+    .filter(roles::name.eq_any(&role_names))
+    .select(roles::name)
+    .first::<String>(conn)
+    .optional()?
+  {
+    error!("Bundle import would delete system role {}", name);
+    return Err(AppError::Conflict(format!("Role '{}' is a system role and cannot be deleted", name)));
+  }
+  if let Some(name) = permissions::table
+    .filter(permissions::system.eq(true))
+    .filter(permissions::name.eq_any(&permission_names))
+    .select(permissions::name)
+    .first::<String>(conn)
+    .optional()?
+  {
+    error!("Bundle import would delete system permission {}", name);
+    return Err(AppError::Conflict(format!("Permission '{}' is a system permission and cannot be deleted", name)));
+  }
+  Ok(())
+}
+
+fn load(conn: &mut PgConnection, include_user_roles: bool) -> Result<RbacBundle, AppError> {
+  debug!("Loading RBAC state from database");
+  let permissions = permissions::table
+    .select((permissions::name, permissions::description))
+    .order(permissions::name.asc())
+    .load::<(String, Option<String>)>(conn)
+    .map_err(|e| {
+      error!("Failed to load permissions: {:?}", e);
+      AppError::from(e)
+    })?
+    .into_iter()
+    .map(|(name, description)| PermissionDefinition { name, description })
+    .collect();
+
+  let mut bundle_roles: Vec<BundleRole> = roles::table
+    .select((roles::name, roles::description))
+    .order(roles::name.asc())
+    .load::<(String, Option<String>)>(conn)
+    .map_err(|e| {
+      error!("Failed to load roles: {:?}", e);
+      AppError::from(e)
+    })?
+    .into_iter()
+    .map(|(name, description)| BundleRole { name, description, permissions: Vec::new() })
+    .collect();
+
+  let grants = role_permissions::table
+    .inner_join(roles::table)
+    .inner_join(permissions::table)
+    .select((roles::name, permissions::name, role_permissions::effect, role_permissions::conditions))
+    .order((roles::name.asc(), permissions::name.asc()))
+    .load::<(String, String, String, Option<serde_json::Value>)>(conn)
+    .map_err(|e| {
+      error!("Failed to load role_permissions: {:?}", e);
+      AppError::from(e)
+    })?;
+  for (role, permission, effect, conditions) in grants {
+    let conditions = conditions
+      .map(serde_json::from_value)
+      .transpose()
+      .map_err(|e| AppError::DatabaseError(format!("Invalid conditions stored for role '{}' and permission '{}': {}", role, permission, e)))?;
+    if let Some(bundle_role) = bundle_roles.iter_mut().find(|r| r.name == role) {
+      bundle_role.permissions.push(BundleRolePermission { permission, effect, conditions });
+    }
+  }
+
+  let bundle_user_roles = if include_user_roles {
+    let assignments = user_roles::table
+      .inner_join(users::table)
+      .inner_join(roles::table)
+      .select((users::username, roles::name, user_roles::valid_from, user_roles::valid_until))
+      .order((users::username.asc(), roles::name.asc()))
+      .load::<(String, String, Option<chrono::DateTime<Utc>>, Option<chrono::DateTime<Utc>>)>(conn)
+      .map_err(|e| {
+        error!("Failed to load user_roles: {:?}", e);
+        AppError::from(e)
+      })?
+      .into_iter()
+      .map(|(username, role, valid_from, valid_until)| BundleUserRole { username, role, valid_from, valid_until })
+      .collect();
+    Some(assignments)
+  } else {
+    None
+  };
+
+  Ok(RbacBundle {
+    version: BUNDLE_VERSION,
+    permissions,
+    roles: bundle_roles,
+    user_roles: bundle_user_roles,
+  })
+}
+
+fn apply(conn: &mut PgConnection, plan: &BundlePlan) -> Result<(), AppError> {
+  let now = Utc::now();
+
+  for change in plan.permissions.iter().filter(|c| c.action != ChangeAction::Delete) {
+    let Some(permission) = &change.after else { continue };
+    debug!("Applying {:?} of permission {}", change.action, permission.name);
+    if change.action == ChangeAction::Create {
+      diesel::insert_into(permissions::table)
+        .values(&NewPermission { name: &permission.name, description: permission.description.as_deref() })
+        .execute(conn)?;
+    } else {
+      diesel::update(permissions::table.filter(permissions::name.eq(&permission.name)))
+        .set((permissions::description.eq(&permission.description), permissions::updated_at.eq(now)))
+        .execute(conn)?;
+    }
+  }
+
+  for change in plan.roles.iter().filter(|c| c.action != ChangeAction::Delete) {
+    let Some(role) = &change.after else { continue };
+    debug!("Applying {:?} of role {}", change.action, role.name);
+    if change.action == ChangeAction::Create {
+      diesel::insert_into(roles::table)
+        .values(&NewRole { name: &role.name, description: role.description.as_deref() })
+        .execute(conn)?;
+    } else {
+      diesel::update(roles::table.filter(roles::name.eq(&role.name)))
+        .set((roles::description.eq(&role.description), roles::updated_at.eq(now)))
+        .execute(conn)?;
+    }
+  }
+
+  let role_ids: HashMap<String, Uuid> = roles::table.select((roles::name, roles::id)).load::<(String, Uuid)>(conn)?.into_iter().collect();
+  let permission_ids: HashMap<String, Uuid> =
+    permissions::table.select((permissions::name, permissions::id)).load::<(String, Uuid)>(conn)?.into_iter().collect();
+
+  for change in &plan.role_permissions {
+    let Some(definition) = change.after.as_ref().or(change.before.as_ref()) else { continue };
+    debug!("Applying {:?} of role_permission {} -> {}", change.action, definition.role, definition.permission);
+    let (Some(&role_id), Some(&permission_id)) = (role_ids.get(&definition.role), permission_ids.get(&definition.permission)) else {
+      return Err(AppError::BadRequest(format!("Role '{}' or permission '{}' does not exist", definition.role, definition.permission)));
+    };
+    let conditions = definition.conditions.as_ref().map(|c| serde_json::json!(c));
+    let target = role_permissions::table
+      .filter(role_permissions::role_id.eq(role_id))
+      .filter(role_permissions::permission_id.eq(permission_id));
+    let event = match change.action {
+      ChangeAction::Create => {
+        diesel::insert_into(role_permissions::table)
+          .values(&NewRolePermission { role_id, permission_id, effect: &definition.effect, conditions })
+          .execute(conn)?;
+        Event::PermissionGranted { role_id, permission_id, effect: definition.effect.clone() }
+      }
+      ChangeAction::Update => {
+        diesel::update(target)
+          .set((role_permissions::effect.eq(&definition.effect), role_permissions::conditions.eq(conditions)))
+          .execute(conn)?;
+        Event::PermissionGranted { role_id, permission_id, effect: definition.effect.clone() }
+      }
+      ChangeAction::Delete => {
+        diesel::delete(target).execute(conn)?;
+        Event::PermissionRevoked { role_id, permission_id }
+      }
+    };
+    events::publish(conn, &event)?;
+  }
+
+  if !plan.user_roles.is_empty() {
+    let usernames: Vec<&str> = plan
+      .user_roles
+      .iter()
+      .filter_map(|c| c.after.as_ref().or(c.before.as_ref()))
+      .map(|ur| ur.username.as_str())
+      .collect();
+    let user_ids: HashMap<String, Uuid> = users::table
+      .filter(users::username.eq_any(&usernames))
+      .select((users::username, users::id))
+      .load::<(String, Uuid)>(conn)?
+      .into_iter()
+      .collect();
+    // Revocations go first so a bundle that swaps conflicting roles is checked against what the
+    // user ends up holding.
+    let (revocations, assignments): (Vec<_>, Vec<_>) = plan.user_roles.iter().partition(|c| c.action == ChangeAction::Delete);
+    for change in revocations.into_iter().chain(assignments) {
+      let Some(assignment) = change.after.as_ref().or(change.before.as_ref()) else { continue };
+      debug!("Applying {:?} of user_role {} -> {}", change.action, assignment.username, assignment.role);
+      let Some(&user_id) = user_ids.get(&assignment.username) else {
+        return Err(AppError::BadRequest(format!("User '{}' does not exist", assignment.username)));
+      };
+      let Some(&role_id) = role_ids.get(&assignment.role) else {
+        return Err(AppError::BadRequest(format!("Role '{}' does not exist", assignment.role)));
+      };
+      let target = user_roles::table.filter(user_roles::user_id.eq(user_id)).filter(user_roles::role_id.eq(role_id));
+      if change.action != ChangeAction::Delete {
+        sod_constraint::enforce(conn, &[user_id], &[role_id])?;
+      }
+      let event = match change.action {
+        ChangeAction::Create => {
+          diesel::insert_into(user_roles::table)
+            .values(&NewUserRole { user_id, role_id, valid_from: assignment.valid_from, valid_until: assignment.valid_until })
+            .execute(conn)?;
+          Event::RoleAssigned { user_id, role_id, valid_from: assignment.valid_from, valid_until: assignment.valid_until }
+        }
+        ChangeAction::Update => {
+          diesel::update(target)
+            .set((user_roles::valid_from.eq(assignment.valid_from), user_roles::valid_until.eq(assignment.valid_until)))
+            .execute(conn)?;
+          Event::RoleAssigned { user_id, role_id, valid_from: assignment.valid_from, valid_until: assignment.valid_until }
+        }
+        ChangeAction::Delete => {
+          diesel::delete(target).execute(conn)?;
+          Event::RoleRevoked { user_id, role_id }
+        }
+      };
+      events::publish(conn, &event)?;
+    }
+  }
+
+  // Pruned roles and permissions go last; their grants and assignments cascade.
+  for change in plan.roles.iter().filter(|c| c.action == ChangeAction::Delete) {
+    let Some(role) = &change.before else { continue };
+    debug!("Deleting role {}", role.name);
+    if let Some(&role_id) = role_ids.get(&role.name) {
+      events::publish_role_removal(conn, role_id)?;
+    }
+    diesel::delete(roles::table.filter(roles::name.eq(&role.name))).execute(conn)?;
+  }
+  for change in plan.permissions.iter().filter(|c| c.action == ChangeAction::Delete) {
+    let Some(permission) = &change.before else { continue };
+    debug!("Deleting permission {}", permission.name);
+    if let Some(&permission_id) = permission_ids.get(&permission.name) {
+      events::publish_permission_removal(conn, permission_id)?;
+    }
+    diesel::delete(permissions::table.filter(permissions::name.eq(&permission.name))).execute(conn)?;
+  }
+  Ok(())
+}
\ No newline at end of file
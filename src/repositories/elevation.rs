@@ -0,0 +1,237 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::schema::{elevation_request_events, elevation_requests};
+use crate::models::elevation::{
+  ElevationEvent, ElevationRequest, ElevationTransition, NewElevationEvent, NewElevationRequest,
+  ACTION_REQUESTED, STATUS_APPROVED, STATUS_EXPIRED, STATUS_PENDING,
+};
+use crate::models::user_role::NewUserRole;
+use crate::models::audit::AuditContext;
+use crate::database::PgPool;
+use crate::repositories::user_role;
+use crate::utilities::error::AppError;
+use crate::utilities::permission_cache;
+use log::{debug, error, info};
+
+pub struct ElevationRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> ElevationRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating ElevationRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_request: NewElevationRequest) -> Result<ElevationRequest, AppError> {
+    info!("Creating elevation request in repository: requester_id={}, role_id={}", new_request.requester_id, new_request.role_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting elevation request into database: requester_id={}", new_request.requester_id);
+    let request = conn.transaction(|conn| {
+      let request: ElevationRequest = diesel::insert_into(elevation_requests::table)
+        .values(&new_request)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create elevation request for requester_id={}: {:?}", new_request.requester_id, e);
+          AppError::from(e)
+        })?;
+      record_event(conn, NewElevationEvent {
+        elevation_request_id: request.id,
+        action: ACTION_REQUESTED,
+        actor_id: Some(request.requester_id),
+        comment: Some(new_request.justification),
+      })?;
+      Ok::<_, AppError>(request)
+    })?;
+    info!("Elevation request created successfully in repository: {}", request.id);
+    Ok(request)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<ElevationRequest, AppError> {
+    info!("Looking up elevation request by ID in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for elevation request ID: {}", id);
+    let request = elevation_requests::table
+      .find(id)
+      .first(&mut conn)
+      .map_err(|e| {
+        error!("Failed to find elevation request with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    info!("Found elevation request by ID in repository: {}", id);
+    Ok(request)
+  }
+
+  pub fn find_all(&self, status: Option<&str>, requester_id: Option<Uuid>) -> Result<Vec<ElevationRequest>, AppError> {
+    info!("Listing elevation requests in repository: status={:?}, requester_id={:?}", status, requester_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let mut query = elevation_requests::table.into_boxed();
+    if let Some(status) = status {
+      query = query.filter(elevation_requests::status.eq(status));
+    }
+    if let Some(requester_id) = requester_id {
+      query = query.filter(elevation_requests::requester_id.eq(requester_id));
+    }
+    debug!("Querying database for elevation requests");
+    let requests = query
+      .order(elevation_requests::created_at.desc())
+      .load::<ElevationRequest>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list elevation requests: {:?}", e);
+        AppError::from(e)
+      })?;
+    info!("Found {} elevation requests", requests.len());
+    Ok(requests)
+  }
+
+  pub fn find_events(&self, id: Uuid) -> Result<Vec<ElevationEvent>, AppError> {
+    info!("Looking up events of elevation request in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for events of elevation request {}", id);
+    let events = elevation_request_events::table
+      .filter(elevation_request_events::elevation_request_id.eq(id))
+      .order(elevation_request_events::created_at.asc())
+      .load::<ElevationEvent>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve events of elevation request {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    info!("Found {} events for elevation request {}", events.len(), id);
+    Ok(events)
+  }
+
+  // Moves a request from `from_status` to `transition.status` and records `actor_id` as having
+  // done it. Fails with Conflict when the request is no longer in `from_status`.
+  pub fn transition(&self, id: Uuid, from_status: &str, actor_id: Uuid, transition: ElevationTransition) -> Result<ElevationRequest, AppError> {
+    info!("Moving elevation request {} from {} to {}", id, from_status, transition.status);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let request = conn.transaction(|conn| apply_transition(conn, id, from_status, actor_id, &transition))?;
+    info!("Elevation request {} is now {}", id, request.status);
+    Ok(request)
+  }
+
+  // Locks the request and, while it is still pending, grants the requested role and records the
+  // approval in the same transaction, so a request is granted at most once and never left
+  // pending with the role assigned.
+  pub fn approve(&self, id: Uuid, approver_id: Uuid, transition: ElevationTransition, context: &AuditContext) -> Result<ElevationRequest, AppError> {
+    info!("Approving elevation request {} in repository", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let request = conn.transaction(|conn| {
+      let pending: ElevationRequest = elevation_requests::table
+        .find(id)
+        .for_update()
+        .first(conn)
+        .map_err(|e| {
+          error!("Failed to lock elevation request {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      if pending.status != STATUS_PENDING {
+        error!("Elevation request {} is not {}", id, STATUS_PENDING);
+        return Err(AppError::Conflict(format!("Elevation request {} is not {}", id, STATUS_PENDING)));
+      }
+      user_role::assign(conn, &NewUserRole {
+        user_id: pending.requester_id,
+        role_id: pending.role_id,
+        valid_from: None,
+        valid_until: transition.expires_at,
+      }, context)?;
+      apply_transition(conn, id, STATUS_PENDING, approver_id, &transition)
+    })?;
+    permission_cache::invalidate_user(request.requester_id);
+    info!("Elevation request {} approved in repository", id);
+    Ok(request)
+  }
+
+  // Marks approved requests whose elevation ended by `now` as expired.
+  pub fn expire_approved(&self, now: DateTime<Utc>) -> Result<Vec<ElevationRequest>, AppError> {
+    debug!("Expiring elevation requests that ended by {}", now);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let expired = conn.transaction(|conn| {
+      let expired: Vec<ElevationRequest> = diesel::update(
+        elevation_requests::table
+          .filter(elevation_requests::status.eq(STATUS_APPROVED))
+          .filter(elevation_requests::expires_at.le(now))
+      )
+      .set(elevation_requests::status.eq(STATUS_EXPIRED))
+      .get_results(conn)
+      .map_err(|e| {
+        error!("Failed to expire elevation requests: {:?}", e);
+        AppError::from(e)
+      })?;
+      for request in &expired {
+        record_event(conn, NewElevationEvent {
+          elevation_request_id: request.id,
+          action: STATUS_EXPIRED,
+          actor_id: None,
+          comment: None,
+        })?;
+      }
+      Ok::<_, AppError>(expired)
+    })?;
+    if !expired.is_empty() {
+      info!("Expired {} elevation requests", expired.len());
+    }
+    Ok(expired)
+  }
+}
+
+// Moves a request from `from_status` to `transition.status`, failing with Conflict when it is no
+// longer in `from_status`.
+fn apply_transition(conn: &mut PgConnection, id: Uuid, from_status: &str, actor_id: Uuid, transition: &ElevationTransition) -> Result<ElevationRequest, AppError> {
+  let request: Option<ElevationRequest> = diesel::update(
+    elevation_requests::table
+      .filter(elevation_requests::id.eq(id))
+      .filter(elevation_requests::status.eq(from_status))
+  )
+  .set(transition)
+  .get_result(conn)
+  .optional()
+  .map_err(|e| {
+    error!("Failed to update elevation request {}: {:?}", id, e);
+    AppError::from(e)
+  })?;
+  let Some(request) = request else {
+    error!("Elevation request {} is not {}", id, from_status);
+    return Err(AppError::Conflict(format!("Elevation request {} is not {}", id, from_status)));
+  };
+  record_event(conn, NewElevationEvent {
+    elevation_request_id: id,
+    action: transition.status,
+    actor_id: Some(actor_id),
+    comment: transition.decision_comment,
+  })?;
+  Ok(request)
+}
+
+fn record_event(conn: &mut PgConnection, event: NewElevationEvent) -> Result<(), AppError> {
+  diesel::insert_into(elevation_request_events::table)
+    .values(&event)
+    .execute(conn)
+    .map(|_| ())
+    .map_err(|e| {
+      error!("Failed to record {} event for elevation request {}: {:?}", event.action, event.elevation_request_id, e);
+      AppError::from(e)
+    })
+}
\ No newline at end of file
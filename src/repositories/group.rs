@@ -0,0 +1,486 @@
+use diesel::prelude::*;
+use diesel::sql_types;
+use uuid::Uuid;
+use crate::schema::{group_roles, group_subgroups, group_users, groups};
+use crate::models::group::{Group, GroupMembersResponse, GroupMembership, NewGroup, NewGroupRole, NewGroupSubgroup, NewGroupUser, UpdateGroup};
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_GROUP, TARGET_GROUP_ROLE, TARGET_GROUP_SUBGROUP, TARGET_GROUP_USER};
+use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::repositories::bootstrap::guard_last_admin;
+use crate::repositories::sod_constraint;
+use crate::utilities::error::AppError;
+use crate::utilities::events::{self, Event};
+use crate::utilities::permission_cache;
+use log::{debug, error, info};
+
+// Groups of the user, following nested groups upwards. UNION drops rows already seen, so the
+// walk ends even if the data holds a cycle.
+const USER_GROUPS_QUERY: &str = "\
+  WITH RECURSIVE memberships AS (
+    SELECT group_id FROM group_users WHERE user_id = $1
+    UNION
+    SELECT gs.parent_group_id FROM group_subgroups gs JOIN memberships m ON gs.child_group_id = m.group_id
+  )
+  SELECT group_id FROM memberships";
+
+const DESCENDANT_GROUPS_QUERY: &str = "\
+  WITH RECURSIVE descendants AS (
+    SELECT child_group_id AS group_id FROM group_subgroups WHERE parent_group_id = $1
+    UNION
+    SELECT gs.child_group_id FROM group_subgroups gs JOIN descendants d ON gs.parent_group_id = d.group_id
+  )
+  SELECT group_id FROM descendants";
+
+// The group and every group it is nested in; members inherit the roles of all of them.
+const ANCESTOR_GROUPS_QUERY: &str = "\
+  WITH RECURSIVE ancestors AS (
+    SELECT $1::uuid AS group_id
+    UNION
+    SELECT gs.parent_group_id FROM group_subgroups gs JOIN ancestors a ON gs.child_group_id = a.group_id
+  )
+  SELECT group_id FROM ancestors";
+
+// Serialises membership and role changes of groups, so the members and roles a separation of
+// duties check reads can not change before the checked change commits.
+const GROUP_CHANGES_LOCK_KEY: i64 = 0x6772_6f75_7073_6f64;
+
+pub struct GroupRepository<'a> {
+  conn: &'a PgPool,
+}
+
+// Ids of every group `user_id` belongs to, directly or through nested groups.
+pub fn find_group_ids_by_user_id(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<Vec<Uuid>> {
+  diesel::sql_query(USER_GROUPS_QUERY)
+    .bind::<sql_types::Uuid, _>(user_id)
+    .load::<GroupMembership>(conn)
+    .map(|rows| rows.into_iter().map(|row| row.group_id).collect())
+}
+
+fn lock_group_changes(conn: &mut PgConnection) -> QueryResult<()> {
+  diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+    .bind::<sql_types::BigInt, _>(GROUP_CHANGES_LOCK_KEY)
+    .execute(conn)
+    .map(|_| ())
+}
+
+// Roles held by every member of `group_id`: its own and those of the groups it is nested in.
+fn find_inherited_role_ids(conn: &mut PgConnection, group_id: Uuid) -> QueryResult<Vec<Uuid>> {
+  let group_ids: Vec<Uuid> = diesel::sql_query(ANCESTOR_GROUPS_QUERY)
+    .bind::<sql_types::Uuid, _>(group_id)
+    .load::<GroupMembership>(conn)?
+    .into_iter()
+    .map(|row| row.group_id)
+    .collect();
+  group_roles::table
+    .filter(group_roles::group_id.eq_any(&group_ids))
+    .select(group_roles::role_id)
+    .distinct()
+    .load::<Uuid>(conn)
+}
+
+// Users of `group_id` and of every group nested in it.
+fn find_member_user_ids(conn: &mut PgConnection, group_id: Uuid) -> QueryResult<Vec<Uuid>> {
+  let mut group_ids: Vec<Uuid> = diesel::sql_query(DESCENDANT_GROUPS_QUERY)
+    .bind::<sql_types::Uuid, _>(group_id)
+    .load::<GroupMembership>(conn)?
+    .into_iter()
+    .map(|row| row.group_id)
+    .collect();
+  group_ids.push(group_id);
+  group_users::table
+    .filter(group_users::group_id.eq_any(&group_ids))
+    .select(group_users::user_id)
+    .distinct()
+    .load::<Uuid>(conn)
+}
+
+impl<'a> GroupRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating GroupRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_group: NewGroup, context: &AuditContext) -> Result<Group, AppError> {
+    info!("Creating group in repository: {}", new_group.name);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting group into database: {}", new_group.name);
+    let group: Group = conn.transaction(|conn| {
+      let group: Group = diesel::insert_into(groups::table)
+        .values(&new_group)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create group {}: {:?}", new_group.name, e);
+          AppError::from(e)
+        })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_GROUP, group.id, &group))?;
+      Ok::<_, AppError>(group)
+    })?;
+    info!("Group created successfully in repository: {}", group.name);
+    Ok(group)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<Group>, AppError> {
+    info!("Listing groups in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let groups = groups::table
+      .order(groups::name.asc())
+      .load::<Group>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list groups: {:?}", e);
+        AppError::from(e)
+      })?;
+    info!("Found {} groups", groups.len());
+    Ok(groups)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<Group, AppError> {
+    info!("Looking up group by ID in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for group ID: {}", id);
+    let group = groups::table
+      .find(id)
+      .first(&mut conn)
+      .map_err(|e| {
+        error!("Failed to find group with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    info!("Found group by ID in repository: {}", id);
+    Ok(group)
+  }
+
+  pub fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<Group>, AppError> {
+    info!("Looking up groups of user_id={} in repository", user_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let group_ids = find_group_ids_by_user_id(&mut conn, user_id).map_err(|e| {
+      error!("Failed to resolve groups of user_id={}: {:?}", user_id, e);
+      AppError::from(e)
+    })?;
+    let groups = groups::table
+      .filter(groups::id.eq_any(&group_ids))
+      .order(groups::name.asc())
+      .load::<Group>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve groups of user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+    info!("Found {} groups for user_id={}", groups.len(), user_id);
+    Ok(groups)
+  }
+
+  // Direct user members, child groups and roles of a group.
+  pub fn find_members(&self, id: Uuid) -> Result<GroupMembersResponse, AppError> {
+    info!("Looking up members of group {} in repository", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let result = (|| {
+      let users = group_users::table
+        .filter(group_users::group_id.eq(id))
+        .select(group_users::user_id)
+        .load::<Uuid>(&mut conn)?;
+      let children = group_subgroups::table
+        .filter(group_subgroups::parent_group_id.eq(id))
+        .select(group_subgroups::child_group_id)
+        .load::<Uuid>(&mut conn)?;
+      let roles = group_roles::table
+        .filter(group_roles::group_id.eq(id))
+        .select(group_roles::role_id)
+        .load::<Uuid>(&mut conn)?;
+      Ok::<_, diesel::result::Error>(GroupMembersResponse { users, groups: children, roles })
+    })()
+    .map_err(|e| {
+      error!("Failed to retrieve members of group {}: {:?}", id, e);
+      AppError::from(e)
+    })?;
+    info!("Found members of group {}", id);
+    Ok(result)
+  }
+
+  pub fn update(&self, id: Uuid, update_group: UpdateGroup, context: &AuditContext) -> Result<Group, AppError> {
+    info!("Updating group in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Updating group in database: {}", id);
+    let group = conn.transaction(|conn| {
+      let before: Group = groups::table.find(id).for_update().first(conn)?;
+      let group: Group = diesel::update(groups::table.find(id))
+        .set(&update_group)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to update group with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      audit::record(conn, context, &AuditRecord::updated(TARGET_GROUP, id, &before, &group))?;
+      Ok::<_, AppError>(group)
+    })?;
+    info!("Group updated successfully in repository: {}", id);
+    Ok(group)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting group in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Deleting group from database: {}", id);
+    let deleted = conn.transaction(|conn| {
+      events::publish_group_removal(conn, id)?;
+      let deleted: Option<Group> = guard_last_admin(conn, |conn| {
+        diesel::delete(groups::table.find(id))
+          .get_result(conn)
+          .optional()
+          .map_err(|e| {
+            error!("Failed to delete group with ID {}: {:?}", id, e);
+            AppError::from(e)
+          })
+      })?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for group {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      if let Some(before) = &deleted {
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_GROUP, id, before))?;
+      }
+      Ok::<_, AppError>(deleted)
+    })?;
+    permission_cache::invalidate_all();
+    if deleted.is_none() {
+      error!("Group with ID {} not found for deletion", id);
+      return Err(AppError::NotFound(format!("Group with ID {} not found", id)));
+    }
+    info!("Group deleted successfully in repository: {}", id);
+    Ok(())
+  }
+
+  pub fn add_user(&self, group_id: Uuid, user_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Adding user_id={} to group {} in repository", user_id, group_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    conn.transaction(|conn| guard_last_admin(conn, |conn| {
+      lock_group_changes(conn)?;
+      let inherited = find_inherited_role_ids(conn, group_id)?;
+      sod_constraint::enforce(conn, &[user_id], &inherited)?;
+      let membership = NewGroupUser { group_id, user_id };
+      diesel::insert_into(group_users::table)
+        .values(&membership)
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to add user_id={} to group {}: {:?}", user_id, group_id, e);
+          AppError::from(e)
+        })?;
+      events::publish(conn, &Event::GroupUserAdded { group_id, user_id })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_GROUP_USER, format!("{}/{}", group_id, user_id), &membership))?;
+      permission_cache::notify_user(conn, user_id).map_err(|e| {
+        error!("Failed to publish permission change for user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+      Ok(())
+    }))?;
+    permission_cache::invalidate_user(user_id);
+    info!("Added user_id={} to group {}", user_id, group_id);
+    Ok(())
+  }
+
+  pub fn remove_user(&self, group_id: Uuid, user_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Removing user_id={} from group {} in repository", user_id, group_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let affected = conn.transaction(|conn| {
+      let affected = guard_last_admin(conn, |conn| {
+        diesel::delete(
+          group_users::table
+            .filter(group_users::group_id.eq(group_id))
+            .filter(group_users::user_id.eq(user_id))
+        )
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to remove user_id={} from group {}: {:?}", user_id, group_id, e);
+          AppError::from(e)
+        })
+      })?;
+      permission_cache::notify_user(conn, user_id).map_err(|e| {
+        error!("Failed to publish permission change for user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+      if affected > 0 {
+        let membership = NewGroupUser { group_id, user_id };
+        events::publish(conn, &Event::GroupUserRemoved { group_id, user_id })?;
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_GROUP_USER, format!("{}/{}", group_id, user_id), &membership))?;
+      }
+      Ok::<_, AppError>(affected)
+    })?;
+    permission_cache::invalidate_user(user_id);
+    if affected == 0 {
+      return Err(AppError::NotFound(format!("User {} is not a member of group {}", user_id, group_id)));
+    }
+    info!("Removed user_id={} from group {}", user_id, group_id);
+    Ok(())
+  }
+
+  // Rejects memberships that would make a group contain itself, or give a member of the child
+  // group two roles of a SoD constraint. The table lock serializes concurrent changes so two
+  // inserts can not close a cycle together.
+  pub fn add_subgroup(&self, parent_group_id: Uuid, child_group_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Adding group {} to group {} in repository", child_group_id, parent_group_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    conn.transaction(|conn| guard_last_admin(conn, |conn| {
+      lock_group_changes(conn)?;
+      diesel::sql_query("LOCK TABLE group_subgroups IN SHARE ROW EXCLUSIVE MODE").execute(conn)?;
+      let descendants = diesel::sql_query(DESCENDANT_GROUPS_QUERY)
+        .bind::<sql_types::Uuid, _>(child_group_id)
+        .load::<GroupMembership>(conn)?;
+      if parent_group_id == child_group_id || descendants.iter().any(|d| d.group_id == parent_group_id) {
+        error!("Adding group {} to group {} would create a cycle", child_group_id, parent_group_id);
+        return Err(AppError::BadRequest(format!(
+          "Adding group {} to group {} would create a cycle",
+          child_group_id, parent_group_id
+        )));
+      }
+      let inherited = find_inherited_role_ids(conn, parent_group_id)?;
+      let held = find_inherited_role_ids(conn, child_group_id)?;
+      sod_constraint::enforce_group(conn, child_group_id, &held, &inherited)?;
+      let members = find_member_user_ids(conn, child_group_id)?;
+      sod_constraint::enforce(conn, &members, &inherited)?;
+      let nesting = NewGroupSubgroup { parent_group_id, child_group_id };
+      diesel::insert_into(group_subgroups::table)
+        .values(&nesting)
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to add group {} to group {}: {:?}", child_group_id, parent_group_id, e);
+          AppError::from(e)
+        })?;
+      events::publish(conn, &Event::GroupSubgroupAdded { parent_group_id, child_group_id })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_GROUP_SUBGROUP, format!("{}/{}", parent_group_id, child_group_id), &nesting))?;
+      permission_cache::notify_all(conn)?;
+      Ok(())
+    }))?;
+    permission_cache::invalidate_all();
+    info!("Added group {} to group {}", child_group_id, parent_group_id);
+    Ok(())
+  }
+
+  pub fn remove_subgroup(&self, parent_group_id: Uuid, child_group_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Removing group {} from group {} in repository", child_group_id, parent_group_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let affected = conn.transaction(|conn| {
+      let affected = guard_last_admin(conn, |conn| {
+        diesel::delete(
+          group_subgroups::table
+            .filter(group_subgroups::parent_group_id.eq(parent_group_id))
+            .filter(group_subgroups::child_group_id.eq(child_group_id))
+        )
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to remove group {} from group {}: {:?}", child_group_id, parent_group_id, e);
+          AppError::from(e)
+        })
+      })?;
+      permission_cache::notify_all(conn)?;
+      if affected > 0 {
+        let nesting = NewGroupSubgroup { parent_group_id, child_group_id };
+        events::publish(conn, &Event::GroupSubgroupRemoved { parent_group_id, child_group_id })?;
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_GROUP_SUBGROUP, format!("{}/{}", parent_group_id, child_group_id), &nesting))?;
+      }
+      Ok::<_, AppError>(affected)
+    })?;
+    permission_cache::invalidate_all();
+    if affected == 0 {
+      return Err(AppError::NotFound(format!("Group {} is not a member of group {}", child_group_id, parent_group_id)));
+    }
+    info!("Removed group {} from group {}", child_group_id, parent_group_id);
+    Ok(())
+  }
+
+  // The role reaches every user of the group and of the groups nested in it, so each of them is
+  // checked against separation of duties.
+  pub fn add_role(&self, group_id: Uuid, role_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Assigning role_id={} to group {} in repository", role_id, group_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    conn.transaction(|conn| guard_last_admin(conn, |conn| {
+      lock_group_changes(conn)?;
+      let held = find_inherited_role_ids(conn, group_id)?;
+      sod_constraint::enforce_group(conn, group_id, &held, &[role_id])?;
+      let members = find_member_user_ids(conn, group_id)?;
+      sod_constraint::enforce(conn, &members, &[role_id])?;
+      let group_role = NewGroupRole { group_id, role_id };
+      diesel::insert_into(group_roles::table)
+        .values(&group_role)
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to assign role_id={} to group {}: {:?}", role_id, group_id, e);
+          AppError::from(e)
+        })?;
+      events::publish(conn, &Event::GroupRoleAssigned { group_id, role_id })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_GROUP_ROLE, format!("{}/{}", group_id, role_id), &group_role))?;
+      permission_cache::notify_all(conn)?;
+      Ok(())
+    }))?;
+    permission_cache::invalidate_all();
+    info!("Assigned role_id={} to group {}", role_id, group_id);
+    Ok(())
+  }
+
+  pub fn remove_role(&self, group_id: Uuid, role_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Removing role_id={} from group {} in repository", role_id, group_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let affected = conn.transaction(|conn| {
+      let affected = guard_last_admin(conn, |conn| {
+        diesel::delete(
+          group_roles::table
+            .filter(group_roles::group_id.eq(group_id))
+            .filter(group_roles::role_id.eq(role_id))
+        )
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to remove role_id={} from group {}: {:?}", role_id, group_id, e);
+          AppError::from(e)
+        })
+      })?;
+      permission_cache::notify_all(conn)?;
+      if affected > 0 {
+        let group_role = NewGroupRole { group_id, role_id };
+        events::publish(conn, &Event::GroupRoleRevoked { group_id, role_id })?;
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_GROUP_ROLE, format!("{}/{}", group_id, role_id), &group_role))?;
+      }
+      Ok::<_, AppError>(affected)
+    })?;
+    permission_cache::invalidate_all();
+    if affected == 0 {
+      return Err(AppError::NotFound(format!("Role {} is not assigned to group {}", role_id, group_id)));
+    }
+    info!("Removed role_id={} from group {}", role_id, group_id);
+    Ok(())
+  }
+}
\ No newline at end of file
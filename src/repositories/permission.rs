@@ -1,9 +1,18 @@
 use diesel::prelude::*;
 use uuid::Uuid;
-use crate::schema::permissions;
+use chrono::Utc;
+use crate::schema::{group_roles, groups, permissions, resource_grants, role_permissions, roles, user_roles};
 use crate::models::permission::{Permission, NewPermission, UpdatePermission};
+use crate::models::authorization::{Grant, GrantRow, GroupGrantRow, ResourceGrantRow};
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_PERMISSION};
 use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::repositories::bootstrap::guard_last_admin;
+use crate::repositories::group::find_group_ids_by_user_id;
+use crate::repositories::user_role::active_at;
 use crate::utilities::error::AppError;
+use crate::utilities::events;
+use crate::utilities::permission_cache;
 use log::{debug, error, info};
 
 pub struct PermissionRepository<'a> {
@@ -16,7 +25,7 @@ impl<'a> PermissionRepository<'a> {
     Self { conn }
   }
 
-  pub fn create(&self, new_permission: NewPermission) -> Result<Permission, AppError> {
+  pub fn create(&self, new_permission: NewPermission, context: &AuditContext) -> Result<Permission, AppError> {
     info!("Creating permission in repository: {}", new_permission.name);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -24,13 +33,15 @@ impl<'a> PermissionRepository<'a> {
     })?;
     debug!("Inserting permission into database: {}", new_permission.name);
     let permission: Permission = conn.transaction(|conn| {
-      diesel::insert_into(permissions::table)
+      let permission: Permission = diesel::insert_into(permissions::table)
         .values(&new_permission)
         .get_result(conn)
         .map_err(|e| {
           error!("Failed to create permission {}: {:?}", new_permission.name, e);
           AppError::from(e)
-        })
+        })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_PERMISSION, permission.id, &permission))?;
+      Ok::<_, AppError>(permission)
     })?;
     info!("Permission created successfully in repository: {}", permission.name);
     Ok(permission)
@@ -72,7 +83,21 @@ impl<'a> PermissionRepository<'a> {
     Ok(permission)
   }
 
-  pub fn update(&self, id: Uuid, update_permission: UpdatePermission) -> Result<Permission, AppError> {
+  pub fn find_grants_by_user_id(&self, user_id: Uuid) -> Result<Vec<Grant>, AppError> {
+    info!("Looking up permission grants by user_id in repository: {}", user_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let grants = find_grants(&mut conn, user_id).map_err(|e| {
+      error!("Failed to retrieve permission grants for user_id={}: {:?}", user_id, e);
+      AppError::from(e)
+    })?;
+    info!("Found {} permission grants for user_id={}", grants.len(), user_id);
+    Ok(grants)
+  }
+
+  pub fn update(&self, id: Uuid, update_permission: UpdatePermission, context: &AuditContext) -> Result<Permission, AppError> {
     info!("Updating permission in repository: {}", id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -80,19 +105,34 @@ impl<'a> PermissionRepository<'a> {
     })?;
     debug!("Updating permission in database: {}", id);
     let permission = conn.transaction(|conn| {
-      diesel::update(permissions::table.find(id))
-        .set(&update_permission)
-        .get_result(conn)
-        .map_err(|e| {
-          error!("Failed to update permission with ID {}: {:?}", id, e);
-          AppError::from(e)
-        })
+      let before: Permission = permissions::table.find(id).for_update().first(conn)?;
+      if before.system && update_permission.name.is_some_and(|name| name != before.name) {
+        error!("Refusing to rename system permission {}", before.name);
+        return Err(AppError::Conflict(format!("Permission '{}' is a system permission and cannot be renamed", before.name)));
+      }
+      // Renaming a wildcard such as `*` can take the admin permission away from its last holder.
+      let permission: Permission = guard_last_admin(conn, |conn| {
+        diesel::update(permissions::table.find(id))
+          .set(&update_permission)
+          .get_result(conn)
+          .map_err(|e| {
+            error!("Failed to update permission with ID {}: {:?}", id, e);
+            AppError::from(e)
+          })
+      })?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for permission {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      audit::record(conn, context, &AuditRecord::updated(TARGET_PERMISSION, id, &before, &permission))?;
+      Ok::<_, AppError>(permission)
     })?;
+    permission_cache::invalidate_all();
     info!("Permission updated successfully in repository: {}", id);
     Ok(permission)
   }
 
-  pub fn delete(&self, id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting permission in repository: {}", id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -100,13 +140,30 @@ impl<'a> PermissionRepository<'a> {
     })?;
     debug!("Deleting permission from database: {}", id);
     let affected = conn.transaction(|conn| {
-      diesel::delete(permissions::table.find(id))
-        .execute(conn)
-        .map_err(|e| {
-          error!("Failed to delete permission with ID {}: {:?}", id, e);
-          AppError::from(e)
-        })
+      let Some(before) = permissions::table.find(id).for_update().first::<Permission>(conn).optional()? else {
+        return Ok(0);
+      };
+      if before.system {
+        error!("Refusing to delete system permission {}", before.name);
+        return Err(AppError::Conflict(format!("Permission '{}' is a system permission and cannot be deleted", before.name)));
+      }
+      events::publish_permission_removal(conn, id)?;
+      let affected = guard_last_admin(conn, |conn| {
+        diesel::delete(permissions::table.find(id))
+          .execute(conn)
+          .map_err(|e| {
+            error!("Failed to delete permission with ID {}: {:?}", id, e);
+            AppError::from(e)
+          })
+      })?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for permission {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      audit::record(conn, context, &AuditRecord::deleted(TARGET_PERMISSION, id, &before))?;
+      Ok::<_, AppError>(affected)
     })?;
+    permission_cache::invalidate_all();
     if affected == 0 {
       error!("Permission with ID {} not found for deletion", id);
       return Err(AppError::NotFound(format!("Permission with ID {} not found", id)));
@@ -114,4 +171,52 @@ impl<'a> PermissionRepository<'a> {
     info!("Permission deleted successfully in repository: {}", id);
     Ok(())
   }
+}
+
+// Grants of the user's active direct roles, resource-scoped roles and group-derived roles.
+pub fn find_grants(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<Vec<Grant>> {
+  debug!("Querying database for permission grants of user_id={}", user_id);
+  let grants = permissions::table
+    .inner_join(role_permissions::table)
+    .inner_join(roles::table.on(roles::id.eq(role_permissions::role_id)))
+    .inner_join(user_roles::table.on(user_roles::role_id.eq(role_permissions::role_id)))
+    .filter(user_roles::user_id.eq(user_id))
+    .filter(active_at(Utc::now()))
+    .select((permissions::name, role_permissions::effect, role_permissions::conditions, roles::name))
+    .load::<GrantRow>(conn)?;
+  debug!("Querying database for resource-scoped grants of user_id={}", user_id);
+  let resource_grants = permissions::table
+    .inner_join(role_permissions::table)
+    .inner_join(roles::table.on(roles::id.eq(role_permissions::role_id)))
+    .inner_join(resource_grants::table.on(resource_grants::role_id.eq(role_permissions::role_id)))
+    .filter(resource_grants::user_id.eq(user_id))
+    .select((
+      permissions::name,
+      role_permissions::effect,
+      role_permissions::conditions,
+      roles::name,
+      resource_grants::resource_type,
+      resource_grants::resource_id,
+    ))
+    .load::<ResourceGrantRow>(conn)?;
+  debug!("Querying database for group-derived grants of user_id={}", user_id);
+  let group_ids = find_group_ids_by_user_id(conn, user_id)?;
+  let group_grants = permissions::table
+    .inner_join(role_permissions::table)
+    .inner_join(roles::table.on(roles::id.eq(role_permissions::role_id)))
+    .inner_join(group_roles::table.on(group_roles::role_id.eq(role_permissions::role_id)))
+    .inner_join(groups::table.on(groups::id.eq(group_roles::group_id)))
+    .filter(group_roles::group_id.eq_any(&group_ids))
+    .select((permissions::name, role_permissions::effect, role_permissions::conditions, roles::name, groups::name))
+    .load::<GroupGrantRow>(conn)?;
+  debug!(
+    "Found {} permission grants, {} resource-scoped grants and {} group-derived grants for user_id={}",
+    grants.len(), resource_grants.len(), group_grants.len(), user_id
+  );
+  Ok(grants
+    .into_iter()
+    .map(Grant::from)
+    .chain(resource_grants.into_iter().map(Grant::from))
+    .chain(group_grants.into_iter().map(Grant::from))
+    .collect())
 }
\ No newline at end of file
@@ -0,0 +1,171 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use crate::schema::policies;
+use crate::models::policy::{Policy, NewPolicy, UpdatePolicy};
+use crate::database::PgPool;
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_POLICY};
+use crate::repositories::audit;
+use crate::utilities::error::AppError;
+use crate::utilities::{permission_cache, policy_store};
+use log::{debug, error, info};
+
+pub struct PolicyRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> PolicyRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating PolicyRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_policy: NewPolicy, context: &AuditContext) -> Result<Policy, AppError> {
+    info!("Creating policy in repository: {}", new_policy.name);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting policy into database: {}", new_policy.name);
+    let policy: Policy = conn.transaction(|conn| {
+      let policy: Policy = diesel::insert_into(policies::table)
+        .values(&new_policy)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create policy {}: {:?}", new_policy.name, e);
+          AppError::from(e)
+        })?;
+      permission_cache::notify_policies(conn).map_err(|e| {
+        error!("Failed to publish policy change for {}: {:?}", new_policy.name, e);
+        AppError::from(e)
+      })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_POLICY, policy.id, &policy))?;
+      Ok::<_, AppError>(policy)
+    })?;
+    policy_store::invalidate();
+    info!("Policy created successfully in repository: {}", policy.name);
+    Ok(policy)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<Policy>, AppError> {
+    info!("Listing policies in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for all policies");
+    let policies = policies::table
+      .order(policies::name.asc())
+      .load::<Policy>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list policies: {:?}", e);
+        AppError::from(e)
+      })?;
+    info!("Found {} policies", policies.len());
+    Ok(policies)
+  }
+
+  pub fn find_enabled(&self) -> Result<Vec<Policy>, AppError> {
+    info!("Listing enabled policies in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for enabled policies");
+    let policies = policies::table
+      .filter(policies::enabled.eq(true))
+      .order(policies::name.asc())
+      .load::<Policy>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list enabled policies: {:?}", e);
+        AppError::from(e)
+      })?;
+    info!("Found {} enabled policies", policies.len());
+    Ok(policies)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<Policy, AppError> {
+    info!("Looking up policy by ID in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for policy ID: {}", id);
+    let policy = policies::table
+      .find(id)
+      .first(&mut conn)
+      .map_err(|e| {
+        error!("Failed to find policy with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    info!("Found policy by ID in repository: {}", id);
+    Ok(policy)
+  }
+
+  pub fn update(&self, id: Uuid, update_policy: UpdatePolicy, context: &AuditContext) -> Result<Policy, AppError> {
+    info!("Updating policy in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Updating policy in database: {}", id);
+    let policy = conn.transaction(|conn| {
+      let before: Policy = policies::table
+        .find(id)
+        .for_update()
+        .first(conn)
+        .map_err(|e| {
+          error!("Failed to find policy with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      let policy: Policy = diesel::update(policies::table.find(id))
+        .set(&update_policy)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to update policy with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      permission_cache::notify_policies(conn).map_err(|e| {
+        error!("Failed to publish policy change for {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      audit::record(conn, context, &AuditRecord::updated(TARGET_POLICY, id, &before, &policy))?;
+      Ok::<_, AppError>(policy)
+    })?;
+    policy_store::invalidate();
+    info!("Policy updated successfully in repository: {}", id);
+    Ok(policy)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting policy in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Deleting policy from database: {}", id);
+    let deleted = conn.transaction(|conn| {
+      let deleted = diesel::delete(policies::table.find(id))
+        .get_result::<Policy>(conn)
+        .optional()
+        .map_err(|e| {
+          error!("Failed to delete policy with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      permission_cache::notify_policies(conn).map_err(|e| {
+        error!("Failed to publish policy change for {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      if let Some(before) = &deleted {
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_POLICY, id, before))?;
+      }
+      Ok::<_, AppError>(deleted)
+    })?;
+    policy_store::invalidate();
+    if deleted.is_none() {
+      error!("Policy with ID {} not found for deletion", id);
+      return Err(AppError::NotFound(format!("Policy with ID {} not found", id)));
+    }
+    info!("Policy deleted successfully in repository: {}", id);
+    Ok(())
+  }
+}
\ No newline at end of file
@@ -0,0 +1,120 @@
+use diesel::prelude::*;
+use crate::schema::relation_tuples;
+use crate::models::relation_tuple::{RelationTuple, NewRelationTuple};
+use crate::database::PgPool;
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_RELATION_TUPLE};
+use crate::repositories::audit;
+use crate::utilities::error::AppError;
+use log::{debug, error, info};
+
+pub struct RelationTupleRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> RelationTupleRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating RelationTupleRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_tuple: NewRelationTuple, context: &AuditContext) -> Result<RelationTuple, AppError> {
+    info!("Creating relation tuple in repository: {}:{}#{}", new_tuple.namespace, new_tuple.object_id, new_tuple.relation);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting relation tuple into database");
+    let tuple: RelationTuple = conn.transaction(|conn| {
+      let tuple: RelationTuple = diesel::insert_into(relation_tuples::table)
+        .values(&new_tuple)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create relation tuple {}:{}#{}: {:?}", new_tuple.namespace, new_tuple.object_id, new_tuple.relation, e);
+          AppError::from(e)
+        })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_RELATION_TUPLE, tuple.id, &tuple))?;
+      Ok::<_, AppError>(tuple)
+    })?;
+    info!("Relation tuple created successfully in repository: {}", tuple.id);
+    Ok(tuple)
+  }
+
+  // Tuples of one relation on one object, or of every relation when `relation` is None.
+  pub fn find(&self, namespace: &str, object_id: &str, relation: Option<&str>) -> Result<Vec<RelationTuple>, AppError> {
+    debug!("Looking up relation tuples for {}:{}#{:?}", namespace, object_id, relation);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let mut query = relation_tuples::table
+      .filter(relation_tuples::namespace.eq(namespace))
+      .filter(relation_tuples::object_id.eq(object_id))
+      .into_boxed();
+    if let Some(relation) = relation {
+      query = query.filter(relation_tuples::relation.eq(relation));
+    }
+    let tuples = query
+      .order(relation_tuples::created_at.asc())
+      .load::<RelationTuple>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve relation tuples for {}:{}: {:?}", namespace, object_id, e);
+        AppError::from(e)
+      })?;
+    debug!("Found {} relation tuples for {}:{}", tuples.len(), namespace, object_id);
+    Ok(tuples)
+  }
+
+  // Tuples naming `subject_namespace:subject_id` as their subject, with or without a relation.
+  pub fn find_by_subject(&self, subject_namespace: &str, subject_id: &str) -> Result<Vec<RelationTuple>, AppError> {
+    debug!("Looking up relation tuples with subject {}:{}", subject_namespace, subject_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let tuples = relation_tuples::table
+      .filter(relation_tuples::subject_namespace.eq(subject_namespace))
+      .filter(relation_tuples::subject_id.eq(subject_id))
+      .order(relation_tuples::created_at.asc())
+      .load::<RelationTuple>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve relation tuples with subject {}:{}: {:?}", subject_namespace, subject_id, e);
+        AppError::from(e)
+      })?;
+    debug!("Found {} relation tuples with subject {}:{}", tuples.len(), subject_namespace, subject_id);
+    Ok(tuples)
+  }
+
+  pub fn delete(&self, tuple: &NewRelationTuple, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting relation tuple in repository: {}:{}#{}", tuple.namespace, tuple.object_id, tuple.relation);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let deleted = conn.transaction(|conn| {
+      let deleted = diesel::delete(
+        relation_tuples::table
+          .filter(relation_tuples::namespace.eq(&tuple.namespace))
+          .filter(relation_tuples::object_id.eq(&tuple.object_id))
+          .filter(relation_tuples::relation.eq(&tuple.relation))
+          .filter(relation_tuples::subject_namespace.eq(&tuple.subject_namespace))
+          .filter(relation_tuples::subject_id.eq(&tuple.subject_id))
+          .filter(relation_tuples::subject_relation.eq(&tuple.subject_relation))
+      )
+      .get_results::<RelationTuple>(conn)
+      .map_err(|e| {
+        error!("Failed to delete relation tuple {}:{}#{}: {:?}", tuple.namespace, tuple.object_id, tuple.relation, e);
+        AppError::from(e)
+      })?;
+      for before in &deleted {
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_RELATION_TUPLE, before.id, before))?;
+      }
+      Ok::<_, AppError>(deleted)
+    })?;
+    if deleted.is_empty() {
+      error!("Relation tuple {}:{}#{} not found for deletion", tuple.namespace, tuple.object_id, tuple.relation);
+      return Err(AppError::NotFound("Relation tuple not found".into()));
+    }
+    info!("Relation tuple deleted successfully: {}:{}#{}", tuple.namespace, tuple.object_id, tuple.relation);
+    Ok(())
+  }
+}
\ No newline at end of file
@@ -0,0 +1,126 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use crate::schema::resource_grants;
+use crate::models::resource_grant::{ResourceGrant, NewResourceGrant};
+use crate::database::PgPool;
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_RESOURCE_GRANT};
+use crate::repositories::audit;
+use crate::utilities::error::AppError;
+use crate::utilities::events::{self, Event};
+use crate::utilities::permission_cache;
+use log::{debug, error, info};
+
+pub struct ResourceGrantRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> ResourceGrantRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating ResourceGrantRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_resource_grant: NewResourceGrant, context: &AuditContext) -> Result<ResourceGrant, AppError> {
+    let user_id = new_resource_grant.user_id;
+    info!("Creating resource_grant in repository: user_id={}, role_id={}, resource={}:{}", user_id, new_resource_grant.role_id, new_resource_grant.resource_type, new_resource_grant.resource_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting resource_grant into database for user_id={}", user_id);
+    let resource_grant: ResourceGrant = conn.transaction(|conn| {
+      let resource_grant: ResourceGrant = diesel::insert_into(resource_grants::table)
+        .values(&new_resource_grant)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create resource_grant for user_id={}: {:?}", user_id, e);
+          AppError::from(e)
+        })?;
+      permission_cache::notify_user(conn, user_id).map_err(|e| {
+        error!("Failed to publish permission change for user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+      events::publish(conn, &Event::resource_role_assigned(&resource_grant))?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_RESOURCE_GRANT, resource_grant.id, &resource_grant))?;
+      Ok::<_, AppError>(resource_grant)
+    })?;
+    permission_cache::invalidate_user(user_id);
+    info!("ResourceGrant created successfully in repository: {}", resource_grant.id);
+    Ok(resource_grant)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<ResourceGrant, AppError> {
+    info!("Looking up resource_grant by ID in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for resource_grant ID: {}", id);
+    let resource_grant = resource_grants::table
+      .find(id)
+      .first(&mut conn)
+      .map_err(|e| {
+        error!("Failed to find resource_grant with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    info!("Found resource_grant by ID in repository: {}", id);
+    Ok(resource_grant)
+  }
+
+  pub fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<ResourceGrant>, AppError> {
+    info!("Looking up resource_grants by user_id={}", user_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for resource_grants with user_id={}", user_id);
+    let resource_grants = resource_grants::table
+      .filter(resource_grants::user_id.eq(user_id))
+      .order(resource_grants::created_at.asc())
+      .load::<ResourceGrant>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve resource_grants for user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+    info!("Found {} resource_grants for user_id={}", resource_grants.len(), user_id);
+    Ok(resource_grants)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting resource_grant in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Deleting resource_grant from database: {}", id);
+    let deleted: Option<Uuid> = conn.transaction(|conn| {
+      let deleted = diesel::delete(resource_grants::table.find(id))
+        .get_result::<ResourceGrant>(conn)
+        .optional()
+        .map_err(|e| {
+          error!("Failed to delete resource_grant with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      if let Some(before) = &deleted {
+        permission_cache::notify_user(conn, before.user_id).map_err(|e| {
+          error!("Failed to publish permission change for user_id={}: {:?}", before.user_id, e);
+          AppError::from(e)
+        })?;
+        events::publish(conn, &Event::resource_role_revoked(before))?;
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_RESOURCE_GRANT, id, before))?;
+      }
+      Ok::<_, AppError>(deleted.map(|before| before.user_id))
+    })?;
+    match deleted {
+      Some(user_id) => {
+        permission_cache::invalidate_user(user_id);
+        info!("ResourceGrant deleted successfully: {}", id);
+        Ok(())
+      }
+      None => {
+        error!("ResourceGrant with ID {} not found for deletion", id);
+        Err(AppError::NotFound(format!("ResourceGrant with ID {} not found", id)))
+      }
+    }
+  }
+}
\ No newline at end of file
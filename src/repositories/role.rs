@@ -1,9 +1,17 @@
 use diesel::prelude::*;
 use uuid::Uuid;
-use crate::schema::roles;
+use chrono::Utc;
+use crate::schema::{group_roles, roles, user_roles};
 use crate::models::role::{Role, NewRole, UpdateRole};
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_ROLE};
 use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::repositories::bootstrap::guard_last_admin;
+use crate::repositories::group::find_group_ids_by_user_id;
+use crate::repositories::user_role::active_at;
 use crate::utilities::error::AppError;
+use crate::utilities::events;
+use crate::utilities::permission_cache;
 use log::{debug, error, info};
 
 pub struct RoleRepository<'a> {
@@ -16,7 +24,7 @@ impl<'a> RoleRepository<'a> {
     Self { conn }
   }
 
-  pub fn create(&self, new_role: NewRole) -> Result<Role, AppError> {
+  pub fn create(&self, new_role: NewRole, context: &AuditContext) -> Result<Role, AppError> {
     info!("Creating role in repository: {}", new_role.name);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -24,13 +32,15 @@ impl<'a> RoleRepository<'a> {
     })?;
     debug!("Inserting role into database: {}", new_role.name);
     let role: Role = conn.transaction(|conn| {
-      diesel::insert_into(roles::table)
+      let role: Role = diesel::insert_into(roles::table)
         .values(&new_role)
         .get_result(conn)
         .map_err(|e| {
           error!("Failed to create role {}: {:?}", new_role.name, e);
           AppError::from(e)
-        })
+        })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_ROLE, role.id, &role))?;
+      Ok::<_, AppError>(role)
     })?;
     info!("Role created successfully in repository: {}", role.name);
     Ok(role)
@@ -72,7 +82,46 @@ impl<'a> RoleRepository<'a> {
     Ok(role)
   }
 
-  pub fn update(&self, id: Uuid, update_role: UpdateRole) -> Result<Role, AppError> {
+  pub fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<Role>, AppError> {
+    info!("Looking up roles by user_id in repository: {}", user_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for roles of user_id={}", user_id);
+    let roles = roles::table
+      .inner_join(user_roles::table)
+      .filter(user_roles::user_id.eq(user_id))
+      .filter(active_at(Utc::now()))
+      .select(roles::all_columns)
+      .load::<Role>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve roles for user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+    debug!("Querying database for group-derived roles of user_id={}", user_id);
+    let group_ids = find_group_ids_by_user_id(&mut conn, user_id).map_err(|e| {
+      error!("Failed to resolve groups of user_id={}: {:?}", user_id, e);
+      AppError::from(e)
+    })?;
+    let assigned: Vec<Uuid> = roles.iter().map(|role| role.id).collect();
+    let group_roles = roles::table
+      .inner_join(group_roles::table)
+      .filter(group_roles::group_id.eq_any(&group_ids))
+      .filter(roles::id.ne_all(&assigned))
+      .select(roles::all_columns)
+      .distinct()
+      .load::<Role>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve group-derived roles for user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+    let roles: Vec<Role> = roles.into_iter().chain(group_roles).collect();
+    info!("Found {} roles for user_id={}", roles.len(), user_id);
+    Ok(roles)
+  }
+
+  pub fn update(&self, id: Uuid, update_role: UpdateRole, context: &AuditContext) -> Result<Role, AppError> {
     info!("Updating role in repository: {}", id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -80,19 +129,31 @@ impl<'a> RoleRepository<'a> {
     })?;
     debug!("Updating role in database: {}", id);
     let role = conn.transaction(|conn| {
-      diesel::update(roles::table.find(id))
+      let before: Role = roles::table.find(id).for_update().first(conn)?;
+      if before.system && update_role.name.is_some_and(|name| name != before.name) {
+        error!("Refusing to rename system role {}", before.name);
+        return Err(AppError::Conflict(format!("Role '{}' is a system role and cannot be renamed", before.name)));
+      }
+      let role: Role = diesel::update(roles::table.find(id))
         .set(&update_role)
         .get_result(conn)
         .map_err(|e| {
           error!("Failed to update role with ID {}: {:?}", id, e);
           AppError::from(e)
-        })
+        })?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for role {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      audit::record(conn, context, &AuditRecord::updated(TARGET_ROLE, id, &before, &role))?;
+      Ok::<_, AppError>(role)
     })?;
+    permission_cache::invalidate_all();
     info!("Role updated successfully in repository: {}", id);
     Ok(role)
   }
 
-  pub fn delete(&self, id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting role in repository: {}", id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -100,13 +161,30 @@ impl<'a> RoleRepository<'a> {
     })?;
     debug!("Deleting role from database: {}", id);
     let affected = conn.transaction(|conn| {
-      diesel::delete(roles::table.find(id))
-        .execute(conn)
-        .map_err(|e| {
-          error!("Failed to delete role with ID {}: {:?}", id, e);
-          AppError::from(e)
-        })
+      let Some(before) = roles::table.find(id).for_update().first::<Role>(conn).optional()? else {
+        return Ok(0);
+      };
+      if before.system {
+        error!("Refusing to delete system role {}", before.name);
+        return Err(AppError::Conflict(format!("Role '{}' is a system role and cannot be deleted", before.name)));
+      }
+      events::publish_role_removal(conn, id)?;
+      let affected = guard_last_admin(conn, |conn| {
+        diesel::delete(roles::table.find(id))
+          .execute(conn)
+          .map_err(|e| {
+            error!("Failed to delete role with ID {}: {:?}", id, e);
+            AppError::from(e)
+          })
+      })?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for role {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      audit::record(conn, context, &AuditRecord::deleted(TARGET_ROLE, id, &before))?;
+      Ok::<_, AppError>(affected)
     })?;
+    permission_cache::invalidate_all();
     if affected == 0 {
       error!("Role with ID {} not found for deletion", id);
       return Err(AppError::NotFound(format!("Role with ID {} not found", id)));
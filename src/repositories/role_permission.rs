@@ -2,8 +2,13 @@ use diesel::prelude::*;
 use uuid::Uuid;
 use crate::schema::role_permissions;
 use crate::models::role_permission::{RolePermission, NewRolePermission};
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_ROLE_PERMISSION};
 use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::repositories::bootstrap::guard_last_admin;
 use crate::utilities::error::AppError;
+use crate::utilities::events::{self, Event};
+use crate::utilities::permission_cache;
 use log::{debug, error, info};
 
 pub struct RolePermissionRepository<'a> {
@@ -16,8 +21,8 @@ impl<'a> RolePermissionRepository<'a> {
     Self { conn }
   }
 
-  pub fn create(&self, role_id: Uuid, permission_id: Uuid) -> Result<RolePermission, AppError> {
-    info!("Creating role_permission in repository: role_id={}, permission_id={}", role_id, permission_id);
+  pub fn create(&self, role_id: Uuid, permission_id: Uuid, effect: &str, conditions: Option<serde_json::Value>, context: &AuditContext) -> Result<RolePermission, AppError> {
+    info!("Creating role_permission in repository: role_id={}, permission_id={}, effect={}", role_id, permission_id, effect);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
       AppError::ConnectionError(format!("Failed to get database connection: {}", e))
@@ -25,17 +30,34 @@ impl<'a> RolePermissionRepository<'a> {
     let new_role_permission = NewRolePermission {
       role_id,
       permission_id,
+      effect,
+      conditions,
     };
     debug!("Inserting role_permission into database: role_id={}, permission_id={}", role_id, permission_id);
     let role_permission: RolePermission = conn.transaction(|conn| {
-      diesel::insert_into(role_permissions::table)
-        .values(&new_role_permission)
-        .get_result(conn)
-        .map_err(|e| {
-          error!("Failed to create role_permission for role_id={} and permission_id={}: {:?}", role_id, permission_id, e);
-          AppError::from(e)
-        })
+      // A deny can take the admin permission away from its last holder.
+      let role_permission: RolePermission = guard_last_admin(conn, |conn| {
+        diesel::insert_into(role_permissions::table)
+          .values(&new_role_permission)
+          .get_result(conn)
+          .map_err(|e| {
+            error!("Failed to create role_permission for role_id={} and permission_id={}: {:?}", role_id, permission_id, e);
+            AppError::from(e)
+          })
+      })?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for role_id={}: {:?}", role_id, e);
+        AppError::from(e)
+      })?;
+      events::publish(conn, &Event::PermissionGranted {
+        role_id,
+        permission_id,
+        effect: effect.to_string(),
+      })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_ROLE_PERMISSION, format!("{}/{}", role_id, permission_id), &role_permission))?;
+      Ok::<_, AppError>(role_permission)
     })?;
+    permission_cache::invalidate_all();
     info!("RolePermission created successfully in repository: role_id={}, permission_id={}", role_id, permission_id);
     Ok(role_permission)
   }
@@ -77,26 +99,39 @@ impl<'a> RolePermissionRepository<'a> {
     Ok(role_permissions)
   }
 
-  pub fn delete(&self, role_id: Uuid, permission_id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, role_id: Uuid, permission_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting role_permission in repository: role_id={}, permission_id={}", role_id, permission_id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
       AppError::ConnectionError(format!("Failed to get database connection: {}", e))
     })?;
     debug!("Deleting role_permission from database: role_id={}, permission_id={}", role_id, permission_id);
-    let affected = conn.transaction(|conn| {
-      diesel::delete(
-        role_permissions::table
-          .filter(role_permissions::role_id.eq(role_id))
-          .filter(role_permissions::permission_id.eq(permission_id))
-      )
-      .execute(conn)
-      .map_err(|e| {
-        error!("Failed to delete role_permission with role_id={} and permission_id={}: {:?}", role_id, permission_id, e);
+    let deleted = conn.transaction(|conn| {
+      let deleted: Option<RolePermission> = guard_last_admin(conn, |conn| {
+        diesel::delete(
+          role_permissions::table
+            .filter(role_permissions::role_id.eq(role_id))
+            .filter(role_permissions::permission_id.eq(permission_id))
+        )
+        .get_result(conn)
+        .optional()
+        .map_err(|e| {
+          error!("Failed to delete role_permission with role_id={} and permission_id={}: {:?}", role_id, permission_id, e);
+          AppError::from(e)
+        })
+      })?;
+      permission_cache::notify_all(conn).map_err(|e| {
+        error!("Failed to publish permission change for role_id={}: {:?}", role_id, e);
         AppError::from(e)
-      })
+      })?;
+      if let Some(before) = &deleted {
+        events::publish(conn, &Event::PermissionRevoked { role_id, permission_id })?;
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_ROLE_PERMISSION, format!("{}/{}", role_id, permission_id), before))?;
+      }
+      Ok::<_, AppError>(deleted)
     })?;
-    if affected == 0 {
+    permission_cache::invalidate_all();
+    if deleted.is_none() {
       error!("RolePermission with role_id={} and permission_id={} not found for deletion", role_id, permission_id);
       return Err(AppError::NotFound(format!("RolePermission with role_id={} and permission_id={} not found", role_id, permission_id)));
     }
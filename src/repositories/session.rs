@@ -0,0 +1,104 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use chrono::Utc;
+use crate::schema::sessions;
+use crate::models::session::{Session, NewSession};
+use crate::models::audit::{AuditContext, AuditRecord};
+use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::utilities::error::AppError;
+use crate::utilities::events::{self, Event};
+use log::{debug, error, info};
+
+pub struct SessionRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> SessionRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating SessionRepository");
+    Self { conn }
+  }
+
+  // `record` describes why the session was opened, such as a login, and is recorded with it.
+  pub fn create(&self, new_session: NewSession, context: &AuditContext, record: &AuditRecord) -> Result<Session, AppError> {
+    info!("Creating session in repository: id={}, user_id={}", new_session.id, new_session.user_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting session into database: id={}", new_session.id);
+    let session: Session = conn.transaction(|conn| {
+      let session = diesel::insert_into(sessions::table)
+        .values(&new_session)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create session {}: {:?}", new_session.id, e);
+          AppError::from(e)
+        })?;
+      audit::record(conn, context, record)?;
+      Ok::<_, AppError>(session)
+    })?;
+    info!("Session created successfully in repository: {}", session.id);
+    Ok(session)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<Session, AppError> {
+    info!("Looking up session by ID in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for session ID: {}", id);
+    let session = sessions::table
+      .find(id)
+      .first(&mut conn)
+      .map_err(|e| {
+        error!("Failed to find session with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    info!("Found session by ID in repository: {}", id);
+    Ok(session)
+  }
+
+  pub fn find_active_by_user_id(&self, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+    info!("Looking up active sessions in repository: user_id={}", user_id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for active sessions of user_id={}", user_id);
+    let sessions = sessions::table
+      .filter(sessions::user_id.eq(user_id))
+      .filter(sessions::revoked_at.is_null())
+      .filter(sessions::expires_at.gt(Utc::now()))
+      .order(sessions::created_at.desc())
+      .load::<Session>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to retrieve sessions for user_id={}: {:?}", user_id, e);
+        AppError::from(e)
+      })?;
+    info!("Found {} active sessions for user_id={}", sessions.len(), user_id);
+    Ok(sessions)
+  }
+}
+
+// Revokes every active session of the user except `keep` and publishes each revocation, so
+// open event streams of those sessions are closed once the transaction commits.
+pub fn revoke_all(conn: &mut PgConnection, user_id: Uuid, keep: Option<Uuid>) -> QueryResult<usize> {
+  let mut query = diesel::update(sessions::table)
+    .filter(sessions::user_id.eq(user_id))
+    .filter(sessions::revoked_at.is_null())
+    .into_boxed();
+  if let Some(keep) = keep {
+    query = query.filter(sessions::id.ne(keep));
+  }
+  let revoked: Vec<Uuid> = query
+    .set(sessions::revoked_at.eq(Utc::now()))
+    .returning(sessions::id)
+    .get_results(conn)?;
+  for &session_id in &revoked {
+    events::publish(conn, &Event::SessionRevoked { user_id, session_id })?;
+  }
+  Ok(revoked.len())
+}
\ No newline at end of file
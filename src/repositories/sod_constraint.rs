@@ -0,0 +1,408 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use crate::schema::{group_roles, roles, sod_constraint_roles, sod_constraints, user_roles, users};
+use crate::models::sod_constraint::{
+  NewSodConstraint, NewSodConstraintRole, SodConstraint, SodConstraintWithRoles, SodViolationRow,
+};
+use crate::database::PgPool;
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_SOD_CONSTRAINT};
+use crate::repositories::audit;
+use crate::repositories::group::find_group_ids_by_user_id;
+use crate::utilities::error::AppError;
+use log::{debug, error, info};
+
+// Roles of a constraint's set each user holds right now, directly or through (nested) groups.
+// UNION drops a role the user holds both ways, so it is listed once.
+const VIOLATION_ROWS_QUERY: &str = "\
+  WITH RECURSIVE memberships(user_id, group_id) AS (
+    SELECT user_id, group_id FROM group_users
+    UNION
+    SELECT m.user_id, gs.parent_group_id FROM group_subgroups gs JOIN memberships m ON gs.child_group_id = m.group_id
+  ),
+  holders(user_id, role_id) AS (
+    SELECT user_id, role_id FROM user_roles
+    WHERE (valid_from IS NULL OR valid_from <= now()) AND (valid_until IS NULL OR valid_until > now())
+    UNION
+    SELECT m.user_id, gr.role_id FROM memberships m JOIN group_roles gr ON gr.group_id = m.group_id
+  )
+  SELECT c.id AS constraint_id, c.name AS constraint_name, u.id AS user_id, u.username, r.id AS role_id, r.name AS role_name
+  FROM sod_constraints c
+  JOIN sod_constraint_roles cr ON cr.constraint_id = c.id
+  JOIN holders h ON h.role_id = cr.role_id
+  JOIN roles r ON r.id = cr.role_id
+  JOIN users u ON u.id = h.user_id
+  ORDER BY c.name, u.username, r.name";
+
+pub struct SodConstraintRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> SodConstraintRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating SodConstraintRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_constraint: NewSodConstraint, role_ids: &[Uuid], context: &AuditContext) -> Result<SodConstraintWithRoles, AppError> {
+    info!("Creating SoD constraint in repository: {}", new_constraint.name);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting SoD constraint into database: {}", new_constraint.name);
+    let constraint = conn.transaction(|conn| {
+      let constraint: SodConstraint = diesel::insert_into(sod_constraints::table)
+        .values(&new_constraint)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create SoD constraint {}: {:?}", new_constraint.name, e);
+          AppError::from(e)
+        })?;
+      let roles: Vec<NewSodConstraintRole> = role_ids
+        .iter()
+        .map(|&role_id| NewSodConstraintRole {
+          constraint_id: constraint.id,
+          role_id,
+        })
+        .collect();
+      diesel::insert_into(sod_constraint_roles::table)
+        .values(&roles)
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to add roles to SoD constraint {}: {:?}", new_constraint.name, e);
+          AppError::from(e)
+        })?;
+      let constraint = SodConstraintWithRoles {
+        constraint,
+        role_ids: role_ids.to_vec(),
+      };
+      audit::record(conn, context, &AuditRecord::created(TARGET_SOD_CONSTRAINT, constraint.constraint.id, &constraint))?;
+      Ok::<_, AppError>(constraint)
+    })?;
+    info!("SoD constraint created successfully in repository: {}", constraint.constraint.name);
+    Ok(constraint)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<SodConstraintWithRoles>, AppError> {
+    info!("Listing SoD constraints in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for SoD constraints");
+    let constraints = sod_constraints::table
+      .order(sod_constraints::name.asc())
+      .load::<SodConstraint>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list SoD constraints: {:?}", e);
+        AppError::from(e)
+      })?;
+    let constraints = with_roles(&mut conn, constraints)?;
+    info!("Found {} SoD constraints", constraints.len());
+    Ok(constraints)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<SodConstraintWithRoles, AppError> {
+    info!("Looking up SoD constraint by ID in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for SoD constraint ID: {}", id);
+    let constraint = sod_constraints::table
+      .find(id)
+      .first::<SodConstraint>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to find SoD constraint with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    let mut constraints = with_roles(&mut conn, vec![constraint])?;
+    info!("Found SoD constraint by ID in repository: {}", id);
+    Ok(constraints.remove(0))
+  }
+
+  pub fn find_violation_rows(&self) -> Result<Vec<SodViolationRow>, AppError> {
+    info!("Looking up role assignments covered by SoD constraints");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let rows = load_violation_rows(&mut conn).map_err(|e| {
+      error!("Failed to retrieve SoD violation rows: {:?}", e);
+      AppError::from(e)
+    })?;
+    info!("Found {} role assignments covered by SoD constraints", rows.len());
+    Ok(rows)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting SoD constraint in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Deleting SoD constraint from database: {}", id);
+    let affected = conn.transaction(|conn| {
+      let before = sod_constraints::table
+        .find(id)
+        .for_update()
+        .first::<SodConstraint>(conn)
+        .optional()
+        .map_err(|e| {
+          error!("Failed to find SoD constraint with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      let Some(before) = before else {
+        return Ok::<_, AppError>(0);
+      };
+      let before = with_roles(conn, vec![before])?.remove(0);
+      let affected = diesel::delete(sod_constraints::table.find(id))
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to delete SoD constraint with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      audit::record(conn, context, &AuditRecord::deleted(TARGET_SOD_CONSTRAINT, id, &before))?;
+      Ok::<_, AppError>(affected)
+    })?;
+    if affected == 0 {
+      error!("SoD constraint with ID {} not found for deletion", id);
+      return Err(AppError::NotFound(format!("SoD constraint with ID {} not found", id)));
+    }
+    info!("SoD constraint deleted successfully in repository: {}", id);
+    Ok(())
+  }
+}
+
+fn load_violation_rows(conn: &mut PgConnection) -> QueryResult<Vec<SodViolationRow>> {
+  diesel::sql_query(VIOLATION_ROWS_QUERY).load::<SodViolationRow>(conn)
+}
+
+fn with_roles(conn: &mut PgConnection, constraints: Vec<SodConstraint>) -> Result<Vec<SodConstraintWithRoles>, AppError> {
+  let ids: Vec<Uuid> = constraints.iter().map(|c| c.id).collect();
+  let roles = sod_constraint_roles::table
+    .filter(sod_constraint_roles::constraint_id.eq_any(&ids))
+    .select((sod_constraint_roles::constraint_id, sod_constraint_roles::role_id))
+    .load::<(Uuid, Uuid)>(conn)
+    .map_err(|e| {
+      error!("Failed to retrieve roles of SoD constraints: {:?}", e);
+      AppError::from(e)
+    })?;
+  Ok(constraints
+    .into_iter()
+    .map(|constraint| SodConstraintWithRoles {
+      role_ids: roles.iter().filter(|(id, _)| *id == constraint.id).map(|(_, role_id)| *role_id).collect(),
+      constraint,
+    })
+    .collect())
+}
+
+// Rejects giving every user in `user_ids` the roles `role_ids` when one of them would then hold
+// two roles of the same constraint, counting roles held directly and through groups. The users'
+// rows stay locked until the caller's transaction ends, so assignments to the same user are
+// checked one after the other. Violations that already exist do not block unrelated changes.
+pub fn enforce(conn: &mut PgConnection, user_ids: &[Uuid], role_ids: &[Uuid]) -> Result<(), AppError> {
+  if user_ids.is_empty() {
+    return Ok(());
+  }
+  let constraints = find_with_any_role(conn, role_ids)?;
+  if constraints.is_empty() {
+    return Ok(());
+  }
+  users::table
+    .filter(users::id.eq_any(user_ids))
+    .select(users::id)
+    .order(users::id.asc())
+    .for_update()
+    .load::<Uuid>(conn)
+    .map_err(|e| {
+      error!("Failed to lock users {:?}: {:?}", user_ids, e);
+      AppError::from(e)
+    })?;
+  for &user_id in user_ids {
+    let held = find_held_role_ids(conn, user_id).map_err(|e| {
+      error!("Failed to retrieve roles held by user_id={}: {:?}", user_id, e);
+      AppError::from(e)
+    })?;
+    check(conn, &constraints, &format!("user {}", user_id), &held, role_ids)?;
+  }
+  Ok(())
+}
+
+// Groups are held to the same rule as users, since every member holds the group's roles.
+pub fn enforce_group(conn: &mut PgConnection, group_id: Uuid, held: &[Uuid], role_ids: &[Uuid]) -> Result<(), AppError> {
+  let constraints = find_with_any_role(conn, role_ids)?;
+  check(conn, &constraints, &format!("group {}", group_id), held, role_ids)
+}
+
+fn find_with_any_role(conn: &mut PgConnection, role_ids: &[Uuid]) -> Result<Vec<SodConstraintWithRoles>, AppError> {
+  if role_ids.is_empty() {
+    return Ok(Vec::new());
+  }
+  let constraints = sod_constraints::table
+    .inner_join(sod_constraint_roles::table)
+    .filter(sod_constraint_roles::role_id.eq_any(role_ids))
+    .select(sod_constraints::all_columns)
+    .distinct()
+    .load::<SodConstraint>(conn)
+    .map_err(|e| {
+      error!("Failed to retrieve SoD constraints for roles {:?}: {:?}", role_ids, e);
+      AppError::from(e)
+    })?;
+  with_roles(conn, constraints)
+}
+
+// Fails when gaining one of `role_ids` not already in `held` puts `holder` in possession of two
+// roles of a constraint.
+fn check(conn: &mut PgConnection, constraints: &[SodConstraintWithRoles], holder: &str, held: &[Uuid], role_ids: &[Uuid]) -> Result<(), AppError> {
+  for constraint in constraints {
+    let gained = constraint.role_ids.iter().find(|id| role_ids.contains(id) && !held.contains(id));
+    let Some(&role_id) = gained else { continue };
+    let other = constraint
+      .role_ids
+      .iter()
+      .find(|&&other| other != role_id && (held.contains(&other) || role_ids.contains(&other)));
+    if let Some(&other) = other {
+      let names = roles::table
+        .filter(roles::id.eq_any([role_id, other]))
+        .select((roles::id, roles::name))
+        .load::<(Uuid, String)>(conn)?;
+      let name = |id: Uuid| names.iter().find(|(r, _)| *r == id).map(|(_, n)| n.clone()).unwrap_or_else(|| id.to_string());
+      error!("Giving role {} to {} violates SoD constraint {}", name(role_id), holder, constraint.constraint.name);
+      return Err(AppError::Conflict(format!(
+        "Separation of duties constraint '{}' forbids {} holding role '{}' together with '{}'",
+        constraint.constraint.name, holder, name(role_id), name(other)
+      )));
+    }
+  }
+  Ok(())
+}
+
+// Every direct assignment counts, including ones that are not valid yet, since static SoD is
+// about who may hold the roles at all.
+fn find_held_role_ids(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<Vec<Uuid>> {
+  let mut held = user_roles::table
+    .filter(user_roles::user_id.eq(user_id))
+    .select(user_roles::role_id)
+    .load::<Uuid>(conn)?;
+  let group_ids = find_group_ids_by_user_id(conn, user_id)?;
+  held.extend(
+    group_roles::table
+      .filter(group_roles::group_id.eq_any(&group_ids))
+      .select(group_roles::role_id)
+      .load::<Uuid>(conn)?,
+  );
+  held.sort();
+  held.dedup();
+  Ok(held)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{Duration, Utc};
+  use crate::models::sod_constraint::group_violations;
+  use crate::schema::{group_subgroups, group_users, groups};
+
+  // Runs against DATABASE_URL inside a transaction that is never committed; without a database
+  // the test is skipped.
+  fn test_connection() -> Option<PgConnection> {
+    dotenvy::dotenv().ok();
+    let mut conn = PgConnection::establish(&std::env::var("DATABASE_URL").ok()?).ok()?;
+    conn.begin_test_transaction().ok()?;
+    Some(conn)
+  }
+
+  fn insert_user(conn: &mut PgConnection, username: &str) -> Uuid {
+    diesel::insert_into(users::table)
+      .values((users::username.eq(username), users::email.eq(format!("{}@example.com", username)), users::password_hash.eq("x")))
+      .returning(users::id)
+      .get_result(conn)
+      .unwrap()
+  }
+
+  fn insert_role(conn: &mut PgConnection, name: &str) -> Uuid {
+    diesel::insert_into(roles::table).values(roles::name.eq(name)).returning(roles::id).get_result(conn).unwrap()
+  }
+
+  fn insert_group(conn: &mut PgConnection, name: &str) -> Uuid {
+    diesel::insert_into(groups::table).values(groups::name.eq(name)).returning(groups::id).get_result(conn).unwrap()
+  }
+
+  fn assign(conn: &mut PgConnection, user_id: Uuid, role_id: Uuid, valid_from: Option<chrono::DateTime<Utc>>, valid_until: Option<chrono::DateTime<Utc>>) {
+    diesel::insert_into(user_roles::table)
+      .values((
+        user_roles::user_id.eq(user_id),
+        user_roles::role_id.eq(role_id),
+        user_roles::valid_from.eq(valid_from),
+        user_roles::valid_until.eq(valid_until),
+      ))
+      .execute(conn)
+      .unwrap();
+  }
+
+  #[test]
+  fn violation_rows_count_active_direct_and_nested_group_roles() {
+    let Some(mut conn) = test_connection() else { return };
+    let conn = &mut conn;
+    let suffix = &Uuid::new_v4().simple().to_string()[..8];
+    let name = |prefix: &str| format!("{}_{}", prefix, suffix);
+    let approve = insert_role(conn, &name("approve"));
+    let submit = insert_role(conn, &name("submit"));
+    let constraint_id: Uuid = diesel::insert_into(sod_constraints::table)
+      .values(sod_constraints::name.eq(name("sod")))
+      .returning(sod_constraints::id)
+      .get_result(conn)
+      .unwrap();
+    diesel::insert_into(sod_constraint_roles::table)
+      .values([
+        (sod_constraint_roles::constraint_id.eq(constraint_id), sod_constraint_roles::role_id.eq(approve)),
+        (sod_constraint_roles::constraint_id.eq(constraint_id), sod_constraint_roles::role_id.eq(submit)),
+      ])
+      .execute(conn)
+      .unwrap();
+    // Members of `team` hold `submit` through the group it is nested in.
+    let parent = insert_group(conn, &name("parent"));
+    let team = insert_group(conn, &name("team"));
+    diesel::insert_into(group_subgroups::table)
+      .values((group_subgroups::parent_group_id.eq(parent), group_subgroups::child_group_id.eq(team)))
+      .execute(conn)
+      .unwrap();
+    diesel::insert_into(group_roles::table)
+      .values((group_roles::group_id.eq(parent), group_roles::role_id.eq(submit)))
+      .execute(conn)
+      .unwrap();
+    let now = Utc::now();
+    let carol = insert_user(conn, &name("carol"));
+    assign(conn, carol, approve, None, None);
+    let dave = insert_user(conn, &name("dave"));
+    assign(conn, dave, approve, Some(now - Duration::days(2)), Some(now + Duration::days(1)));
+    assign(conn, dave, submit, Some(now - Duration::days(2)), Some(now - Duration::days(1)));
+    let erin = insert_user(conn, &name("erin"));
+    assign(conn, erin, approve, Some(now + Duration::days(1)), None);
+    let frank = insert_user(conn, &name("frank"));
+    assign(conn, frank, submit, None, None);
+    for user_id in [carol, erin, frank] {
+      diesel::insert_into(group_users::table)
+        .values((group_users::group_id.eq(team), group_users::user_id.eq(user_id)))
+        .execute(conn)
+        .unwrap();
+    }
+
+    let rows: Vec<SodViolationRow> = load_violation_rows(conn).unwrap().into_iter().filter(|row| row.constraint_id == constraint_id).collect();
+    let held: Vec<(String, String)> = rows.iter().map(|row| (row.username.clone(), row.role_name.clone())).collect();
+    assert_eq!(
+      held,
+      vec![
+        (name("carol"), name("approve")),
+        (name("carol"), name("submit")),
+        (name("dave"), name("approve")),
+        (name("erin"), name("submit")),
+        (name("frank"), name("submit")),
+      ]
+    );
+    let violations = group_violations(rows);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].user_id, carol);
+  }
+}
\ No newline at end of file
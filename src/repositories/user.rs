@@ -1,9 +1,15 @@
 use diesel::prelude::*;
 use uuid::Uuid;
+use chrono::Utc;
 use crate::schema::users;
 use crate::models::user::{User, NewUser, UpdateUser};
+use crate::models::audit::{AuditContext, AuditRecord, ACTION_PASSWORD_CHANGE, TARGET_USER};
 use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::repositories::bootstrap::guard_last_admin;
+use crate::repositories::session;
 use crate::utilities::error::AppError;
+use crate::utilities::events::{self, Event};
 use log::{debug, error, info};
 
 pub struct UserRepository<'a> {
@@ -16,7 +22,7 @@ impl<'a> UserRepository<'a> {
     Self { conn }
   }
 
-  pub fn create(&self, new_user: NewUser) -> Result<User, AppError> {
+  pub fn create(&self, new_user: NewUser, context: &AuditContext) -> Result<User, AppError> {
     info!("Creating user in repository: {}", new_user.username);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -24,13 +30,19 @@ impl<'a> UserRepository<'a> {
     })?;
     debug!("Inserting user into database: {}", new_user.username);
     let user: User = conn.transaction(|conn| {
-      diesel::insert_into(users::table)
+      let user: User = diesel::insert_into(users::table)
         .values(&new_user)
         .get_result(conn)
         .map_err(|e| {
           error!("Failed to create user {}: {:?}", new_user.username, e);
           AppError::from(e)
-        })
+        })?;
+      events::publish(conn, &Event::UserCreated {
+        user_id: user.id,
+        username: user.username.clone(),
+      })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_USER, user.id, &user))?;
+      Ok::<_, AppError>(user)
     })?;
     info!("User created successfully in repository: {}", user.username);
     Ok(user)
@@ -72,7 +84,7 @@ impl<'a> UserRepository<'a> {
     Ok(user)
   }
 
-  pub fn update(&self, id: Uuid, update_user: UpdateUser) -> Result<User, AppError> {
+  pub fn update(&self, id: Uuid, update_user: UpdateUser, context: &AuditContext) -> Result<User, AppError> {
     info!("Updating user in repository: {}", id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -80,34 +92,81 @@ impl<'a> UserRepository<'a> {
     })?;
     debug!("Updating user in database: {}", id);
     let user = conn.transaction(|conn| {
-      diesel::update(users::table.find(id))
+      let before: User = users::table.find(id).for_update().first(conn)?;
+      let user: User = diesel::update(users::table.find(id))
         .set(&update_user)
         .get_result(conn)
         .map_err(|e| {
           error!("Failed to update user with ID {}: {:?}", id, e);
           AppError::from(e)
-        })
+        })?;
+      // Password hashes are never recorded, so a password change gets its own event.
+      if update_user.username.is_some() || update_user.email.is_some() || update_user.attributes.is_some() {
+        audit::record(conn, context, &AuditRecord::updated(TARGET_USER, id, &before, &user))?;
+      }
+      if update_user.password_hash.is_some() {
+        audit::record(conn, context, &AuditRecord::new(ACTION_PASSWORD_CHANGE, TARGET_USER, Some(id.to_string())))?;
+      }
+      Ok::<_, AppError>(user)
     })?;
     info!("User updated successfully in repository: {}", id);
     Ok(user)
   }
 
-  pub fn delete(&self, id: Uuid) -> Result<(), AppError> {
+  // Sets the password hash and revokes every session of the user except `keep`, returning how
+  // many were revoked.
+  pub fn change_password(&self, id: Uuid, password_hash: &str, keep: Option<Uuid>, context: &AuditContext) -> Result<usize, AppError> {
+    info!("Changing password in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let revoked = conn.transaction(|conn| {
+      let affected = diesel::update(users::table.find(id))
+        .set((users::password_hash.eq(password_hash), users::updated_at.eq(Utc::now())))
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to change password of user with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      if affected == 0 {
+        return Err(AppError::NotFound(format!("User with ID {} not found", id)));
+      }
+      audit::record(conn, context, &AuditRecord::new(ACTION_PASSWORD_CHANGE, TARGET_USER, Some(id.to_string())))?;
+      let revoked = session::revoke_all(conn, id, keep).map_err(|e| {
+        error!("Failed to revoke sessions for user_id={}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      Ok::<_, AppError>(revoked)
+    })?;
+    info!("Password changed in repository for user_id={}, revoked {} sessions", id, revoked);
+    Ok(revoked)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting user in repository: {}", id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
       AppError::ConnectionError(format!("Failed to get database connection: {}", e))
     })?;
     debug!("Deleting user from database: {}", id);
-    let affected = conn.transaction(|conn| {
-      diesel::delete(users::table.find(id))
-          .execute(conn)
+    let deleted = conn.transaction(|conn| {
+      let deleted: Option<User> = guard_last_admin(conn, |conn| {
+        diesel::delete(users::table.find(id))
+          .get_result(conn)
+          .optional()
           .map_err(|e| {
             error!("Failed to delete user with ID {}: {:?}", id, e);
             AppError::from(e)
           })
+      })?;
+      if let Some(before) = &deleted {
+        events::publish(conn, &Event::UserDeleted { user_id: id })?;
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_USER, id, before))?;
+      }
+      Ok::<_, AppError>(deleted)
     })?;
-    if affected == 0 {
+    if deleted.is_none() {
       error!("User with ID {} not found for deletion", id);
       return Err(AppError::NotFound(format!("User with ID {} not found", id)));
     }
@@ -1,9 +1,16 @@
 use diesel::prelude::*;
 use uuid::Uuid;
-use crate::schema::user_roles;
-use crate::models::user_role::{UserRole, NewUserRole};
+use chrono::{DateTime, Utc};
+use crate::schema::{expired_user_roles, user_roles};
+use crate::models::user_role::{UserRole, NewUserRole, NewExpiredUserRole};
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_USER_ROLE};
 use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::repositories::bootstrap::guard_last_admin;
+use crate::repositories::sod_constraint;
 use crate::utilities::error::AppError;
+use crate::utilities::events::{self, Event};
+use crate::utilities::permission_cache;
 use log::{debug, error, info};
 
 pub struct UserRoleRepository<'a> {
@@ -16,7 +23,7 @@ impl<'a> UserRoleRepository<'a> {
     Self { conn }
   }
 
-  pub fn create(&self, user_id: Uuid, role_id: Uuid) -> Result<UserRole, AppError> {
+  pub fn create(&self, user_id: Uuid, role_id: Uuid, valid_from: Option<DateTime<Utc>>, valid_until: Option<DateTime<Utc>>, context: &AuditContext) -> Result<UserRole, AppError> {
     info!("Creating user_role in repository: user_id={}, role_id={}", user_id, role_id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
@@ -25,17 +32,12 @@ impl<'a> UserRoleRepository<'a> {
     let new_user_role = NewUserRole {
       user_id,
       role_id,
+      valid_from,
+      valid_until,
     };
     debug!("Inserting user_role into database: user_id={}, role_id={}", user_id, role_id);
-    let user_role: UserRole = conn.transaction(|conn| {
-      diesel::insert_into(user_roles::table)
-          .values(&new_user_role)
-          .get_result(conn)
-          .map_err(|e| {
-            error!("Failed to create user_role for user_id={} and role_id={}: {:?}", user_id, role_id, e);
-            AppError::from(e)
-          })
-    })?;
+    let user_role = conn.transaction(|conn| assign(conn, &new_user_role, context))?;
+    permission_cache::invalidate_user(user_id);
     info!("UserRole created successfully in repository: user_id={}, role_id={}", user_id, role_id);
     Ok(user_role)
   }
@@ -77,30 +79,167 @@ impl<'a> UserRoleRepository<'a> {
     Ok(user_roles)
   }
 
-  pub fn delete(&self, user_id: Uuid, role_id: Uuid) -> Result<(), AppError> {
+  pub fn delete(&self, user_id: Uuid, role_id: Uuid, context: &AuditContext) -> Result<(), AppError> {
     info!("Deleting user_role in repository: user_id={}, role_id={}", user_id, role_id);
     let mut conn = self.conn.get().map_err(|e| {
       error!("Failed to get database connection: {}", e);
       AppError::ConnectionError(format!("Failed to get database connection: {}", e))
     })?;
     debug!("Deleting user_role from database: user_id={}, role_id={}", user_id, role_id);
-    let affected = conn.transaction(|conn| {
-      diesel::delete(
-        user_roles::table
-            .filter(user_roles::user_id.eq(user_id))
-            .filter(user_roles::role_id.eq(role_id))
-      )
-      .execute(conn)
-      .map_err(|e| {
-        error!("Failed to delete user_role with user_id={} and role_id={}: {:?}", user_id, role_id, e);
+    let deleted = conn.transaction(|conn| {
+      let deleted: Option<UserRole> = guard_last_admin(conn, |conn| {
+        diesel::delete(
+          user_roles::table
+              .filter(user_roles::user_id.eq(user_id))
+              .filter(user_roles::role_id.eq(role_id))
+        )
+        .get_result(conn)
+        .optional()
+        .map_err(|e| {
+          error!("Failed to delete user_role with user_id={} and role_id={}: {:?}", user_id, role_id, e);
+          AppError::from(e)
+        })
+      })?;
+      permission_cache::notify_user(conn, user_id).map_err(|e| {
+        error!("Failed to publish permission change for user_id={}: {:?}", user_id, e);
         AppError::from(e)
-      })
+      })?;
+      if let Some(before) = &deleted {
+        events::publish(conn, &Event::RoleRevoked { user_id, role_id })?;
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_USER_ROLE, format!("{}/{}", user_id, role_id), before))?;
+      }
+      Ok::<_, AppError>(deleted)
     })?;
-    if affected == 0 {
+    permission_cache::invalidate_user(user_id);
+    if deleted.is_none() {
       error!("UserRole with user_id={} and role_id={} not found for deletion", user_id, role_id);
       return Err(AppError::NotFound(format!("UserRole with user_id={} and role_id={} not found", user_id, role_id)));
     }
     info!("UserRole deleted successfully: user_id={}, role_id={}", user_id, role_id);
     Ok(())
   }
+
+  // Moves assignments whose validity ended by `now` to expired_user_roles.
+  pub fn archive_expired(&self, now: DateTime<Utc>) -> Result<Vec<UserRole>, AppError> {
+    debug!("Archiving user_roles expired by {}", now);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let expired = conn.transaction(|conn| {
+      let expired: Vec<UserRole> = diesel::delete(user_roles::table.filter(user_roles::valid_until.le(now)))
+        .get_results(conn)
+        .map_err(|e| {
+          error!("Failed to delete expired user_roles: {:?}", e);
+          AppError::from(e)
+        })?;
+      let archived: Vec<NewExpiredUserRole> = expired
+        .iter()
+        .filter_map(|user_role| {
+          user_role.valid_until.map(|valid_until| NewExpiredUserRole {
+            user_id: user_role.user_id,
+            role_id: user_role.role_id,
+            created_at: user_role.created_at,
+            valid_from: user_role.valid_from,
+            valid_until,
+          })
+        })
+        .collect();
+      diesel::insert_into(expired_user_roles::table)
+        .values(&archived)
+        .execute(conn)
+        .map_err(|e| {
+          error!("Failed to archive expired user_roles: {:?}", e);
+          AppError::from(e)
+        })?;
+      for user_role in &expired {
+        permission_cache::notify_user(conn, user_role.user_id)?;
+        events::publish(conn, &Event::UserRoleExpired {
+          user_id: user_role.user_id,
+          role_id: user_role.role_id,
+        })?;
+      }
+      Ok::<_, AppError>(expired)
+    })?;
+    for user_role in &expired {
+      permission_cache::invalidate_user(user_role.user_id);
+    }
+    if !expired.is_empty() {
+      info!("Archived {} expired user_roles", expired.len());
+    }
+    Ok(expired)
+  }
+
+  // Announces assignments whose validity started in (`since`, `now`], so cached permissions
+  // pick them up without waiting for the cache TTL.
+  pub fn announce_activated(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> Result<Vec<UserRole>, AppError> {
+    debug!("Looking up user_roles activated between {} and {}", since, now);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let activated = conn.transaction(|conn| {
+      let activated = user_roles::table
+        .filter(user_roles::valid_from.gt(since))
+        .filter(user_roles::valid_from.le(now))
+        .load::<UserRole>(conn)
+        .map_err(|e| {
+          error!("Failed to retrieve activated user_roles: {:?}", e);
+          AppError::from(e)
+        })?;
+      for user_role in &activated {
+        permission_cache::notify_user(conn, user_role.user_id)?;
+        events::publish(conn, &Event::UserRoleActivated {
+          user_id: user_role.user_id,
+          role_id: user_role.role_id,
+        })?;
+      }
+      Ok::<_, AppError>(activated)
+    })?;
+    for user_role in &activated {
+      permission_cache::invalidate_user(user_role.user_id);
+    }
+    if !activated.is_empty() {
+      info!("Announced {} activated user_roles", activated.len());
+    }
+    Ok(activated)
+  }
+}
+
+// Inserts the assignment within the caller's transaction once separation of duties allows it and
+// a role with deny grants does not take the admin permission from its last holder.
+// Callers invalidate the user's cached permissions after committing.
+pub fn assign(conn: &mut PgConnection, new_user_role: &NewUserRole, context: &AuditContext) -> Result<UserRole, AppError> {
+  guard_last_admin(conn, |conn| {
+    let (user_id, role_id) = (new_user_role.user_id, new_user_role.role_id);
+    sod_constraint::enforce(conn, &[user_id], &[role_id])?;
+    let user_role: UserRole = diesel::insert_into(user_roles::table)
+      .values(new_user_role)
+      .get_result(conn)
+      .map_err(|e| {
+        error!("Failed to create user_role for user_id={} and role_id={}: {:?}", user_id, role_id, e);
+        AppError::from(e)
+      })?;
+    permission_cache::notify_user(conn, user_id).map_err(|e| {
+      error!("Failed to publish permission change for user_id={}: {:?}", user_id, e);
+      AppError::from(e)
+    })?;
+    events::publish(conn, &Event::RoleAssigned {
+      user_id,
+      role_id,
+      valid_from: new_user_role.valid_from,
+      valid_until: new_user_role.valid_until,
+    })?;
+    audit::record(conn, context, &AuditRecord::created(TARGET_USER_ROLE, format!("{}/{}", user_id, role_id), &user_role))?;
+    Ok(user_role)
+  })
+}
+
+// Assignments whose validity window contains `now`; permission queries only consider these.
+#[diesel::dsl::auto_type]
+pub fn active_at(now: DateTime<Utc>) -> _ {
+  user_roles::valid_from
+    .is_null()
+    .or(user_roles::valid_from.le(now))
+    .and(user_roles::valid_until.is_null().or(user_roles::valid_until.gt(now)))
 }
\ No newline at end of file
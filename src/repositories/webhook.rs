@@ -0,0 +1,302 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use chrono::{DateTime, Duration, Utc};
+use crate::schema::{outbox_events, webhook_deliveries, webhooks};
+use crate::models::webhook::{
+  DueDelivery, NewWebhook, NewWebhookDelivery, OutboxEvent, UpdateWebhook, Webhook, WebhookDelivery, STATUS_DEAD, STATUS_DELIVERED,
+  STATUS_PENDING,
+};
+use crate::models::audit::{AuditContext, AuditRecord, TARGET_WEBHOOK};
+use crate::database::PgPool;
+use crate::repositories::audit;
+use crate::utilities::error::AppError;
+use log::{debug, error, info};
+
+pub struct WebhookRepository<'a> {
+  conn: &'a PgPool,
+}
+
+impl<'a> WebhookRepository<'a> {
+  pub fn new(conn: &'a PgPool) -> Self {
+    debug!("Creating WebhookRepository");
+    Self { conn }
+  }
+
+  pub fn create(&self, new_webhook: NewWebhook, context: &AuditContext) -> Result<Webhook, AppError> {
+    info!("Creating webhook in repository: {}", new_webhook.url);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Inserting webhook into database: {}", new_webhook.url);
+    let webhook: Webhook = conn.transaction(|conn| {
+      let webhook: Webhook = diesel::insert_into(webhooks::table)
+        .values(&new_webhook)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to create webhook {}: {:?}", new_webhook.url, e);
+          AppError::from(e)
+        })?;
+      audit::record(conn, context, &AuditRecord::created(TARGET_WEBHOOK, webhook.id, &webhook))?;
+      Ok::<_, AppError>(webhook)
+    })?;
+    info!("Webhook created successfully in repository: {}", webhook.id);
+    Ok(webhook)
+  }
+
+  pub fn find_all(&self) -> Result<Vec<Webhook>, AppError> {
+    info!("Listing webhooks in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for all webhooks");
+    let webhooks = webhooks::table
+      .order(webhooks::created_at.asc())
+      .load::<Webhook>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list webhooks: {:?}", e);
+        AppError::from(e)
+      })?;
+    info!("Found {} webhooks", webhooks.len());
+    Ok(webhooks)
+  }
+
+  pub fn find_by_id(&self, id: Uuid) -> Result<Webhook, AppError> {
+    info!("Looking up webhook by ID in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Querying database for webhook ID: {}", id);
+    let webhook = webhooks::table
+      .find(id)
+      .first(&mut conn)
+      .map_err(|e| {
+        error!("Failed to find webhook with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+    info!("Found webhook by ID in repository: {}", id);
+    Ok(webhook)
+  }
+
+  pub fn update(&self, id: Uuid, update_webhook: UpdateWebhook, context: &AuditContext) -> Result<Webhook, AppError> {
+    info!("Updating webhook in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Updating webhook in database: {}", id);
+    let webhook = conn.transaction(|conn| {
+      let before: Webhook = webhooks::table.find(id).for_update().first(conn)?;
+      let webhook: Webhook = diesel::update(webhooks::table.find(id))
+        .set(&update_webhook)
+        .get_result(conn)
+        .map_err(|e| {
+          error!("Failed to update webhook with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      audit::record(conn, context, &AuditRecord::updated(TARGET_WEBHOOK, id, &before, &webhook))?;
+      Ok::<_, AppError>(webhook)
+    })?;
+    info!("Webhook updated successfully in repository: {}", id);
+    Ok(webhook)
+  }
+
+  pub fn delete(&self, id: Uuid, context: &AuditContext) -> Result<(), AppError> {
+    info!("Deleting webhook in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    debug!("Deleting webhook from database: {}", id);
+    let deleted = conn.transaction(|conn| {
+      let deleted: Option<Webhook> = diesel::delete(webhooks::table.find(id))
+        .get_result(conn)
+        .optional()
+        .map_err(|e| {
+          error!("Failed to delete webhook with ID {}: {:?}", id, e);
+          AppError::from(e)
+        })?;
+      if let Some(before) = &deleted {
+        audit::record(conn, context, &AuditRecord::deleted(TARGET_WEBHOOK, id, before))?;
+      }
+      Ok::<_, AppError>(deleted)
+    })?;
+    if deleted.is_none() {
+      error!("Webhook with ID {} not found for deletion", id);
+      return Err(AppError::NotFound(format!("Webhook with ID {} not found", id)));
+    }
+    info!("Webhook deleted successfully in repository: {}", id);
+    Ok(())
+  }
+
+  // Creates a delivery per subscribed active webhook for up to `limit` undispatched outbox
+  // events. Events are locked so concurrent dispatchers do not fan out the same event twice.
+  pub fn fan_out(&self, limit: i64) -> Result<usize, AppError> {
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    conn.transaction(|conn| {
+      let events = outbox_events::table
+        .filter(outbox_events::dispatched_at.is_null())
+        .order(outbox_events::created_at.asc())
+        .limit(limit)
+        .for_update()
+        .skip_locked()
+        .load::<OutboxEvent>(conn)?;
+      if events.is_empty() {
+        return Ok::<_, AppError>(0);
+      }
+      let active = webhooks::table.filter(webhooks::active.eq(true)).load::<Webhook>(conn)?;
+      let deliveries: Vec<NewWebhookDelivery> = events
+        .iter()
+        .flat_map(|event| {
+          active
+            .iter()
+            .filter(|webhook| webhook.subscribes_to(&event.event_type))
+            .map(|webhook| NewWebhookDelivery { webhook_id: webhook.id, outbox_event_id: event.id })
+        })
+        .collect();
+      diesel::insert_into(webhook_deliveries::table)
+        .values(&deliveries)
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+      let ids: Vec<Uuid> = events.iter().map(|event| event.id).collect();
+      diesel::update(outbox_events::table.filter(outbox_events::id.eq_any(&ids)))
+        .set(outbox_events::dispatched_at.eq(Utc::now()))
+        .execute(conn)?;
+      debug!("Fanned out {} outbox events into {} deliveries", events.len(), deliveries.len());
+      Ok(events.len())
+    })
+  }
+
+  // Claims up to `limit` due deliveries to active webhooks by pushing their next attempt `lease`
+  // into the future, so a dispatcher that dies mid-delivery only delays them.
+  pub fn claim_due(&self, limit: i64, lease: Duration) -> Result<Vec<DueDelivery>, AppError> {
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    conn.transaction(|conn| {
+      let now = Utc::now();
+      let active = webhooks::table.filter(webhooks::active.eq(true)).select(webhooks::id);
+      let ids = webhook_deliveries::table
+        .filter(webhook_deliveries::status.eq(STATUS_PENDING))
+        .filter(webhook_deliveries::next_attempt_at.le(now))
+        .filter(webhook_deliveries::webhook_id.eq_any(active))
+        .order(webhook_deliveries::next_attempt_at.asc())
+        .limit(limit)
+        .select(webhook_deliveries::id)
+        .for_update()
+        .skip_locked()
+        .load::<Uuid>(conn)?;
+      if ids.is_empty() {
+        return Ok::<_, AppError>(Vec::new());
+      }
+      diesel::update(webhook_deliveries::table.filter(webhook_deliveries::id.eq_any(&ids)))
+        .set(webhook_deliveries::next_attempt_at.eq(now + lease))
+        .execute(conn)?;
+      let due = webhook_deliveries::table
+        .inner_join(webhooks::table)
+        .inner_join(outbox_events::table)
+        .filter(webhook_deliveries::id.eq_any(&ids))
+        .order(outbox_events::created_at.asc())
+        .load::<(WebhookDelivery, Webhook, OutboxEvent)>(conn)?
+        .into_iter()
+        .map(|(delivery, webhook, event)| DueDelivery { delivery, webhook, event })
+        .collect();
+      Ok(due)
+    })
+  }
+
+  pub fn record_success(&self, id: Uuid, status_code: i32) -> Result<(), AppError> {
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let now = Utc::now();
+    diesel::update(webhook_deliveries::table.find(id))
+      .set((
+        webhook_deliveries::status.eq(STATUS_DELIVERED),
+        webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+        webhook_deliveries::last_status_code.eq(status_code),
+        webhook_deliveries::last_error.eq(None::<String>),
+        webhook_deliveries::delivered_at.eq(now),
+      ))
+      .execute(&mut conn)?;
+    Ok(())
+  }
+
+  // Schedules another attempt at `next_attempt_at`, or dead-letters the delivery without one.
+  pub fn record_failure(&self, id: Uuid, status_code: Option<i32>, error: &str, next_attempt_at: Option<DateTime<Utc>>) -> Result<(), AppError> {
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let status = if next_attempt_at.is_some() { STATUS_PENDING } else { STATUS_DEAD };
+    diesel::update(webhook_deliveries::table.find(id))
+      .set((
+        webhook_deliveries::status.eq(status),
+        webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+        webhook_deliveries::last_status_code.eq(status_code),
+        webhook_deliveries::last_error.eq(error),
+        webhook_deliveries::next_attempt_at.eq(next_attempt_at.unwrap_or_else(Utc::now)),
+      ))
+      .execute(&mut conn)?;
+    Ok(())
+  }
+
+  pub fn find_dead(&self, webhook_id: Option<Uuid>) -> Result<Vec<(WebhookDelivery, OutboxEvent)>, AppError> {
+    info!("Listing dead webhook deliveries in repository");
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    let mut query = webhook_deliveries::table
+      .inner_join(outbox_events::table)
+      .filter(webhook_deliveries::status.eq(STATUS_DEAD))
+      .into_boxed();
+    if let Some(webhook_id) = webhook_id {
+      query = query.filter(webhook_deliveries::webhook_id.eq(webhook_id));
+    }
+    let deliveries = query
+      .order(webhook_deliveries::created_at.desc())
+      .load::<(WebhookDelivery, OutboxEvent)>(&mut conn)
+      .map_err(|e| {
+        error!("Failed to list dead webhook deliveries: {:?}", e);
+        AppError::from(e)
+      })?;
+    info!("Found {} dead webhook deliveries", deliveries.len());
+    Ok(deliveries)
+  }
+
+  // Moves a dead delivery back to pending with a fresh attempt budget.
+  pub fn retry(&self, id: Uuid) -> Result<(WebhookDelivery, OutboxEvent), AppError> {
+    info!("Retrying webhook delivery in repository: {}", id);
+    let mut conn = self.conn.get().map_err(|e| {
+      error!("Failed to get database connection: {}", e);
+      AppError::ConnectionError(format!("Failed to get database connection: {}", e))
+    })?;
+    conn.transaction(|conn| {
+      let delivery: WebhookDelivery = webhook_deliveries::table.find(id).for_update().first(conn).map_err(|e| {
+        error!("Failed to find webhook delivery with ID {}: {:?}", id, e);
+        AppError::from(e)
+      })?;
+      if delivery.status != STATUS_DEAD {
+        return Err(AppError::Conflict(format!("Webhook delivery {} is {}, not dead", id, delivery.status)));
+      }
+      let delivery = diesel::update(webhook_deliveries::table.find(id))
+        .set((
+          webhook_deliveries::status.eq(STATUS_PENDING),
+          webhook_deliveries::attempts.eq(0),
+          webhook_deliveries::next_attempt_at.eq(Utc::now()),
+        ))
+        .get_result::<WebhookDelivery>(conn)?;
+      let event = outbox_events::table.find(delivery.outbox_event_id).first::<OutboxEvent>(conn)?;
+      info!("Webhook delivery {} queued for retry", id);
+      Ok((delivery, event))
+    })
+  }
+}
\ No newline at end of file
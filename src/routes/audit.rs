@@ -0,0 +1,85 @@
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use validator::Validate;
+use crate::config::Config;
+use crate::database::PgPool;
+use crate::handlers::audit::AuditHandler;
+use crate::models::audit::{AuditEventResponse, AuditFilter, MAX_PAGE_SIZE};
+use log::{error, info};
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: i64 = 100;
+
+// `from` is inclusive and `until` exclusive; the newest events come first.
+#[derive(Deserialize, Validate)]
+pub struct ListAuditEventsQuery {
+  pub actor_id: Option<Uuid>,
+  #[validate(length(min = 1, max = 50))]
+  pub target_type: Option<String>,
+  #[validate(length(min = 1, max = 255))]
+  pub target_id: Option<String>,
+  pub from: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+  #[validate(range(min = 1, max = MAX_PAGE_SIZE))]
+  pub limit: Option<i64>,
+}
+
+pub struct AuditRoutes;
+
+impl AuditRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/audit_events")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::get().to(Self::list_audit_events))
+        .route("/verify", web::get().to(Self::verify_audit_chain)),
+    );
+  }
+
+  async fn list_audit_events(query: web::Query<ListAuditEventsQuery>, pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing list audit events request");
+    if let Err(e) = query.validate() {
+      error!("Validation failed for audit event listing: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let query = query.into_inner();
+    let filter = AuditFilter {
+      actor_id: query.actor_id,
+      target_type: query.target_type,
+      target_id: query.target_id,
+      from: query.from,
+      until: query.until,
+      limit: query.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+    };
+    let handler = AuditHandler::new(&pool);
+    match handler.find_all(&filter) {
+      Ok(events) => {
+        info!("Retrieved {} audit events", events.len());
+        HttpResponse::Ok().json(events.into_iter().map(AuditEventResponse::from).collect::<Vec<_>>())
+      }
+      Err(e) => {
+        error!("Failed to list audit events: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  // Responds 200 with `valid: false` and the first broken link when the chain was tampered with.
+  async fn verify_audit_chain(pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    info!("Processing verify audit chain request");
+    let handler = AuditHandler::new(&pool);
+    match handler.verify(config.audit.checkpoint_secret.as_deref()) {
+      Ok(report) => {
+        info!("Audit chain verification finished: valid={}", report.valid);
+        HttpResponse::Ok().json(report)
+      }
+      Err(e) => {
+        error!("Failed to verify the audit chain: {}", e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
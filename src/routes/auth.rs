@@ -1,6 +1,7 @@
-use actix_web::{web, HttpResponse, Responder, ResponseError};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 use log::{info, error};
 use crate::handlers::auth::{AuthHandler, RegisterRequest, LoginRequest};
+use crate::middlewares::jwt::audit_context;
 
 pub struct AuthRoutes;
 
@@ -13,10 +14,11 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
   );
 }
 
-async fn register(pool: web::Data<crate::database::PgPool>, config: web::Data<crate::config::Config>, req: web::Json<RegisterRequest>) -> impl Responder {
+async fn register(pool: web::Data<crate::database::PgPool>, config: web::Data<crate::config::Config>, req: web::Json<RegisterRequest>, http_req: HttpRequest) -> impl Responder {
   info!("Processing register request for username: {}", req.username);
-  let auth_handler = AuthHandler::new(&pool, config.auth.jwt_secret.clone(), config.auth.expiration_seconds);
-  match auth_handler.register(&req) {
+  let auth_handler = AuthHandler::new(&pool, config.auth.clone());
+  let context = audit_context(&http_req, &config.auth);
+  match auth_handler.register(&req, &context) {
     Ok(user_response) => {
       info!("User registered successfully: {}", user_response.username);
       HttpResponse::Ok().json(user_response)
@@ -28,10 +30,11 @@ async fn register(pool: web::Data<crate::database::PgPool>, config: web::Data<cr
   }
 }
 
-async fn login(pool: web::Data<crate::database::PgPool>, config: web::Data<crate::config::Config>, req: web::Json<LoginRequest>) -> impl Responder {
+async fn login(pool: web::Data<crate::database::PgPool>, config: web::Data<crate::config::Config>, req: web::Json<LoginRequest>, http_req: HttpRequest) -> impl Responder {
   info!("Processing login request for username: {}", req.username);
-  let auth_handler = AuthHandler::new(&pool, config.auth.jwt_secret.clone(), config.auth.expiration_seconds);
-  match auth_handler.login(&req) {
+  let auth_handler = AuthHandler::new(&pool, config.auth.clone());
+  let context = audit_context(&http_req, &config.auth);
+  match auth_handler.login(&req, &context) {
     Ok(login_response) => {
       info!("User logged in successfully: {}", login_response.user.username);
       HttpResponse::Ok().json(login_response)
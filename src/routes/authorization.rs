@@ -0,0 +1,88 @@
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::Validate;
+use crate::database::PgPool;
+use crate::handlers::authorization::AuthorizationHandler;
+use crate::middlewares::jwt::Claims;
+use crate::models::authorization::ResourceRef;
+use crate::models::condition::RequestContext;
+use chrono::{DateTime, Utc};
+use std::net::IpAddr;
+use crate::routes::resource_grant::validate_resource_type;
+use log::{error, info};
+use uuid::Uuid;
+
+#[derive(Deserialize, Validate)]
+pub struct CheckPermissionRequest {
+  // Defaults to the caller when omitted.
+  pub user_id: Option<Uuid>,
+  #[validate(length(min = 1, max = 50))]
+  pub permission: String,
+  // Both or neither; without a resource only unscoped grants apply.
+  #[validate(length(min = 1, max = 50), custom(function = "validate_resource_type"))]
+  pub resource_type: Option<String>,
+  #[validate(length(min = 1, max = 255))]
+  pub resource_id: Option<String>,
+  // Context for conditional grants; defaults to no client IP and the current time.
+  pub client_ip: Option<IpAddr>,
+  pub time: Option<DateTime<Utc>>,
+}
+
+pub struct AuthorizationRoutes;
+
+impl AuthorizationRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/authorization")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("/check", web::post().to(Self::check)),
+    );
+  }
+
+  async fn check(req: web::Json<CheckPermissionRequest>, claims: web::ReqData<Claims>, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing permission check request for permission: {}", req.permission);
+    if let Err(e) = req.0.validate() {
+      error!("Validation failed for permission check: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let resource = match (req.resource_type.as_deref(), req.resource_id.as_deref()) {
+      (Some(resource_type), Some(resource_id)) => Some(ResourceRef::new(resource_type, resource_id)),
+      (None, None) => None,
+      _ => {
+        error!("Permission check specified only one of resource_type and resource_id");
+        return HttpResponse::BadRequest().json(serde_json::json!({
+          "error": "Validation error: resource_type and resource_id must be given together"
+        }));
+      }
+    };
+    let user_id = match req.user_id {
+      Some(user_id) => user_id,
+      None => match Uuid::parse_str(&claims.sub) {
+        Ok(user_id) => user_id,
+        Err(e) => {
+          error!("Invalid user_id in token: {}", e);
+          return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid user ID in token"
+          }));
+        }
+      },
+    };
+    let mut context = RequestContext::new(req.client_ip);
+    if let Some(time) = req.time {
+      context.time = time;
+    }
+    let handler = AuthorizationHandler::new(&pool, config.auth.clone());
+    match handler.check(user_id, &req.permission, resource.as_ref(), &context) {
+      Ok(decision) => {
+        info!("Permission check completed for user_id={}: allowed={}", user_id, decision.allowed);
+        HttpResponse::Ok().json(decision)
+      }
+      Err(e) => {
+        error!("Failed to check permission {} for user_id={}: {}", req.permission, user_id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
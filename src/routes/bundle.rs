@@ -0,0 +1,98 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+use crate::database::PgPool;
+use crate::handlers::bundle::BundleHandler;
+use crate::middlewares::jwt::audit_context;
+use crate::models::bundle::BundleFormat;
+use log::{error, info};
+
+fn validate_format(format: &str) -> Result<(), ValidationError> {
+  if BundleFormat::parse(format).is_some() {
+    Ok(())
+  } else {
+    Err(ValidationError::new("invalid_format").with_message("Format must be 'json' or 'yaml'".into()))
+  }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ExportBundleQuery {
+  #[validate(custom(function = "validate_format"))]
+  pub format: Option<String>,
+  #[serde(default)]
+  pub include_user_roles: bool,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ImportBundleQuery {
+  #[validate(custom(function = "validate_format"))]
+  pub format: Option<String>,
+  #[serde(default)]
+  pub dry_run: bool,
+  #[serde(default)]
+  pub prune: bool,
+}
+
+pub struct BundleRoutes;
+
+impl BundleRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/rbac")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("/export", web::get().to(Self::export_bundle))
+        .route("/import", web::post().to(Self::import_bundle)),
+    );
+  }
+
+  async fn export_bundle(query: web::Query<ExportBundleQuery>, pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing RBAC export request");
+    if let Err(e) = query.validate() {
+      error!("Validation failed for RBAC export: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let format = query.format.as_deref().and_then(BundleFormat::parse).unwrap_or(BundleFormat::Json);
+    let handler = BundleHandler::new(&pool);
+    match handler.export(query.include_user_roles).and_then(|bundle| format.serialize(&bundle)) {
+      Ok(body) => {
+        info!("RBAC bundle exported successfully");
+        HttpResponse::Ok().content_type(format.content_type()).body(body)
+      }
+      Err(e) => {
+        error!("Failed to export RBAC bundle: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  // The format comes from `?format=` or, failing that, the Content-Type of the body.
+  async fn import_bundle(req: HttpRequest, query: web::Query<ImportBundleQuery>, body: String, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing RBAC import request (dry_run={}, prune={})", query.dry_run, query.prune);
+    if let Err(e) = query.validate() {
+      error!("Validation failed for RBAC import: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let content_type = req.headers().get("content-type").and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let format = match query.format.as_deref().and_then(BundleFormat::parse) {
+      Some(format) => format,
+      None if content_type.contains("yaml") => BundleFormat::Yaml,
+      None => BundleFormat::Json,
+    };
+    let handler = BundleHandler::new(&pool);
+    let context = audit_context(&req, &config.auth);
+    match format.deserialize(&body).and_then(|bundle| handler.import(&bundle, query.prune, query.dry_run, &context)) {
+      Ok(report) => {
+        info!("RBAC bundle import processed: {} changes", report.changes);
+        HttpResponse::Ok().json(report)
+      }
+      Err(e) => {
+        error!("Failed to import RBAC bundle: {}", e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
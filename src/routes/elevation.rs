@@ -0,0 +1,223 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+use crate::config::Config;
+use crate::database::PgPool;
+use crate::handlers::elevation::ElevationHandler;
+use crate::middlewares::jwt::{audit_context, request_context, Claims};
+use crate::models::elevation::{ElevationRequestResponse, STATUSES};
+use log::{error, info};
+use uuid::Uuid;
+
+fn validate_status(status: &str) -> Result<(), ValidationError> {
+  if STATUSES.contains(&status) {
+    Ok(())
+  } else {
+    Err(ValidationError::new("invalid_status").with_message(format!("status must be one of {}", STATUSES.join(", ")).into()))
+  }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct CreateElevationRequest {
+  pub role_id: Uuid,
+  #[validate(length(min = 10, max = 2000))]
+  pub justification: String,
+  // Defaults to `elevation.default_duration_minutes`.
+  pub duration_minutes: Option<i64>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct DecideElevationRequest {
+  #[validate(length(min = 1, max = 2000))]
+  pub comment: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ListElevationsQuery {
+  #[validate(custom(function = "validate_status"))]
+  pub status: Option<String>,
+  pub requester_id: Option<Uuid>,
+}
+
+pub struct ElevationRoutes;
+
+impl ElevationRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/elevations")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::post().to(Self::create_elevation))
+        .route("", web::get().to(Self::list_elevations))
+        .route("/mine", web::get().to(Self::list_my_elevations))
+        .route("/{id}", web::get().to(Self::get_elevation))
+        .route("/{id}/approve", web::post().to(Self::approve_elevation))
+        .route("/{id}/deny", web::post().to(Self::deny_elevation))
+        .route("/{id}/cancel", web::post().to(Self::cancel_elevation)),
+    );
+  }
+
+  fn caller_id(claims: &Claims) -> Result<Uuid, HttpResponse> {
+    Uuid::parse_str(&claims.sub).map_err(|e| {
+      error!("Invalid user_id in token: {}", e);
+      HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Invalid user ID in token"
+      }))
+    })
+  }
+
+  async fn create_elevation(req: web::Json<CreateElevationRequest>, claims: web::ReqData<Claims>, pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    info!("Processing elevation request for role_id={}", req.role_id);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for elevation request: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let requester_id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    let handler = ElevationHandler::new(&pool, config.auth.clone(), config.elevation.clone());
+    match handler.request(requester_id, req.role_id, &req.justification, req.duration_minutes) {
+      Ok(request) => {
+        info!("Elevation request created via route: {}", request.id);
+        HttpResponse::Ok().json(ElevationRequestResponse::from(request))
+      }
+      Err(e) => {
+        error!("Failed to create elevation request for role_id={}: {}", req.role_id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_elevations(query: web::Query<ListElevationsQuery>, pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    info!("Processing list elevation requests");
+    if let Err(e) = query.validate() {
+      error!("Validation failed for elevation listing: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = ElevationHandler::new(&pool, config.auth.clone(), config.elevation.clone());
+    match handler.find_all(query.status.as_deref(), query.requester_id) {
+      Ok(requests) => {
+        info!("Retrieved {} elevation requests", requests.len());
+        HttpResponse::Ok().json(requests.into_iter().map(ElevationRequestResponse::from).collect::<Vec<_>>())
+      }
+      Err(e) => {
+        error!("Failed to list elevation requests: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_my_elevations(claims: web::ReqData<Claims>, pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    let requester_id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    info!("Processing list elevation requests of requester_id={}", requester_id);
+    let handler = ElevationHandler::new(&pool, config.auth.clone(), config.elevation.clone());
+    match handler.find_all(None, Some(requester_id)) {
+      Ok(requests) => {
+        info!("Retrieved {} elevation requests for requester_id={}", requests.len(), requester_id);
+        HttpResponse::Ok().json(requests.into_iter().map(ElevationRequestResponse::from).collect::<Vec<_>>())
+      }
+      Err(e) => {
+        error!("Failed to list elevation requests for requester_id={}: {}", requester_id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_elevation(path: web::Path<Uuid>, pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing get elevation request for ID: {}", id);
+    let handler = ElevationHandler::new(&pool, config.auth.clone(), config.elevation.clone());
+    match handler.find_by_id(id) {
+      Ok((request, events)) => {
+        info!("Elevation request retrieved successfully: {}", id);
+        HttpResponse::Ok().json(ElevationRequestResponse::with_events(request, events))
+      }
+      Err(e) => {
+        error!("Failed to retrieve elevation request {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn approve_elevation(path: web::Path<Uuid>, req: web::Json<DecideElevationRequest>, http_req: HttpRequest, claims: web::ReqData<Claims>, pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing approve elevation request for ID: {}", id);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for elevation approval: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let approver_id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    let context = request_context(&http_req, &config.auth);
+    let audit = audit_context(&http_req, &config.auth);
+    let handler = ElevationHandler::new(&pool, config.auth.clone(), config.elevation.clone());
+    match handler.approve(id, approver_id, &claims, &context, req.comment.as_deref(), &audit) {
+      Ok(request) => {
+        info!("Elevation request approved via route: {}", id);
+        HttpResponse::Ok().json(ElevationRequestResponse::from(request))
+      }
+      Err(e) => {
+        error!("Failed to approve elevation request {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn deny_elevation(path: web::Path<Uuid>, req: web::Json<DecideElevationRequest>, http_req: HttpRequest, claims: web::ReqData<Claims>, pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing deny elevation request for ID: {}", id);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for elevation denial: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let approver_id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    let context = request_context(&http_req, &config.auth);
+    let handler = ElevationHandler::new(&pool, config.auth.clone(), config.elevation.clone());
+    match handler.deny(id, approver_id, &claims, &context, req.comment.as_deref()) {
+      Ok(request) => {
+        info!("Elevation request denied via route: {}", id);
+        HttpResponse::Ok().json(ElevationRequestResponse::from(request))
+      }
+      Err(e) => {
+        error!("Failed to deny elevation request {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn cancel_elevation(path: web::Path<Uuid>, claims: web::ReqData<Claims>, pool: web::Data<PgPool>, config: web::Data<Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing cancel elevation request for ID: {}", id);
+    let requester_id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    let handler = ElevationHandler::new(&pool, config.auth.clone(), config.elevation.clone());
+    match handler.cancel(id, requester_id) {
+      Ok(request) => {
+        info!("Elevation request cancelled via route: {}", id);
+        HttpResponse::Ok().json(ElevationRequestResponse::from(request))
+      }
+      Err(e) => {
+        error!("Failed to cancel elevation request {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
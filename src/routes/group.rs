@@ -0,0 +1,256 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::Validate;
+use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
+use crate::handlers::group::GroupHandler;
+use crate::models::group::GroupResponse;
+use log::{error, info};
+use uuid::Uuid;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateGroupRequest {
+  #[validate(length(min = 2, max = 100))]
+  pub name: String,
+  #[validate(length(min = 1))]
+  pub description: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateGroupRequest {
+  #[validate(length(min = 2, max = 100))]
+  pub name: Option<String>,
+  #[validate(length(min = 1))]
+  pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AddGroupUserRequest {
+  pub user_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct AddSubgroupRequest {
+  pub group_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct AddGroupRoleRequest {
+  pub role_id: Uuid,
+}
+
+pub struct GroupRoutes;
+
+impl GroupRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/groups")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::post().to(Self::create_group))
+        .route("", web::get().to(Self::list_groups))
+        .route("/{id}", web::get().to(Self::get_group))
+        .route("/{id}", web::put().to(Self::update_group))
+        .route("/{id}", web::delete().to(Self::delete_group))
+        .route("/{id}/members", web::get().to(Self::get_members))
+        .route("/{id}/users", web::post().to(Self::add_user))
+        .route("/{id}/users/{user_id}", web::delete().to(Self::remove_user))
+        .route("/{id}/groups", web::post().to(Self::add_subgroup))
+        .route("/{id}/groups/{child_id}", web::delete().to(Self::remove_subgroup))
+        .route("/{id}/roles", web::post().to(Self::add_role))
+        .route("/{id}/roles/{role_id}", web::delete().to(Self::remove_role)),
+    );
+  }
+
+  async fn create_group(req: web::Json<CreateGroupRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing create group request for name: {}", req.name);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for group creation: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(&req.name, req.description.as_deref(), &context) {
+      Ok(group) => {
+        info!("Group created successfully via route: {}", group.name);
+        HttpResponse::Ok().json(GroupResponse::from(group))
+      }
+      Err(e) => {
+        error!("Failed to create group {}: {}", req.name, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_groups(pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing list groups request");
+    let handler = GroupHandler::new(&pool);
+    match handler.find_all() {
+      Ok(groups) => {
+        info!("Retrieved {} groups", groups.len());
+        HttpResponse::Ok().json(groups.into_iter().map(GroupResponse::from).collect::<Vec<_>>())
+      }
+      Err(e) => {
+        error!("Failed to list groups: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_group(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = *path;
+    info!("Processing get group request for ID: {}", id);
+    let handler = GroupHandler::new(&pool);
+    match handler.find_by_id(id) {
+      Ok(group) => {
+        info!("Group retrieved successfully: {}", id);
+        HttpResponse::Ok().json(GroupResponse::from(group))
+      }
+      Err(e) => {
+        error!("Failed to retrieve group {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn update_group(path: web::Path<Uuid>, req: web::Json<UpdateGroupRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing update group request for ID: {}", id);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for group update: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.update(id, req.name.as_deref(), req.description.as_deref(), &context) {
+      Ok(group) => {
+        info!("Group updated successfully: {}", id);
+        HttpResponse::Ok().json(GroupResponse::from(group))
+      }
+      Err(e) => {
+        error!("Failed to update group {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn delete_group(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing delete group request for ID: {}", id);
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
+      Ok(()) => {
+        info!("Group deleted successfully: {}", id);
+        HttpResponse::Ok().finish()
+      }
+      Err(e) => {
+        error!("Failed to delete group {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_members(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = *path;
+    info!("Processing get group members request for ID: {}", id);
+    let handler = GroupHandler::new(&pool);
+    match handler.members(id) {
+      Ok(members) => {
+        info!("Group members retrieved successfully: {}", id);
+        HttpResponse::Ok().json(members)
+      }
+      Err(e) => {
+        error!("Failed to retrieve members of group {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn add_user(path: web::Path<Uuid>, req: web::Json<AddGroupUserRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing add user_id={} to group {}", req.user_id, id);
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.add_user(id, req.user_id, &context) {
+      Ok(()) => HttpResponse::Ok().finish(),
+      Err(e) => {
+        error!("Failed to add user_id={} to group {}: {}", req.user_id, id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn remove_user(path: web::Path<(Uuid, Uuid)>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let (id, user_id) = path.into_inner();
+    info!("Processing remove user_id={} from group {}", user_id, id);
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.remove_user(id, user_id, &context) {
+      Ok(()) => HttpResponse::Ok().finish(),
+      Err(e) => {
+        error!("Failed to remove user_id={} from group {}: {}", user_id, id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn add_subgroup(path: web::Path<Uuid>, req: web::Json<AddSubgroupRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing add group {} to group {}", req.group_id, id);
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.add_subgroup(id, req.group_id, &context) {
+      Ok(()) => HttpResponse::Ok().finish(),
+      Err(e) => {
+        error!("Failed to add group {} to group {}: {}", req.group_id, id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn remove_subgroup(path: web::Path<(Uuid, Uuid)>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let (id, child_id) = path.into_inner();
+    info!("Processing remove group {} from group {}", child_id, id);
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.remove_subgroup(id, child_id, &context) {
+      Ok(()) => HttpResponse::Ok().finish(),
+      Err(e) => {
+        error!("Failed to remove group {} from group {}: {}", child_id, id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn add_role(path: web::Path<Uuid>, req: web::Json<AddGroupRoleRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing assign role_id={} to group {}", req.role_id, id);
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.add_role(id, req.role_id, &context) {
+      Ok(()) => HttpResponse::Ok().finish(),
+      Err(e) => {
+        error!("Failed to assign role_id={} to group {}: {}", req.role_id, id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn remove_role(path: web::Path<(Uuid, Uuid)>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let (id, role_id) = path.into_inner();
+    info!("Processing remove role_id={} from group {}", role_id, id);
+    let handler = GroupHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.remove_role(id, role_id, &context) {
+      Ok(()) => HttpResponse::Ok().finish(),
+      Err(e) => {
+        error!("Failed to remove role_id={} from group {}: {}", role_id, id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
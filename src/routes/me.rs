@@ -0,0 +1,147 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::Validate;
+use crate::database::PgPool;
+use crate::middlewares::jwt::{audit_context, Claims};
+use crate::middlewares::scope::RequireScope;
+use crate::handlers::oauth::PROFILE_SCOPE;
+use crate::handlers::user::UserHandler;
+use crate::models::user::UserResponse;
+use log::{error, info};
+use uuid::Uuid;
+
+// Attributes feed authorization conditions, so users cannot edit their own.
+#[derive(Deserialize, Validate)]
+pub struct UpdateMeRequest {
+  #[validate(length(min = 3))]
+  pub username: Option<String>,
+  #[validate(email)]
+  pub email: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+  #[validate(length(min = 1))]
+  pub current_password: String,
+  #[validate(length(min = 8))]
+  pub new_password: String,
+}
+
+pub struct MeRoutes;
+
+impl MeRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/me")
+        .wrap(RequireScope::new(PROFILE_SCOPE))
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::get().to(Self::get_me))
+        .route("", web::patch().to(Self::update_me))
+        .route("/password", web::post().to(Self::change_password))
+        .route("/sessions", web::get().to(Self::list_sessions)),
+    );
+  }
+
+  fn caller_id(claims: &Claims) -> Result<Uuid, HttpResponse> {
+    Uuid::parse_str(&claims.sub).map_err(|e| {
+      error!("Invalid user_id in token: {}", e);
+      HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Invalid user ID in token"
+      }))
+    })
+  }
+
+  async fn get_me(claims: web::ReqData<Claims>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    info!("Processing get me request for user_id={}", id);
+    let handler = UserHandler::new(&pool);
+    match handler.find_by_id(id) {
+      Ok(user) => HttpResponse::Ok().json(UserResponse::from(user)),
+      Err(e) => {
+        error!("Failed to retrieve user {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn update_me(req: web::Json<UpdateMeRequest>, claims: web::ReqData<Claims>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    info!("Processing update me request for user_id={}", id);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for profile update: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = UserHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.update(id, req.username.as_deref(), req.email.as_deref(), None, &context) {
+      Ok(user) => {
+        info!("Profile updated successfully: {}", id);
+        HttpResponse::Ok().json(UserResponse::from(user))
+      }
+      Err(e) => {
+        error!("Failed to update profile {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn change_password(req: web::Json<ChangePasswordRequest>, claims: web::ReqData<Claims>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    info!("Processing change password request for user_id={}", id);
+    if claims.act.is_some() {
+      error!("Refusing password change for user_id={} with a delegated token", id);
+      return HttpResponse::Forbidden().json(serde_json::json!({
+        "error": "A delegated token cannot change the user's password"
+      }));
+    }
+    if let Err(e) = req.validate() {
+      error!("Validation failed for password change: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = UserHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    let session_id = Uuid::parse_str(&claims.jti).ok();
+    match handler.change_password(id, &req.current_password, &req.new_password, session_id, &context) {
+      Ok(()) => {
+        info!("Password changed successfully: {}", id);
+        HttpResponse::Ok().finish()
+      }
+      Err(e) => {
+        error!("Failed to change password of {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_sessions(claims: web::ReqData<Claims>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = match Self::caller_id(&claims) {
+      Ok(id) => id,
+      Err(response) => return response,
+    };
+    info!("Processing list sessions request for user_id={}", id);
+    let handler = UserHandler::new(&pool);
+    match handler.sessions(id, Uuid::parse_str(&claims.jti).ok()) {
+      Ok(sessions) => {
+        info!("Retrieved {} sessions for user_id={}", sessions.len(), id);
+        HttpResponse::Ok().json(sessions)
+      }
+      Err(e) => {
+        error!("Failed to list sessions of {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
@@ -6,6 +6,19 @@ pub mod role;
 pub mod permission;
 pub mod user_role;
 pub mod role_permission;
+pub mod oauth;
+pub mod authorization;
+pub mod resource_grant;
+pub mod relation;
+pub mod policy;
+pub mod elevation;
+pub mod sod_constraint;
+pub mod group;
+pub mod bundle;
+pub mod audit;
+pub mod webhook;
+pub mod permission_event;
+pub mod me;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
   cfg.service(
@@ -19,6 +32,19 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
           .configure(permission::PermissionRoutes::configure)
           .configure(user_role::UserRoleRoutes::configure)
           .configure(role_permission::RolePermissionRoutes::configure)
+          .configure(oauth::OAuthRoutes::configure)
+          .configure(authorization::AuthorizationRoutes::configure)
+          .configure(resource_grant::ResourceGrantRoutes::configure)
+          .configure(relation::RelationRoutes::configure)
+          .configure(policy::PolicyRoutes::configure)
+          .configure(elevation::ElevationRoutes::configure)
+          .configure(sod_constraint::SodConstraintRoutes::configure)
+          .configure(group::GroupRoutes::configure)
+          .configure(bundle::BundleRoutes::configure)
+          .configure(audit::AuditRoutes::configure)
+          .configure(webhook::WebhookRoutes::configure)
+          .configure(permission_event::PermissionEventRoutes::configure)
+          .configure(me::MeRoutes::configure)
       )
   );
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use crate::database::PgPool;
+use crate::handlers::oauth::{OAuthHandler, IntrospectionRequest, TokenExchangeRequest};
+use crate::middlewares::jwt::{audit_context, Claims};
+use log::{error, info};
+
+pub struct OAuthRoutes;
+
+impl OAuthRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/oauth")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("/token", web::post().to(Self::token))
+        .route("/introspect", web::post().to(Self::introspect)),
+    );
+  }
+
+  async fn token(req: web::Form<TokenExchangeRequest>, claims: web::ReqData<Claims>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing token exchange request from actor: {}", claims.sub);
+    let handler = OAuthHandler::new(&pool, config.auth.clone());
+    let context = audit_context(&http_req, &config.auth);
+    match handler.exchange(&claims, &req, &context) {
+      Ok(response) => {
+        info!("Token exchange completed for actor {} and audience {}", claims.sub, req.audience);
+        HttpResponse::Ok().json(response)
+      }
+      Err(e) => {
+        error!("Token exchange failed for actor {}: {}", claims.sub, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn introspect(req: web::Form<IntrospectionRequest>, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing token introspection request");
+    let handler = OAuthHandler::new(&pool, config.auth.clone());
+    match handler.introspect(&req) {
+      Ok(response) => {
+        info!("Token introspection completed: active={}", response.active);
+        HttpResponse::Ok().json(response)
+      }
+      Err(e) => {
+        error!("Failed to introspect token: {}", e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
@@ -1,15 +1,28 @@
-use actix_web::{web, HttpResponse, Responder, ResponseError};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
 use crate::handlers::permission::PermissionHandler;
 use crate::models::permission::PermissionResponse;
+use crate::utilities::permission_matcher;
 use log::{error, info};
 use uuid::Uuid;
 
+// Permission names are dot-separated namespaces, optionally ending in a `*` wildcard segment
+fn validate_permission_name(name: &str) -> Result<(), ValidationError> {
+  if permission_matcher::is_valid_name(name) {
+    Ok(())
+  } else {
+    Err(ValidationError::new("invalid_permission_name").with_message(
+      "Permission names must be dot-separated lowercase segments, optionally ending in '*'".into(),
+    ))
+  }
+}
+
 #[derive(Deserialize, Validate)]
 pub struct CreatePermissionRequest {
-  #[validate(length(min = 2))]
+  #[validate(length(min = 1, max = 50), custom(function = "validate_permission_name"))]
   pub name: String,
   #[validate(length(min = 1))]
   pub description: Option<String>,
@@ -17,7 +30,7 @@ pub struct CreatePermissionRequest {
 
 #[derive(Deserialize, Validate)]
 pub struct UpdatePermissionRequest {
-  #[validate(length(min = 2))]
+  #[validate(length(min = 1, max = 50), custom(function = "validate_permission_name"))]
   pub name: Option<String>,
   #[validate(length(min = 1))]
   pub description: Option<String>,
@@ -37,7 +50,7 @@ impl PermissionRoutes {
     );
   }
 
-  async fn create_permission(req: web::Json<CreatePermissionRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn create_permission(req: web::Json<CreatePermissionRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     info!("Processing create permission request for name: {}", req.name);
     if let Err(e) = req.validate() {
       error!("Validation failed for permission creation: {}", e);
@@ -46,7 +59,8 @@ impl PermissionRoutes {
       }));
     }
     let handler = PermissionHandler::new(&pool);
-    match handler.create(&req.name, req.description.as_deref()) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(&req.name, req.description.as_deref(), &context) {
       Ok(permission) => {
         info!("Permission created successfully via route: {}", permission.name);
         HttpResponse::Ok().json(PermissionResponse::from(permission))
@@ -74,7 +88,7 @@ impl PermissionRoutes {
     }
   }
 
-  async fn update_permission(path: web::Path<Uuid>, req: web::Json<UpdatePermissionRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn update_permission(path: web::Path<Uuid>, req: web::Json<UpdatePermissionRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let id = *path;
     info!("Processing update permission request for ID: {}", id);
     if let Err(e) = req.validate() {
@@ -84,7 +98,8 @@ impl PermissionRoutes {
       }));
     }
     let handler = PermissionHandler::new(&pool);
-    match handler.update(id, req.name.as_deref(), req.description.as_deref()) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.update(id, req.name.as_deref(), req.description.as_deref(), &context) {
       Ok(permission) => {
         info!("Permission updated successfully: {}", id);
         HttpResponse::Ok().json(PermissionResponse::from(permission))
@@ -96,11 +111,12 @@ impl PermissionRoutes {
     }
   }
 
-  async fn delete_permission(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn delete_permission(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let id = *path;
     info!("Processing delete permission request for ID: {}", id);
     let handler = PermissionHandler::new(&pool);
-    match handler.delete(id) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
       Ok(()) => {
         info!("Permission deleted successfully: {}", id);
         HttpResponse::Ok().finish()
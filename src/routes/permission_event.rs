@@ -0,0 +1,54 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::Deserialize;
+use crate::middlewares::jwt::Claims;
+use crate::utilities::event_stream::{self, StreamFilter, StreamSession};
+use log::{error, info};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct PermissionEventsQuery {
+  pub user_id: Option<Uuid>,
+  pub role_id: Option<Uuid>,
+}
+
+pub struct PermissionEventRoutes;
+
+impl PermissionEventRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/permission_events")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::get().to(Self::stream_permission_events)),
+    );
+  }
+
+  // A `permissions_changed` event with scope `all` means every cached permission is stale; it
+  // is also sent whenever the stream may have missed changes. The stream closes when the token
+  // expires or its session is revoked, and when the client falls too far behind.
+  async fn stream_permission_events(query: web::Query<PermissionEventsQuery>, claims: web::ReqData<Claims>) -> impl Responder {
+    info!("Processing permission event stream request (user_id={:?}, role_id={:?})", query.user_id, query.role_id);
+    let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+      error!("Invalid user_id in token: {}", claims.sub);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Invalid user ID in token"
+      }));
+    };
+    let filter = StreamFilter {
+      user_id: query.user_id,
+      role_id: query.role_id,
+    };
+    let session = StreamSession {
+      user_id,
+      session_id: Uuid::parse_str(&claims.jti).ok(),
+      expires_at: DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now),
+    };
+    let stream = event_stream::subscribe(filter, session).map(Ok::<_, actix_web::Error>);
+    HttpResponse::Ok()
+      .content_type("text/event-stream")
+      .insert_header(("Cache-Control", "no-cache"))
+      .insert_header(("X-Accel-Buffering", "no"))
+      .streaming(stream)
+  }
+}
\ No newline at end of file
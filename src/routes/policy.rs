@@ -0,0 +1,141 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::Validate;
+use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
+use crate::handlers::policy::PolicyHandler;
+use crate::models::policy::PolicyResponse;
+use log::{error, info};
+use uuid::Uuid;
+
+#[derive(Deserialize, Validate)]
+pub struct CreatePolicyRequest {
+  #[validate(length(min = 1, max = 100))]
+  pub name: String,
+  #[validate(length(min = 1))]
+  pub body: String,
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UpdatePolicyRequest {
+  #[validate(length(min = 1, max = 100))]
+  pub name: Option<String>,
+  #[validate(length(min = 1))]
+  pub body: Option<String>,
+  pub enabled: Option<bool>,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+pub struct PolicyRoutes;
+
+impl PolicyRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/policies")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::post().to(Self::create_policy))
+        .route("", web::get().to(Self::list_policies))
+        .route("/{id}", web::get().to(Self::get_policy))
+        .route("/{id}", web::put().to(Self::update_policy))
+        .route("/{id}", web::delete().to(Self::delete_policy)),
+    );
+  }
+
+  async fn create_policy(req: web::Json<CreatePolicyRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing create policy request for name: {}", req.name);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for policy creation: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = PolicyHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(&req.name, &req.body, req.enabled, &context) {
+      Ok(policy) => {
+        info!("Policy created successfully via route: {}", policy.name);
+        HttpResponse::Ok().json(PolicyResponse::from(policy))
+      }
+      Err(e) => {
+        error!("Failed to create policy {}: {}", req.name, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_policies(pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing list policies request");
+    let handler = PolicyHandler::new(&pool);
+    match handler.find_all() {
+      Ok(policies) => {
+        info!("Policies retrieved successfully");
+        HttpResponse::Ok().json(policies.into_iter().map(PolicyResponse::from).collect::<Vec<_>>())
+      }
+      Err(e) => {
+        error!("Failed to list policies: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_policy(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = *path;
+    info!("Processing get policy request for ID: {}", id);
+    let handler = PolicyHandler::new(&pool);
+    match handler.find_by_id(id) {
+      Ok(policy) => {
+        info!("Policy retrieved successfully: {}", id);
+        HttpResponse::Ok().json(PolicyResponse::from(policy))
+      }
+      Err(e) => {
+        error!("Failed to retrieve policy {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn update_policy(path: web::Path<Uuid>, req: web::Json<UpdatePolicyRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing update policy request for ID: {}", id);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for policy update: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = PolicyHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.update(id, req.name.as_deref(), req.body.as_deref(), req.enabled, &context) {
+      Ok(policy) => {
+        info!("Policy updated successfully: {}", id);
+        HttpResponse::Ok().json(PolicyResponse::from(policy))
+      }
+      Err(e) => {
+        error!("Failed to update policy {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn delete_policy(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing delete policy request for ID: {}", id);
+    let handler = PolicyHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
+      Ok(()) => {
+        info!("Policy deleted successfully: {}", id);
+        HttpResponse::Ok().finish()
+      }
+      Err(e) => {
+        error!("Failed to delete policy {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
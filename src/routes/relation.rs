@@ -0,0 +1,140 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use validator::Validate;
+use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
+use crate::handlers::relation::{RelationHandler, TupleRequest, ExpandRequest, ListObjectsRequest, CheckResponse, ListObjectsResponse};
+use crate::models::namespace::NamespaceConfig;
+use crate::models::relation_tuple::RelationTupleResponse;
+use log::{error, info};
+
+pub struct RelationRoutes;
+
+impl RelationRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/relations")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("/tuples", web::post().to(Self::write_tuple))
+        .route("/tuples", web::delete().to(Self::delete_tuple))
+        .route("/tuples/{namespace}/{object_id}", web::get().to(Self::read_tuples))
+        .route("/check", web::post().to(Self::check))
+        .route("/expand", web::post().to(Self::expand))
+        .route("/list_objects", web::post().to(Self::list_objects)),
+    );
+  }
+
+  async fn write_tuple(req: web::Json<TupleRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>, namespaces: web::Data<NamespaceConfig>) -> impl Responder {
+    info!("Processing write relation tuple request: {}:{}#{}", req.namespace, req.object_id, req.relation);
+    if let Err(e) = req.0.validate() {
+      error!("Validation failed for relation tuple: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = RelationHandler::new(&pool, &namespaces);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.write(&req, &context) {
+      Ok(tuple) => {
+        info!("Relation tuple written successfully: {}", tuple.id);
+        HttpResponse::Ok().json(RelationTupleResponse::from(tuple))
+      }
+      Err(e) => {
+        error!("Failed to write relation tuple: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn delete_tuple(req: web::Json<TupleRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>, namespaces: web::Data<NamespaceConfig>) -> impl Responder {
+    info!("Processing delete relation tuple request: {}:{}#{}", req.namespace, req.object_id, req.relation);
+    if let Err(e) = req.0.validate() {
+      error!("Validation failed for relation tuple: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = RelationHandler::new(&pool, &namespaces);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(&req, &context) {
+      Ok(()) => {
+        info!("Relation tuple deleted successfully");
+        HttpResponse::Ok().finish()
+      }
+      Err(e) => {
+        error!("Failed to delete relation tuple: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn read_tuples(path: web::Path<(String, String)>, pool: web::Data<PgPool>, namespaces: web::Data<NamespaceConfig>) -> impl Responder {
+    let (namespace, object_id) = path.into_inner();
+    info!("Processing read relation tuples request: {}:{}", namespace, object_id);
+    let handler = RelationHandler::new(&pool, &namespaces);
+    match handler.read(&namespace, &object_id) {
+      Ok(tuples) => {
+        info!("Retrieved {} relation tuples for {}:{}", tuples.len(), namespace, object_id);
+        let response: Vec<RelationTupleResponse> = tuples.into_iter().map(RelationTupleResponse::from).collect();
+        HttpResponse::Ok().json(response)
+      }
+      Err(e) => {
+        error!("Failed to read relation tuples for {}:{}: {}", namespace, object_id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn check(req: web::Json<TupleRequest>, pool: web::Data<PgPool>, namespaces: web::Data<NamespaceConfig>) -> impl Responder {
+    info!("Processing relation check request: {}:{}#{}", req.namespace, req.object_id, req.relation);
+    if let Err(e) = req.0.validate() {
+      error!("Validation failed for relation check: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = RelationHandler::new(&pool, &namespaces);
+    match handler.check(&req.namespace, &req.object_id, &req.relation, &req.subject) {
+      Ok(allowed) => HttpResponse::Ok().json(CheckResponse { allowed }),
+      Err(e) => {
+        error!("Failed to check relation: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn expand(req: web::Json<ExpandRequest>, pool: web::Data<PgPool>, namespaces: web::Data<NamespaceConfig>) -> impl Responder {
+    info!("Processing relation expand request: {}:{}#{}", req.namespace, req.object_id, req.relation);
+    if let Err(e) = req.0.validate() {
+      error!("Validation failed for relation expand: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = RelationHandler::new(&pool, &namespaces);
+    match handler.expand(&req) {
+      Ok(tree) => HttpResponse::Ok().json(tree),
+      Err(e) => {
+        error!("Failed to expand relation: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_objects(req: web::Json<ListObjectsRequest>, pool: web::Data<PgPool>, namespaces: web::Data<NamespaceConfig>) -> impl Responder {
+    info!("Processing list objects request: {}#{}", req.namespace, req.relation);
+    if let Err(e) = req.0.validate() {
+      error!("Validation failed for list objects: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = RelationHandler::new(&pool, &namespaces);
+    match handler.list_objects(&req) {
+      Ok(object_ids) => HttpResponse::Ok().json(ListObjectsResponse { object_ids }),
+      Err(e) => {
+        error!("Failed to list objects: {}", e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
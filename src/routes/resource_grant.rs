@@ -0,0 +1,117 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
+use crate::handlers::resource_grant::ResourceGrantHandler;
+use crate::models::resource_grant::ResourceGrantResponse;
+use log::{error, info};
+use uuid::Uuid;
+
+pub fn validate_resource_type(resource_type: &str) -> Result<(), ValidationError> {
+  if resource_type.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+    Ok(())
+  } else {
+    Err(ValidationError::new("invalid_resource_type").with_message(
+      "Resource types may only contain lowercase letters, digits, '_' and '-'".into(),
+    ))
+  }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct CreateResourceGrantRequest {
+  pub user_id: Uuid,
+  pub role_id: Uuid,
+  #[validate(length(min = 1, max = 50), custom(function = "validate_resource_type"))]
+  pub resource_type: String,
+  // '*' grants the role on every resource of the type.
+  #[validate(length(min = 1, max = 255))]
+  pub resource_id: String,
+}
+
+pub struct ResourceGrantRoutes;
+
+impl ResourceGrantRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/resource_grants")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::post().to(Self::create_resource_grant))
+        .route("/users/{user_id}", web::get().to(Self::get_resource_grants_by_user))
+        .route("/{id}", web::get().to(Self::get_resource_grant))
+        .route("/{id}", web::delete().to(Self::delete_resource_grant)),
+    );
+  }
+
+  async fn create_resource_grant(req: web::Json<CreateResourceGrantRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing create resource_grant request: user_id={}, role_id={}", req.user_id, req.role_id);
+    if let Err(e) = req.0.validate() {
+      error!("Validation failed for resource_grant creation: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = ResourceGrantHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(req.user_id, req.role_id, &req.resource_type, &req.resource_id, &context) {
+      Ok(resource_grant) => {
+        info!("ResourceGrant created successfully via route: {}", resource_grant.id);
+        HttpResponse::Ok().json(ResourceGrantResponse::from(resource_grant))
+      }
+      Err(e) => {
+        error!("Failed to create resource_grant for user_id={} and role_id={}: {}", req.user_id, req.role_id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_resource_grant(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = *path;
+    info!("Processing get resource_grant request: {}", id);
+    let handler = ResourceGrantHandler::new(&pool);
+    match handler.find_by_id(id) {
+      Ok(resource_grant) => {
+        info!("ResourceGrant retrieved successfully: {}", id);
+        HttpResponse::Ok().json(ResourceGrantResponse::from(resource_grant))
+      }
+      Err(e) => {
+        error!("Failed to retrieve resource_grant {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_resource_grants_by_user(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let user_id = *path;
+    info!("Processing get resource_grants request for user_id={}", user_id);
+    let handler = ResourceGrantHandler::new(&pool);
+    match handler.find_by_user_id(user_id) {
+      Ok(resource_grants) => {
+        info!("Retrieved {} resource_grants for user_id={}", resource_grants.len(), user_id);
+        let response: Vec<ResourceGrantResponse> = resource_grants.into_iter().map(ResourceGrantResponse::from).collect();
+        HttpResponse::Ok().json(response)
+      }
+      Err(e) => {
+        error!("Failed to retrieve resource_grants for user_id={}: {}", user_id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn delete_resource_grant(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing delete resource_grant request: {}", id);
+    let handler = ResourceGrantHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
+      Ok(()) => {
+        info!("ResourceGrant deleted successfully: {}", id);
+        HttpResponse::Ok().finish()
+      }
+      Err(e) => {
+        error!("Failed to delete resource_grant {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
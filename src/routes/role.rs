@@ -1,7 +1,8 @@
-use actix_web::{web, HttpResponse, Responder, ResponseError};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use validator::Validate;
 use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
 use crate::handlers::role::RoleHandler;
 use crate::models::role::RoleResponse;
 use log::{error, info};
@@ -37,7 +38,7 @@ impl RoleRoutes {
     );
   }
 
-  async fn create_role(req: web::Json<CreateRoleRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn create_role(req: web::Json<CreateRoleRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     info!("Processing create role request for name: {}", req.name);
     if let Err(e) = req.validate() {
       error!("Validation failed for role creation: {}", e);
@@ -46,7 +47,8 @@ impl RoleRoutes {
       }));
     }
     let handler = RoleHandler::new(&pool);
-    match handler.create(&req.name, req.description.as_deref()) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(&req.name, req.description.as_deref(), &context) {
       Ok(role) => {
         info!("Role created successfully via route: {}", role.name);
         HttpResponse::Ok().json(RoleResponse::from(role))
@@ -74,7 +76,7 @@ impl RoleRoutes {
     }
   }
 
-  async fn update_role(path: web::Path<Uuid>, req: web::Json<UpdateRoleRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn update_role(path: web::Path<Uuid>, req: web::Json<UpdateRoleRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let id = *path;
     info!("Processing update role request for ID: {}", id);
     if let Err(e) = req.validate() {
@@ -84,7 +86,8 @@ impl RoleRoutes {
       }));
     }
     let handler = RoleHandler::new(&pool);
-    match handler.update(id, req.name.as_deref(), req.description.as_deref()) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.update(id, req.name.as_deref(), req.description.as_deref(), &context) {
       Ok(role) => {
         info!("Role updated successfully: {}", id);
         HttpResponse::Ok().json(RoleResponse::from(role))
@@ -96,11 +99,12 @@ impl RoleRoutes {
     }
   }
 
-  async fn delete_role(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn delete_role(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let id = *path;
     info!("Processing delete role request for ID: {}", id);
     let handler = RoleHandler::new(&pool);
-    match handler.delete(id) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
       Ok(()) => {
         info!("Role deleted successfully: {}", id);
         HttpResponse::Ok().finish()
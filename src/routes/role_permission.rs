@@ -1,8 +1,11 @@
-use actix_web::{web, HttpResponse, Responder, ResponseError};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use validator::{Validate, ValidationError};
 use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
 use crate::handlers::role_permission::RolePermissionHandler;
+use crate::models::authorization::{Effect, EFFECT_ALLOW};
+use crate::models::condition::Conditions;
 use crate::models::role_permission::RolePermissionResponse;
 use log::{error, info};
 use uuid::Uuid;
@@ -17,12 +20,28 @@ fn validate_uuid(uuid: &Uuid) -> Result<(), ValidationError> {
   }
 }
 
+fn validate_effect(effect: &str) -> Result<(), ValidationError> {
+  if Effect::parse(effect).is_some() {
+    Ok(())
+  } else {
+    Err(ValidationError::new("invalid_effect").with_message("Effect must be 'allow' or 'deny'".into()))
+  }
+}
+
+fn validate_conditions(conditions: &Conditions) -> Result<(), ValidationError> {
+  conditions.validate().map_err(|e| ValidationError::new("invalid_conditions").with_message(e.into()))
+}
+
 #[derive(Deserialize, Validate)]
 pub struct CreateRolePermissionRequest {
   #[validate(custom(function = "validate_uuid"))]
   pub role_id: Uuid,
   #[validate(custom(function = "validate_uuid"))]
   pub permission_id: Uuid,
+  #[validate(custom(function = "validate_effect"))]
+  pub effect: Option<String>,
+  #[validate(custom(function = "validate_conditions"))]
+  pub conditions: Option<Conditions>,
 }
 
 pub struct RolePermissionRoutes;
@@ -39,7 +58,7 @@ impl RolePermissionRoutes {
     );
   }
 
-  async fn create_role_permission(req: web::Json<CreateRolePermissionRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn create_role_permission(req: web::Json<CreateRolePermissionRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     info!("Processing create role_permission request: role_id={}, permission_id={}", req.role_id, req.permission_id);
     if let Err(e) = req.0.validate() {
       error!("Validation failed for role_permission creation: {}", e);
@@ -48,7 +67,9 @@ impl RolePermissionRoutes {
       }));
     }
     let handler = RolePermissionHandler::new(&pool);
-    match handler.create(req.role_id, req.permission_id) {
+    let effect = req.effect.as_deref().unwrap_or(EFFECT_ALLOW);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(req.role_id, req.permission_id, effect, req.conditions.as_ref(), &context) {
       Ok(role_permission) => {
         info!("RolePermission created successfully via route: role_id={}, permission_id={}", req.role_id, req.permission_id);
         HttpResponse::Ok().json(RolePermissionResponse::from(role_permission))
@@ -93,11 +114,12 @@ impl RolePermissionRoutes {
     }
   }
 
-  async fn delete_role_permission(path: web::Path<(Uuid, Uuid)>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn delete_role_permission(path: web::Path<(Uuid, Uuid)>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let (role_id, permission_id) = path.into_inner();
     info!("Processing delete role_permission request: role_id={}, permission_id={}", role_id, permission_id);
     let handler = RolePermissionHandler::new(&pool);
-    match handler.delete(role_id, permission_id) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(role_id, permission_id, &context) {
       Ok(()) => {
         info!("RolePermission deleted successfully: role_id={}, permission_id={}", role_id, permission_id);
         HttpResponse::Ok().finish()
@@ -0,0 +1,121 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::Validate;
+use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
+use crate::handlers::sod_constraint::SodConstraintHandler;
+use crate::models::sod_constraint::SodConstraintResponse;
+use log::{error, info};
+use uuid::Uuid;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateSodConstraintRequest {
+  #[validate(length(min = 2, max = 100))]
+  pub name: String,
+  #[validate(length(min = 1))]
+  pub description: Option<String>,
+  // Roles nobody may hold two of at the same time.
+  #[validate(length(min = 2))]
+  pub role_ids: Vec<Uuid>,
+}
+
+pub struct SodConstraintRoutes;
+
+impl SodConstraintRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/sod_constraints")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::post().to(Self::create_constraint))
+        .route("", web::get().to(Self::list_constraints))
+        .route("/violations", web::get().to(Self::list_violations))
+        .route("/{id}", web::get().to(Self::get_constraint))
+        .route("/{id}", web::delete().to(Self::delete_constraint)),
+    );
+  }
+
+  async fn create_constraint(req: web::Json<CreateSodConstraintRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing create SoD constraint request for name: {}", req.name);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for SoD constraint creation: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = SodConstraintHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(&req.name, req.description.as_deref(), &req.role_ids, &context) {
+      Ok(constraint) => {
+        info!("SoD constraint created successfully via route: {}", req.name);
+        HttpResponse::Ok().json(SodConstraintResponse::from(constraint))
+      }
+      Err(e) => {
+        error!("Failed to create SoD constraint {}: {}", req.name, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_constraints(pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing list SoD constraints request");
+    let handler = SodConstraintHandler::new(&pool);
+    match handler.find_all() {
+      Ok(constraints) => {
+        info!("Retrieved {} SoD constraints", constraints.len());
+        HttpResponse::Ok().json(constraints.into_iter().map(SodConstraintResponse::from).collect::<Vec<_>>())
+      }
+      Err(e) => {
+        error!("Failed to list SoD constraints: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_violations(pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing SoD violation report request");
+    let handler = SodConstraintHandler::new(&pool);
+    match handler.violations() {
+      Ok(violations) => {
+        info!("Retrieved {} SoD violations", violations.len());
+        HttpResponse::Ok().json(violations)
+      }
+      Err(e) => {
+        error!("Failed to build SoD violation report: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_constraint(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = *path;
+    info!("Processing get SoD constraint request for ID: {}", id);
+    let handler = SodConstraintHandler::new(&pool);
+    match handler.find_by_id(id) {
+      Ok(constraint) => {
+        info!("SoD constraint retrieved successfully: {}", id);
+        HttpResponse::Ok().json(SodConstraintResponse::from(constraint))
+      }
+      Err(e) => {
+        error!("Failed to retrieve SoD constraint {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn delete_constraint(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing delete SoD constraint request for ID: {}", id);
+    let handler = SodConstraintHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
+      Ok(()) => {
+        info!("SoD constraint deleted successfully: {}", id);
+        HttpResponse::Ok().finish()
+      }
+      Err(e) => {
+        error!("Failed to delete SoD constraint {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
@@ -1,10 +1,13 @@
-use actix_web::{web, HttpResponse, Responder, ResponseError};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use validator::Validate;
 use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
+use crate::handlers::authorization::AuthorizationHandler;
 use crate::handlers::user::UserHandler;
 use crate::models::user::UserResponse;
 use log::{error, info};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Deserialize, Validate)]
@@ -27,6 +30,13 @@ pub struct UpdateUserRequest {
   pub password: Option<String>,
 }
 
+// Attributes feed conditional grants, so they are set through their own admin-only endpoint.
+#[derive(Deserialize)]
+pub struct UpdateUserAttributesRequest {
+  // Replaces every attribute of the user.
+  pub attributes: HashMap<String, String>,
+}
+
 pub struct UserRoutes;
 
 impl UserRoutes {
@@ -36,12 +46,14 @@ impl UserRoutes {
         .wrap(crate::middlewares::jwt::JwtMiddleware)
         .route("", web::post().to(Self::create_user))
         .route("/{id}", web::get().to(Self::get_user))
+        .route("/{id}/effective_permissions", web::get().to(Self::get_effective_permissions))
         .route("/{id}", web::put().to(Self::update_user))
+        .route("/{id}/attributes", web::put().to(Self::update_user_attributes))
         .route("/{id}", web::delete().to(Self::delete_user)),
     );
   }
 
-  async fn create_user(req: web::Json<CreateUserRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn create_user(req: web::Json<CreateUserRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     info!("Processing create user request for username: {}", req.username);
     if let Err(e) = req.validate() {
       error!("Validation failed for user creation: {}", e);
@@ -50,7 +62,8 @@ impl UserRoutes {
       }));
     }
     let handler = UserHandler::new(&pool);
-    match handler.create(&req.username, &req.email, &req.password) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(&req.username, &req.email, &req.password, &context) {
       Ok(user) => {
         info!("User created successfully via route: {}", user.username);
         HttpResponse::Ok().json(UserResponse::from(user))
@@ -78,7 +91,23 @@ impl UserRoutes {
     }
   }
 
-  async fn update_user(path: web::Path<Uuid>, req: web::Json<UpdateUserRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn get_effective_permissions(path: web::Path<Uuid>, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing effective permissions request for user ID: {}", id);
+    let handler = AuthorizationHandler::new(&pool, config.auth.clone());
+    match handler.effective_permissions(id) {
+      Ok(effective) => {
+        info!("Effective permissions retrieved successfully for user: {}", id);
+        HttpResponse::Ok().json(effective)
+      }
+      Err(e) => {
+        error!("Failed to resolve effective permissions for user {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn update_user(path: web::Path<Uuid>, req: web::Json<UpdateUserRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let id = *path;
     info!("Processing update user request for ID: {}", id);
     if let Err(e) = req.validate() {
@@ -88,11 +117,13 @@ impl UserRoutes {
       }));
     }
     let handler = UserHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
     match handler.update(
       id,
       req.username.as_deref(),
       req.email.as_deref(),
       req.password.as_deref(),
+      &context,
     ) {
       Ok(user) => {
         info!("User updated successfully: {}", id);
@@ -105,11 +136,29 @@ impl UserRoutes {
     }
   }
 
-  async fn delete_user(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn update_user_attributes(path: web::Path<Uuid>, req: web::Json<UpdateUserAttributesRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing update user attributes request for ID: {}", id);
+    let handler = UserHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.update_attributes(id, &req.attributes, &context) {
+      Ok(user) => {
+        info!("User attributes updated successfully: {}", id);
+        HttpResponse::Ok().json(UserResponse::from(user))
+      }
+      Err(e) => {
+        error!("Failed to update attributes of user {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn delete_user(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let id = *path;
     info!("Processing delete user request for ID: {}", id);
     let handler = UserHandler::new(&pool);
-    match handler.delete(id) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
       Ok(()) => {
         info!("User deleted successfully: {}", id);
         HttpResponse::Ok().finish()
@@ -1,11 +1,13 @@
-use actix_web::{web, HttpResponse, Responder, ResponseError};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use validator::{Validate, ValidationError};
 use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
 use crate::handlers::user_role::UserRoleHandler;
 use crate::models::user_role::UserRoleResponse;
 use log::{error, info};
 use uuid::Uuid;
+use chrono::{DateTime, Utc};
 
 // Custom validator for UUID fields (reused from role_permission.rs)
 fn validate_uuid(uuid: &Uuid) -> Result<(), ValidationError> {
@@ -22,6 +24,9 @@ pub struct CreateUserRoleRequest {
   pub user_id: Uuid,
   #[validate(custom(function = "validate_uuid"))]
   pub role_id: Uuid,
+  // Optional validity window; the assignment is ignored outside it and archived once expired.
+  pub valid_from: Option<DateTime<Utc>>,
+  pub valid_until: Option<DateTime<Utc>>,
 }
 
 pub struct UserRoleRoutes;
@@ -38,7 +43,7 @@ impl UserRoleRoutes {
     );
   }
 
-  async fn create_user_role(req: web::Json<CreateUserRoleRequest>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn create_user_role(req: web::Json<CreateUserRoleRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     info!("Processing create user_role request: user_id={}, role_id={}", req.user_id, req.role_id);
     if let Err(e) = req.0.validate() {
       error!("Validation failed for user_role creation: {}", e);
@@ -47,7 +52,8 @@ impl UserRoleRoutes {
       }));
     }
     let handler = UserRoleHandler::new(&pool);
-    match handler.create(req.user_id, req.role_id) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(req.user_id, req.role_id, req.valid_from, req.valid_until, &context) {
       Ok(user_role) => {
         info!("UserRole created successfully via route: user_id={}, role_id={}", req.user_id, req.role_id);
         HttpResponse::Ok().json(UserRoleResponse::from(user_role))
@@ -92,11 +98,12 @@ impl UserRoleRoutes {
     }
   }
 
-  async fn delete_user_role(path: web::Path<(Uuid, Uuid)>, pool: web::Data<PgPool>) -> impl Responder {
+  async fn delete_user_role(path: web::Path<(Uuid, Uuid)>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
     let (user_id, role_id) = path.into_inner();
     info!("Processing delete user_role request: user_id={}, role_id={}", user_id, role_id);
     let handler = UserRoleHandler::new(&pool);
-    match handler.delete(user_id, role_id) {
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(user_id, role_id, &context) {
       Ok(()) => {
         info!("UserRole deleted successfully: user_id={}, role_id={}", user_id, role_id);
         HttpResponse::Ok().finish()
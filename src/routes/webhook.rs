@@ -0,0 +1,186 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use validator::Validate;
+use crate::database::PgPool;
+use crate::middlewares::jwt::audit_context;
+use crate::handlers::webhook::WebhookHandler;
+use crate::models::webhook::WebhookResponse;
+use log::{error, info};
+use uuid::Uuid;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateWebhookRequest {
+  #[validate(url)]
+  pub url: String,
+  #[validate(length(min = 16, max = 255))]
+  pub secret: Option<String>,
+  #[serde(default)]
+  pub event_types: Vec<String>,
+  #[serde(default = "default_active")]
+  pub active: bool,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateWebhookRequest {
+  #[validate(url)]
+  pub url: Option<String>,
+  #[validate(length(min = 16, max = 255))]
+  pub secret: Option<String>,
+  pub event_types: Option<Vec<String>>,
+  pub active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct DeadLettersQuery {
+  pub webhook_id: Option<Uuid>,
+}
+
+fn default_active() -> bool {
+  true
+}
+
+pub struct WebhookRoutes;
+
+impl WebhookRoutes {
+  pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+      web::scope("/webhooks")
+        .wrap(crate::middlewares::jwt::JwtMiddleware)
+        .route("", web::post().to(Self::create_webhook))
+        .route("", web::get().to(Self::list_webhooks))
+        .route("/dead_letters", web::get().to(Self::list_dead_letters))
+        .route("/deliveries/{id}/retry", web::post().to(Self::retry_delivery))
+        .route("/{id}", web::get().to(Self::get_webhook))
+        .route("/{id}", web::put().to(Self::update_webhook))
+        .route("/{id}", web::delete().to(Self::delete_webhook)),
+    );
+  }
+
+  async fn create_webhook(req: web::Json<CreateWebhookRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    info!("Processing create webhook request for url: {}", req.url);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for webhook creation: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = WebhookHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.create(&req.url, req.secret.as_deref(), &req.event_types, req.active, &context) {
+      Ok(webhook) => {
+        info!("Webhook created successfully via route: {}", webhook.id);
+        let secret = webhook.secret.clone();
+        HttpResponse::Ok().json(WebhookResponse {
+          secret: Some(secret),
+          ..WebhookResponse::from(webhook)
+        })
+      }
+      Err(e) => {
+        error!("Failed to create webhook {}: {}", req.url, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_webhooks(pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing list webhooks request");
+    let handler = WebhookHandler::new(&pool);
+    match handler.find_all() {
+      Ok(webhooks) => {
+        info!("Webhooks retrieved successfully");
+        HttpResponse::Ok().json(webhooks.into_iter().map(WebhookResponse::from).collect::<Vec<_>>())
+      }
+      Err(e) => {
+        error!("Failed to list webhooks: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn list_dead_letters(query: web::Query<DeadLettersQuery>, pool: web::Data<PgPool>) -> impl Responder {
+    info!("Processing list dead webhook deliveries request");
+    let handler = WebhookHandler::new(&pool);
+    match handler.dead_letters(query.webhook_id) {
+      Ok(deliveries) => {
+        info!("Retrieved {} dead webhook deliveries", deliveries.len());
+        HttpResponse::Ok().json(deliveries)
+      }
+      Err(e) => {
+        error!("Failed to list dead webhook deliveries: {}", e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn retry_delivery(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = *path;
+    info!("Processing retry webhook delivery request for ID: {}", id);
+    let handler = WebhookHandler::new(&pool);
+    match handler.retry(id) {
+      Ok(delivery) => {
+        info!("Webhook delivery queued for retry: {}", id);
+        HttpResponse::Ok().json(delivery)
+      }
+      Err(e) => {
+        error!("Failed to retry webhook delivery {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn get_webhook(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let id = *path;
+    info!("Processing get webhook request for ID: {}", id);
+    let handler = WebhookHandler::new(&pool);
+    match handler.find_by_id(id) {
+      Ok(webhook) => {
+        info!("Webhook retrieved successfully: {}", id);
+        HttpResponse::Ok().json(WebhookResponse::from(webhook))
+      }
+      Err(e) => {
+        error!("Failed to retrieve webhook {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn update_webhook(path: web::Path<Uuid>, req: web::Json<UpdateWebhookRequest>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing update webhook request for ID: {}", id);
+    if let Err(e) = req.validate() {
+      error!("Validation failed for webhook update: {}", e);
+      return HttpResponse::BadRequest().json(serde_json::json!({
+        "error": format!("Validation error: {}", e)
+      }));
+    }
+    let handler = WebhookHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.update(id, req.url.as_deref(), req.secret.as_deref(), req.event_types.as_deref(), req.active, &context) {
+      Ok(webhook) => {
+        info!("Webhook updated successfully: {}", id);
+        HttpResponse::Ok().json(WebhookResponse::from(webhook))
+      }
+      Err(e) => {
+        error!("Failed to update webhook {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+
+  async fn delete_webhook(path: web::Path<Uuid>, http_req: HttpRequest, pool: web::Data<PgPool>, config: web::Data<crate::config::Config>) -> impl Responder {
+    let id = *path;
+    info!("Processing delete webhook request for ID: {}", id);
+    let handler = WebhookHandler::new(&pool);
+    let context = audit_context(&http_req, &config.auth);
+    match handler.delete(id, &context) {
+      Ok(()) => {
+        info!("Webhook deleted successfully: {}", id);
+        HttpResponse::Ok().finish()
+      }
+      Err(e) => {
+        error!("Failed to delete webhook {}: {}", id, e);
+        e.error_response()
+      }
+    }
+  }
+}
\ No newline at end of file
@@ -1,5 +1,131 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_checkpoints (id) {
+        id -> Uuid,
+        seq -> Int8,
+        #[max_length = 64]
+        hash -> Varchar,
+        #[max_length = 64]
+        signature -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    audit_events (id) {
+        id -> Uuid,
+        actor_id -> Nullable<Uuid>,
+        #[max_length = 255]
+        actor -> Nullable<Varchar>,
+        #[max_length = 50]
+        action -> Varchar,
+        #[max_length = 50]
+        target_type -> Varchar,
+        #[max_length = 255]
+        target_id -> Nullable<Varchar>,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        #[max_length = 45]
+        ip -> Nullable<Varchar>,
+        #[max_length = 255]
+        request_id -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        seq -> Int8,
+        #[max_length = 64]
+        prev_hash -> Nullable<Varchar>,
+        #[max_length = 64]
+        hash -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    elevation_request_events (id) {
+        id -> Uuid,
+        elevation_request_id -> Uuid,
+        #[max_length = 20]
+        action -> Varchar,
+        actor_id -> Nullable<Uuid>,
+        comment -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    elevation_requests (id) {
+        id -> Uuid,
+        requester_id -> Uuid,
+        role_id -> Uuid,
+        justification -> Text,
+        duration_seconds -> Int4,
+        #[max_length = 20]
+        status -> Varchar,
+        approver_id -> Nullable<Uuid>,
+        decision_comment -> Nullable<Text>,
+        decided_at -> Nullable<Timestamptz>,
+        expires_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    expired_user_roles (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        role_id -> Uuid,
+        created_at -> Timestamptz,
+        valid_from -> Nullable<Timestamptz>,
+        valid_until -> Timestamptz,
+        expired_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    group_roles (group_id, role_id) {
+        group_id -> Uuid,
+        role_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    group_subgroups (parent_group_id, child_group_id) {
+        parent_group_id -> Uuid,
+        child_group_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    group_users (group_id, user_id) {
+        group_id -> Uuid,
+        user_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    groups (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    outbox_events (id) {
+        id -> Uuid,
+        #[max_length = 50]
+        event_type -> Varchar,
+        payload -> Jsonb,
+        created_at -> Timestamptz,
+        dispatched_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     permissions (id) {
         id -> Uuid,
@@ -8,6 +134,51 @@ diesel::table! {
         description -> Nullable<Text>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        system -> Bool,
+    }
+}
+
+diesel::table! {
+    policies (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        body -> Text,
+        enabled -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    relation_tuples (id) {
+        id -> Uuid,
+        #[max_length = 50]
+        namespace -> Varchar,
+        #[max_length = 255]
+        object_id -> Varchar,
+        #[max_length = 50]
+        relation -> Varchar,
+        #[max_length = 50]
+        subject_namespace -> Varchar,
+        #[max_length = 255]
+        subject_id -> Varchar,
+        #[max_length = 50]
+        subject_relation -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    resource_grants (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        role_id -> Uuid,
+        #[max_length = 50]
+        resource_type -> Varchar,
+        #[max_length = 255]
+        resource_id -> Varchar,
+        created_at -> Timestamptz,
     }
 }
 
@@ -16,6 +187,9 @@ diesel::table! {
         role_id -> Uuid,
         permission_id -> Uuid,
         created_at -> Timestamptz,
+        #[max_length = 5]
+        effect -> Varchar,
+        conditions -> Nullable<Jsonb>,
     }
 }
 
@@ -27,6 +201,34 @@ diesel::table! {
         description -> Nullable<Text>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        system -> Bool,
+    }
+}
+
+diesel::table! {
+    sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        expires_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    sod_constraint_roles (constraint_id, role_id) {
+        constraint_id -> Uuid,
+        role_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    sod_constraints (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
     }
 }
 
@@ -35,6 +237,8 @@ diesel::table! {
         user_id -> Uuid,
         role_id -> Uuid,
         created_at -> Timestamptz,
+        valid_from -> Nullable<Timestamptz>,
+        valid_until -> Nullable<Timestamptz>,
     }
 }
 
@@ -49,18 +253,79 @@ diesel::table! {
         password_hash -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        attributes -> Jsonb,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Uuid,
+        webhook_id -> Uuid,
+        outbox_event_id -> Uuid,
+        #[max_length = 20]
+        status -> Varchar,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_status_code -> Nullable<Int4>,
+        last_error -> Nullable<Text>,
+        delivered_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Uuid,
+        url -> Text,
+        #[max_length = 255]
+        secret -> Varchar,
+        event_types -> Array<Text>,
+        active -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
+diesel::joinable!(elevation_request_events -> elevation_requests (elevation_request_id));
+diesel::joinable!(elevation_requests -> roles (role_id));
+diesel::joinable!(group_roles -> groups (group_id));
+diesel::joinable!(group_roles -> roles (role_id));
+diesel::joinable!(group_users -> groups (group_id));
+diesel::joinable!(group_users -> users (user_id));
+diesel::joinable!(resource_grants -> roles (role_id));
+diesel::joinable!(resource_grants -> users (user_id));
 diesel::joinable!(role_permissions -> permissions (permission_id));
 diesel::joinable!(role_permissions -> roles (role_id));
+diesel::joinable!(sessions -> users (user_id));
+diesel::joinable!(sod_constraint_roles -> roles (role_id));
+diesel::joinable!(sod_constraint_roles -> sod_constraints (constraint_id));
 diesel::joinable!(user_roles -> roles (role_id));
 diesel::joinable!(user_roles -> users (user_id));
+diesel::joinable!(webhook_deliveries -> outbox_events (outbox_event_id));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit_checkpoints,
+    audit_events,
+    elevation_request_events,
+    elevation_requests,
+    expired_user_roles,
+    group_roles,
+    group_subgroups,
+    group_users,
+    groups,
+    outbox_events,
     permissions,
+    policies,
+    relation_tuples,
+    resource_grants,
     role_permissions,
     roles,
+    sessions,
+    sod_constraint_roles,
+    sod_constraints,
     user_roles,
     users,
+    webhook_deliveries,
+    webhooks,
 );
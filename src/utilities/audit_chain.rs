@@ -0,0 +1,117 @@
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+// The predecessor of the first chained audit event.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+type HmacSha256 = Hmac<Sha256>;
+
+// SHA-256 over the previous hash and the canonical JSON of the event, hex encoded.
+pub fn hash(prev_hash: &str, payload: &Value) -> String {
+  let mut canonical = String::new();
+  write_canonical(payload, &mut canonical);
+  let mut hasher = Sha256::new();
+  hasher.update(prev_hash.as_bytes());
+  hasher.update(b"\n");
+  hasher.update(canonical.as_bytes());
+  hex::encode(hasher.finalize())
+}
+
+pub fn sign_checkpoint(secret: &str, seq: i64, hash: &str) -> String {
+  hex::encode(checkpoint_mac(secret, seq, hash).finalize().into_bytes())
+}
+
+pub fn verify_checkpoint(secret: &str, seq: i64, hash: &str, signature: &str) -> bool {
+  match hex::decode(signature) {
+    Ok(signature) => checkpoint_mac(secret, seq, hash).verify_slice(&signature).is_ok(),
+    Err(_) => false,
+  }
+}
+
+fn checkpoint_mac(secret: &str, seq: i64, hash: &str) -> HmacSha256 {
+  let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(format!("{}:{}", seq, hash).as_bytes());
+  mac
+}
+
+// Object keys are sorted at every level, so the hash does not depend on the key order JSONB
+// hands back.
+fn write_canonical(value: &Value, out: &mut String) {
+  match value {
+    Value::Object(map) => {
+      let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+      entries.sort_by(|a, b| a.0.cmp(b.0));
+      out.push('{');
+      for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+          out.push(',');
+        }
+        out.push_str(&Value::String(key.clone()).to_string());
+        out.push(':');
+        write_canonical(value, out);
+      }
+      out.push('}');
+    }
+    Value::Array(items) => {
+      out.push('[');
+      for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+          out.push(',');
+        }
+        write_canonical(item, out);
+      }
+      out.push(']');
+    }
+    scalar => out.push_str(&scalar.to_string()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  const SECRET: &str = "checkpoint-secret";
+
+  #[test]
+  fn canonical_json_sorts_keys_at_every_level() {
+    let mut out = String::new();
+    write_canonical(&json!({ "b": [{ "z": 1, "a": null }], "a": "x\"y" }), &mut out);
+    assert_eq!(out, r#"{"a":"x\"y","b":[{"a":null,"z":1}]}"#);
+  }
+
+  #[test]
+  fn key_order_does_not_change_the_hash() {
+    let first: Value = serde_json::from_str(r#"{"action":"role.update","target":{"id":"r1","name":"ops"}}"#).unwrap();
+    let second: Value = serde_json::from_str(r#"{"target":{"name":"ops","id":"r1"},"action":"role.update"}"#).unwrap();
+    assert_eq!(hash(GENESIS_HASH, &first), hash(GENESIS_HASH, &second));
+  }
+
+  #[test]
+  fn payload_and_previous_hash_change_the_hash() {
+    let payload = json!({ "action": "role.update", "after": { "name": "ops" } });
+    let original = hash(GENESIS_HASH, &payload);
+    assert_eq!(original.len(), 64);
+    assert_ne!(hash(GENESIS_HASH, &json!({ "action": "role.update", "after": { "name": "admin" } })), original);
+    assert_ne!(hash(&original, &payload), original);
+  }
+
+  #[test]
+  fn checkpoint_signatures_round_trip() {
+    let head = hash(GENESIS_HASH, &json!({ "seq": 42 }));
+    let signature = sign_checkpoint(SECRET, 42, &head);
+    assert!(verify_checkpoint(SECRET, 42, &head, &signature));
+  }
+
+  #[test]
+  fn tampered_checkpoints_fail_verification() {
+    let head = hash(GENESIS_HASH, &json!({ "seq": 42 }));
+    let signature = sign_checkpoint(SECRET, 42, &head);
+    assert!(!verify_checkpoint(SECRET, 43, &head, &signature));
+    assert!(!verify_checkpoint(SECRET, 42, GENESIS_HASH, &signature));
+    assert!(!verify_checkpoint("other-secret", 42, &head, &signature));
+    assert!(!verify_checkpoint(SECRET, 42, &head, "not hex"));
+    assert!(!verify_checkpoint(SECRET, 42, &head, &signature[..62]));
+  }
+}
\ No newline at end of file
@@ -0,0 +1,23 @@
+use crate::config::Config;
+use crate::database::PgPool;
+use crate::handlers::audit::AuditHandler;
+use std::thread;
+use std::time::Duration;
+use log::{error, info, warn};
+
+// Periodically signs the head of the audit chain. Safe to run on every instance: a seq is only
+// checkpointed once.
+pub fn spawn_checkpointer(pool: PgPool, config: &Config) {
+  let Some(secret) = config.audit.checkpoint_secret.clone() else {
+    warn!("AUDIT__CHECKPOINT_SECRET is not set; the audit chain is not checkpointed");
+    return;
+  };
+  let interval = Duration::from_secs(config.audit.checkpoint_interval_seconds);
+  info!("Checkpointing the audit chain every {:?}", interval);
+  thread::spawn(move || loop {
+    thread::sleep(interval);
+    if let Err(e) = AuditHandler::new(&pool).checkpoint(&secret) {
+      error!("Failed to checkpoint the audit chain: {}", e);
+    }
+  });
+}
\ No newline at end of file
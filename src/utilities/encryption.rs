@@ -1,11 +1,13 @@
 use argon2::{
   password_hash::{
-    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString
+    rand_core::{OsRng, RngCore}, PasswordHash, PasswordHasher, PasswordVerifier, SaltString
   },
   Argon2,
 };
 use crate::utilities::error::AppError;
 
+const SECRET_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789";
+
 pub struct Encryption;
 
 impl Encryption {
@@ -24,4 +26,22 @@ impl Encryption {
       .verify_password(password_string.as_bytes(), &parsed_hash)
       .is_ok())
   }
+
+  // Random alphanumeric secret without look-alike characters, for one-time passwords.
+  pub fn generate_secret(length: usize) -> String {
+    // Rejecting bytes past the largest multiple of the alphabet size keeps the choice uniform.
+    let limit = 256 - 256 % SECRET_ALPHABET.len();
+    let mut secret = String::with_capacity(length);
+    let mut buffer = [0u8; 64];
+    while secret.len() < length {
+      OsRng.fill_bytes(&mut buffer);
+      for &byte in buffer.iter().filter(|&&b| (b as usize) < limit) {
+        if secret.len() == length {
+          break;
+        }
+        secret.push(SECRET_ALPHABET[byte as usize % SECRET_ALPHABET.len()] as char);
+      }
+    }
+    secret
+  }
 }
\ No newline at end of file
@@ -20,6 +20,8 @@ pub enum AppError {
   InvalidCredentials,
   #[display("Forbidden")]
   Forbidden,
+  #[display("Forbidden: {}", _0)]
+  AccessDenied(String),
   #[display("Not Found: {}", _0)]
   NotFound(String),
   #[display("Conflict: {}", _0)]
@@ -41,6 +43,7 @@ impl Error for AppError {
       AppError::Unauthorized(_) => None,
       AppError::InvalidCredentials => None,
       AppError::Forbidden => None,
+      AppError::AccessDenied(_) => None,
       AppError::NotFound(_) => None,
       AppError::Conflict(_) => None,
       AppError::DatabaseError(_) => None,
@@ -58,6 +61,7 @@ impl ResponseError for AppError {
       AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
       AppError::InvalidCredentials => StatusCode::UNAUTHORIZED,
       AppError::Forbidden => StatusCode::FORBIDDEN,
+      AppError::AccessDenied(_) => StatusCode::FORBIDDEN,
       AppError::NotFound(_) => StatusCode::NOT_FOUND,
       AppError::Conflict(_) => StatusCode::CONFLICT,
       AppError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -111,6 +115,10 @@ impl From<DieselError> for AppError {
                 error!("Unique violation on role_permissions_role_id_permission_id_key: {}", info.message());
                 AppError::Conflict("Duplicate entry for constraint: role_permissions_role_id_permission_id_key".to_string())
               }
+              Some("idx_elevation_requests_pending") => {
+                error!("Unique violation on idx_elevation_requests_pending: {}", info.message());
+                AppError::Conflict("An elevation request for this role is already pending".to_string())
+              }
               Some(constraint) => {
                 error!("Unique violation on constraint {}: {}", constraint, info.message());
                 AppError::Conflict(format!("Duplicate entry for constraint: {}", constraint))
@@ -0,0 +1,185 @@
+use actix_web::rt::time::sleep;
+use actix_web::web::Bytes;
+use chrono::{DateTime, Utc};
+use futures::channel::mpsc::{self, Sender};
+use futures::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::utilities::{events, permission_cache};
+use log::{debug, warn};
+
+// Comments sent to idle streams keep proxies from closing them and reveal disconnected
+// subscribers.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+const RETRY_MILLISECONDS: u64 = 5000;
+// Events a subscriber may fall behind by before it is disconnected.
+const BUFFER_SIZE: usize = 64;
+
+static SUBSCRIBERS: LazyLock<Mutex<Vec<Subscriber>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+// Only changes concerning `user_id` and `role_id`, when set, are streamed. Changes that may
+// affect anyone, such as a role's grants being edited, are streamed to every subscriber.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamFilter {
+  pub user_id: Option<Uuid>,
+  pub role_id: Option<Uuid>,
+}
+
+// The token a stream was opened with. The stream ends when the token expires, its session is
+// revoked or its user is deleted.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamSession {
+  pub user_id: Uuid,
+  pub session_id: Option<Uuid>,
+  pub expires_at: DateTime<Utc>,
+}
+
+struct Subscriber {
+  filter: StreamFilter,
+  session: StreamSession,
+  sender: Sender<Bytes>,
+}
+
+impl Subscriber {
+  // Returns false once the subscriber is gone or has fallen too far behind.
+  fn send(&mut self, bytes: Bytes) -> bool {
+    match self.sender.try_send(bytes) {
+      Ok(()) => true,
+      Err(e) if e.is_full() => {
+        warn!("Disconnecting permission event subscriber of user_id={} that fell behind", self.session.user_id);
+        false
+      }
+      Err(_) => false,
+    }
+  }
+}
+
+struct StreamEvent {
+  name: String,
+  data: Value,
+  user_id: Option<Uuid>,
+  role_id: Option<Uuid>,
+  broadcast: bool,
+}
+
+impl StreamEvent {
+  fn matches(&self, filter: &StreamFilter) -> bool {
+    self.broadcast
+      || (filter.user_id.is_none_or(|id| self.user_id == Some(id)) && filter.role_id.is_none_or(|id| self.role_id == Some(id)))
+  }
+
+  fn to_bytes(&self) -> Bytes {
+    Bytes::from(format!("event: {}\ndata: {}\n\n", self.name, self.data))
+  }
+}
+
+// Registers a stream receiving matching changes as server-sent events. It starts with a
+// `permissions_changed` event of scope `all`, since a reconnecting client may have missed
+// changes, and ends when the receiver is dropped or the subscriber is disconnected.
+pub fn subscribe(filter: StreamFilter, session: StreamSession) -> impl Stream<Item = Bytes> {
+  let (mut sender, receiver) = mpsc::channel(BUFFER_SIZE);
+  let _ = sender.try_send(Bytes::from(format!("retry: {}\n\n", RETRY_MILLISECONDS)));
+  let _ = sender.try_send(invalidate_all().to_bytes());
+  let mut subscribers = SUBSCRIBERS.lock().unwrap_or_else(|e| e.into_inner());
+  subscribers.push(Subscriber { filter, session, sender });
+  debug!("Added permission event subscriber ({} total)", subscribers.len());
+  let lifetime = (session.expires_at - Utc::now()).to_std().unwrap_or_default();
+  receiver.take_until(sleep(lifetime))
+}
+
+fn publish(event: &StreamEvent) {
+  let mut subscribers = SUBSCRIBERS.lock().unwrap_or_else(|e| e.into_inner());
+  let bytes = event.to_bytes();
+  subscribers.retain_mut(|subscriber| !event.matches(&subscriber.filter) || subscriber.send(bytes.clone()));
+}
+
+// Drops the subscribers of revoked sessions and deleted users, which ends their streams.
+fn disconnect(event: &StreamEvent) {
+  let session_id = match event.name.as_str() {
+    "session_revoked" => event.data.get("session_id").and_then(Value::as_str).and_then(|id| Uuid::parse_str(id).ok()),
+    "user_deleted" => None,
+    _ => return,
+  };
+  let mut subscribers = SUBSCRIBERS.lock().unwrap_or_else(|e| e.into_inner());
+  subscribers.retain(|subscriber| match session_id {
+    Some(session_id) => subscriber.session.session_id != Some(session_id),
+    None => Some(subscriber.session.user_id) != event.user_id,
+  });
+}
+
+fn heartbeat() {
+  let mut subscribers = SUBSCRIBERS.lock().unwrap_or_else(|e| e.into_inner());
+  subscribers.retain_mut(|subscriber| subscriber.send(Bytes::from_static(b": keepalive\n\n")));
+}
+
+// Notifications sent while disconnected are lost, so subscribers drop everything they cached.
+fn invalidate_all() -> StreamEvent {
+  StreamEvent {
+    name: "permissions_changed".into(),
+    data: json!({ "type": "permissions_changed", "scope": "all" }),
+    user_id: None,
+    role_id: None,
+    broadcast: true,
+  }
+}
+
+fn parse(channel: &str, payload: &str) -> Option<StreamEvent> {
+  if channel == events::CHANNEL {
+    let data: Value = serde_json::from_str(payload).ok()?;
+    let id = |key: &str| data.get(key).and_then(Value::as_str).and_then(|id| Uuid::parse_str(id).ok());
+    return Some(StreamEvent {
+      name: data.get("type").and_then(Value::as_str).unwrap_or("event").to_string(),
+      user_id: id("user_id"),
+      role_id: id("role_id"),
+      broadcast: false,
+      data,
+    });
+  }
+  match payload {
+    permission_cache::INVALIDATE_ALL => Some(invalidate_all()),
+    permission_cache::INVALIDATE_POLICIES => Some(StreamEvent {
+      name: "policies_changed".into(),
+      data: json!({ "type": "policies_changed" }),
+      user_id: None,
+      role_id: None,
+      broadcast: true,
+    }),
+    _ => {
+      let user_id = Uuid::parse_str(payload).ok()?;
+      Some(StreamEvent {
+        name: "permissions_changed".into(),
+        data: json!({ "type": "permissions_changed", "scope": "user", "user_id": user_id }),
+        user_id: Some(user_id),
+        role_id: None,
+        broadcast: false,
+      })
+    }
+  }
+}
+
+// Relays a notification received by the permission cache listener to the subscribers of this
+// instance.
+pub fn relay(channel: &str, payload: &str) {
+  match parse(channel, payload) {
+    Some(event) => {
+      publish(&event);
+      disconnect(&event);
+    }
+    None => warn!("Ignoring malformed {} payload {:?}", channel, payload),
+  }
+}
+
+// Tells every subscriber to drop its cached permissions after the listener reconnected.
+pub fn resync() {
+  publish(&invalidate_all());
+}
+
+pub fn spawn_heartbeat() {
+  thread::spawn(|| loop {
+    thread::sleep(HEARTBEAT_INTERVAL);
+    heartbeat();
+  });
+}
\ No newline at end of file
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::models::resource_grant::ResourceGrant;
+use crate::schema::{group_roles, group_subgroups, group_users, outbox_events, resource_grants, role_permissions, user_roles};
+
+// Authorization events for other services, published as JSON on this channel.
+pub const CHANNEL: &str = "authorization_events";
+
+pub const EVENT_TYPES: [&str; 17] = [
+  "user_created",
+  "user_deleted",
+  "role_assigned",
+  "role_revoked",
+  "permission_granted",
+  "permission_revoked",
+  "user_role_expired",
+  "user_role_activated",
+  "group_user_added",
+  "group_user_removed",
+  "group_subgroup_added",
+  "group_subgroup_removed",
+  "group_role_assigned",
+  "group_role_revoked",
+  "resource_role_assigned",
+  "resource_role_revoked",
+  "session_revoked",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+  UserCreated { user_id: Uuid, username: String },
+  UserDeleted { user_id: Uuid },
+  RoleAssigned { user_id: Uuid, role_id: Uuid, valid_from: Option<DateTime<Utc>>, valid_until: Option<DateTime<Utc>> },
+  RoleRevoked { user_id: Uuid, role_id: Uuid },
+  PermissionGranted { role_id: Uuid, permission_id: Uuid, effect: String },
+  PermissionRevoked { role_id: Uuid, permission_id: Uuid },
+  UserRoleExpired { user_id: Uuid, role_id: Uuid },
+  UserRoleActivated { user_id: Uuid, role_id: Uuid },
+  GroupUserAdded { group_id: Uuid, user_id: Uuid },
+  GroupUserRemoved { group_id: Uuid, user_id: Uuid },
+  GroupSubgroupAdded { parent_group_id: Uuid, child_group_id: Uuid },
+  GroupSubgroupRemoved { parent_group_id: Uuid, child_group_id: Uuid },
+  GroupRoleAssigned { group_id: Uuid, role_id: Uuid },
+  GroupRoleRevoked { group_id: Uuid, role_id: Uuid },
+  ResourceRoleAssigned { grant_id: Uuid, user_id: Uuid, role_id: Uuid, resource_type: String, resource_id: String },
+  ResourceRoleRevoked { grant_id: Uuid, user_id: Uuid, role_id: Uuid, resource_type: String, resource_id: String },
+  SessionRevoked { user_id: Uuid, session_id: Uuid },
+}
+
+impl Event {
+  // Matches the serialized `type` tag.
+  pub fn event_type(&self) -> &'static str {
+    match self {
+      Event::UserCreated { .. } => EVENT_TYPES[0],
+      Event::UserDeleted { .. } => EVENT_TYPES[1],
+      Event::RoleAssigned { .. } => EVENT_TYPES[2],
+      Event::RoleRevoked { .. } => EVENT_TYPES[3],
+      Event::PermissionGranted { .. } => EVENT_TYPES[4],
+      Event::PermissionRevoked { .. } => EVENT_TYPES[5],
+      Event::UserRoleExpired { .. } => EVENT_TYPES[6],
+      Event::UserRoleActivated { .. } => EVENT_TYPES[7],
+      Event::GroupUserAdded { .. } => EVENT_TYPES[8],
+      Event::GroupUserRemoved { .. } => EVENT_TYPES[9],
+      Event::GroupSubgroupAdded { .. } => EVENT_TYPES[10],
+      Event::GroupSubgroupRemoved { .. } => EVENT_TYPES[11],
+      Event::GroupRoleAssigned { .. } => EVENT_TYPES[12],
+      Event::GroupRoleRevoked { .. } => EVENT_TYPES[13],
+      Event::ResourceRoleAssigned { .. } => EVENT_TYPES[14],
+      Event::ResourceRoleRevoked { .. } => EVENT_TYPES[15],
+      Event::SessionRevoked { .. } => EVENT_TYPES[16],
+    }
+  }
+
+  pub fn resource_role_assigned(grant: &ResourceGrant) -> Self {
+    Event::ResourceRoleAssigned {
+      grant_id: grant.id,
+      user_id: grant.user_id,
+      role_id: grant.role_id,
+      resource_type: grant.resource_type.clone(),
+      resource_id: grant.resource_id.clone(),
+    }
+  }
+
+  pub fn resource_role_revoked(grant: &ResourceGrant) -> Self {
+    Event::ResourceRoleRevoked {
+      grant_id: grant.id,
+      user_id: grant.user_id,
+      role_id: grant.role_id,
+      resource_type: grant.resource_type.clone(),
+      resource_id: grant.resource_id.clone(),
+    }
+  }
+}
+
+// Notifies listeners and writes the event to the outbox for webhook delivery. Must run inside
+// the mutating transaction so the event is only delivered once committed.
+pub fn publish(conn: &mut PgConnection, event: &Event) -> QueryResult<()> {
+  let payload = serde_json::to_value(event).map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))?;
+  diesel::insert_into(outbox_events::table)
+    .values((outbox_events::event_type.eq(event.event_type()), outbox_events::payload.eq(&payload)))
+    .execute(conn)?;
+  diesel::sql_query("SELECT pg_notify($1, $2)")
+    .bind::<Text, _>(CHANNEL)
+    .bind::<Text, _>(payload.to_string())
+    .execute(conn)
+    .map(|_| ())
+}
+
+// Revocations implied by deleting a role, published before its rows cascade away.
+pub fn publish_role_removal(conn: &mut PgConnection, role_id: Uuid) -> QueryResult<()> {
+  let user_ids = user_roles::table.filter(user_roles::role_id.eq(role_id)).select(user_roles::user_id).load::<Uuid>(conn)?;
+  for user_id in user_ids {
+    publish(conn, &Event::RoleRevoked { user_id, role_id })?;
+  }
+  let permission_ids =
+    role_permissions::table.filter(role_permissions::role_id.eq(role_id)).select(role_permissions::permission_id).load::<Uuid>(conn)?;
+  for permission_id in permission_ids {
+    publish(conn, &Event::PermissionRevoked { role_id, permission_id })?;
+  }
+  let group_ids = group_roles::table.filter(group_roles::role_id.eq(role_id)).select(group_roles::group_id).load::<Uuid>(conn)?;
+  for group_id in group_ids {
+    publish(conn, &Event::GroupRoleRevoked { group_id, role_id })?;
+  }
+  let grants = resource_grants::table.filter(resource_grants::role_id.eq(role_id)).load::<ResourceGrant>(conn)?;
+  for grant in &grants {
+    publish(conn, &Event::resource_role_revoked(grant))?;
+  }
+  Ok(())
+}
+
+pub fn publish_permission_removal(conn: &mut PgConnection, permission_id: Uuid) -> QueryResult<()> {
+  let role_ids =
+    role_permissions::table.filter(role_permissions::permission_id.eq(permission_id)).select(role_permissions::role_id).load::<Uuid>(conn)?;
+  for role_id in role_ids {
+    publish(conn, &Event::PermissionRevoked { role_id, permission_id })?;
+  }
+  Ok(())
+}
+
+pub fn publish_group_removal(conn: &mut PgConnection, group_id: Uuid) -> QueryResult<()> {
+  let user_ids = group_users::table.filter(group_users::group_id.eq(group_id)).select(group_users::user_id).load::<Uuid>(conn)?;
+  for user_id in user_ids {
+    publish(conn, &Event::GroupUserRemoved { group_id, user_id })?;
+  }
+  let nestings = group_subgroups::table
+    .filter(group_subgroups::parent_group_id.eq(group_id).or(group_subgroups::child_group_id.eq(group_id)))
+    .select((group_subgroups::parent_group_id, group_subgroups::child_group_id))
+    .load::<(Uuid, Uuid)>(conn)?;
+  for (parent_group_id, child_group_id) in nestings {
+    publish(conn, &Event::GroupSubgroupRemoved { parent_group_id, child_group_id })?;
+  }
+  let role_ids = group_roles::table.filter(group_roles::group_id.eq(group_id)).select(group_roles::role_id).load::<Uuid>(conn)?;
+  for role_id in role_ids {
+    publish(conn, &Event::GroupRoleRevoked { group_id, role_id })?;
+  }
+  Ok(())
+}
\ No newline at end of file
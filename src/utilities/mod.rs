@@ -1,2 +1,12 @@
 pub mod error;
-pub mod encryption;
\ No newline at end of file
+pub mod encryption;
+pub mod permission_cache;
+pub mod permission_matcher;
+pub mod policy;
+pub mod policy_store;
+pub mod events;
+pub mod role_expiry;
+pub mod audit_chain;
+pub mod audit_checkpoint;
+pub mod webhooks;
+pub mod event_stream;
\ No newline at end of file
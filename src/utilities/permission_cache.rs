@@ -0,0 +1,156 @@
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::{Client, NoTls};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use crate::models::authorization::Grant;
+use crate::utilities::{event_stream, events, policy_store};
+use log::{debug, error, info, warn};
+
+pub const CHANNEL: &str = "permission_changes";
+pub const INVALIDATE_ALL: &str = "all";
+pub const INVALIDATE_POLICIES: &str = "policies";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// Effective permission grants per user, shared by every worker of this process.
+static CACHE: LazyLock<RwLock<HashMap<Uuid, CacheEntry>>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+// Bumped on every invalidation so a load that raced with a change is not cached.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+struct CacheEntry {
+  grants: Arc<Vec<Grant>>,
+  loaded_at: Instant,
+}
+
+pub fn get(user_id: Uuid, ttl: Duration) -> Option<Arc<Vec<Grant>>> {
+  let cache = CACHE.read().unwrap_or_else(|e| e.into_inner());
+  cache
+    .get(&user_id)
+    .filter(|entry| entry.loaded_at.elapsed() < ttl)
+    .map(|entry| Arc::clone(&entry.grants))
+}
+
+pub fn generation() -> u64 {
+  GENERATION.load(Ordering::SeqCst)
+}
+
+// `generation` must be read before loading the grants from the database.
+pub fn insert(user_id: Uuid, grants: Vec<Grant>, generation: u64) -> Arc<Vec<Grant>> {
+  let grants = Arc::new(grants);
+  let mut cache = CACHE.write().unwrap_or_else(|e| e.into_inner());
+  if GENERATION.load(Ordering::SeqCst) != generation {
+    debug!("Permissions for user_id={} changed while loading; not caching", user_id);
+    return grants;
+  }
+  cache.insert(user_id, CacheEntry {
+    grants: Arc::clone(&grants),
+    loaded_at: Instant::now(),
+  });
+  grants
+}
+
+pub fn invalidate_user(user_id: Uuid) {
+  debug!("Invalidating cached permissions for user_id={}", user_id);
+  let mut cache = CACHE.write().unwrap_or_else(|e| e.into_inner());
+  GENERATION.fetch_add(1, Ordering::SeqCst);
+  cache.remove(&user_id);
+}
+
+pub fn invalidate_all() {
+  debug!("Invalidating all cached permissions");
+  let mut cache = CACHE.write().unwrap_or_else(|e| e.into_inner());
+  GENERATION.fetch_add(1, Ordering::SeqCst);
+  cache.clear();
+}
+
+// Publishes a change to every instance. Must run inside the mutating transaction so the
+// notification is only delivered once the change is committed.
+pub fn notify_user(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<()> {
+  notify(conn, &user_id.to_string())
+}
+
+pub fn notify_all(conn: &mut PgConnection) -> QueryResult<()> {
+  notify(conn, INVALIDATE_ALL)
+}
+
+pub fn notify_policies(conn: &mut PgConnection) -> QueryResult<()> {
+  notify(conn, INVALIDATE_POLICIES)
+}
+
+fn notify(conn: &mut PgConnection, payload: &str) -> QueryResult<()> {
+  diesel::sql_query("SELECT pg_notify($1, $2)")
+    .bind::<Text, _>(CHANNEL)
+    .bind::<Text, _>(payload)
+    .execute(conn)
+    .map(|_| ())
+}
+
+fn handle_notification(payload: &str) {
+  if payload == INVALIDATE_ALL {
+    invalidate_all();
+    return;
+  }
+  if payload == INVALIDATE_POLICIES {
+    policy_store::invalidate();
+    return;
+  }
+  match Uuid::parse_str(payload) {
+    Ok(user_id) => invalidate_user(user_id),
+    Err(e) => {
+      warn!("Ignoring malformed {} payload {:?}: {}", CHANNEL, payload, e);
+      invalidate_all();
+    }
+  }
+}
+
+// Listens for changes and authorization events published by any instance (including this one)
+// on a dedicated connection, reconnecting forever, and relays both to the event streams of
+// this instance. Everything is dropped after a reconnect because notifications sent while
+// disconnected are lost.
+pub fn spawn_listener(database_url: String) {
+  thread::spawn(move || loop {
+    match Client::connect(&database_url, NoTls) {
+      Ok(mut client) => {
+        if let Err(e) = client.batch_execute(&format!("LISTEN {}; LISTEN {}", CHANNEL, events::CHANNEL)) {
+          error!("Failed to LISTEN on {} and {}: {}", CHANNEL, events::CHANNEL, e);
+        } else {
+          info!("Listening for permission changes on channels {} and {}", CHANNEL, events::CHANNEL);
+          invalidate_all();
+          policy_store::invalidate();
+          event_stream::resync();
+          let mut notifications = client.notifications();
+          let mut iter = notifications.blocking_iter();
+          loop {
+            match iter.next() {
+              Ok(Some(notification)) => {
+                debug!("Received {} notification: {}", notification.channel(), notification.payload());
+                if notification.channel() == CHANNEL {
+                  handle_notification(notification.payload());
+                }
+                event_stream::relay(notification.channel(), notification.payload());
+              }
+              Ok(None) => {
+                warn!("Permission change listener connection closed");
+                break;
+              }
+              Err(e) => {
+                error!("Permission change listener failed: {}", e);
+                break;
+              }
+            }
+          }
+        }
+      }
+      Err(e) => error!("Failed to connect permission change listener: {}", e),
+    }
+    invalidate_all();
+    policy_store::invalidate();
+    thread::sleep(RECONNECT_DELAY);
+  });
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+// Permission names are dot-separated namespaces such as `admin.create_user_role`. A grant
+// may end in a `*` segment (`billing.invoices.*`) to cover every name below that prefix,
+// and a lone `*` covers every permission.
+const WILDCARD: &str = "*";
+
+pub fn is_valid_name(name: &str) -> bool {
+  let segments: Vec<&str> = name.split('.').collect();
+  segments.iter().enumerate().all(|(index, segment)| {
+    if *segment == WILDCARD {
+      return index == segments.len() - 1;
+    }
+    !segment.is_empty()
+      && segment
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+  })
+}
+
+// Whether `pattern` grants `name`. `name` may itself be a wildcard, in which case the
+// pattern must cover everything the wildcard does.
+pub fn matches(pattern: &str, name: &str) -> bool {
+  if pattern == WILDCARD {
+    return true;
+  }
+  match pattern.strip_suffix(WILDCARD) {
+    Some(prefix) => name.starts_with(prefix) && name.len() > prefix.len(),
+    None => pattern == name,
+  }
+}
+
+pub fn any_matches<'a, I>(patterns: I, name: &str) -> bool
+where
+  I: IntoIterator<Item = &'a str>,
+{
+  patterns.into_iter().any(|pattern| matches(pattern, name))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn lone_wildcard_matches_everything() {
+    assert!(matches("*", "admin.create_user"));
+    assert!(matches("*", "tickets"));
+    assert!(matches("*", "billing.*"));
+  }
+
+  #[test]
+  fn prefix_wildcard_matches_names_below_the_prefix() {
+    assert!(matches("admin.*", "admin.create_user"));
+    assert!(matches("billing.*", "billing.invoices.read"));
+    assert!(matches("billing.*", "billing.invoices.*"));
+  }
+
+  #[test]
+  fn prefix_wildcard_does_not_match_the_prefix_itself_or_siblings() {
+    assert!(!matches("admin.*", "admin"));
+    assert!(!matches("admin.*", "admin."));
+    assert!(!matches("admin.*", "administrator.create_user"));
+    assert!(!matches("billing.invoices.*", "billing.*"));
+  }
+
+  #[test]
+  fn exact_names_match_only_themselves() {
+    assert!(matches("tickets.delete", "tickets.delete"));
+    assert!(!matches("tickets.delete", "tickets.deleted"));
+    assert!(!matches("tickets.delete", "tickets.*"));
+    assert!(!matches("tickets.delete", "*"));
+  }
+
+  #[test]
+  fn any_matches_checks_every_pattern() {
+    assert!(any_matches(["reports.view", "admin.*"], "admin.delete_user"));
+    assert!(!any_matches(["reports.view", "admin.*"], "tickets.view"));
+    assert!(!any_matches(Vec::<&str>::new(), "tickets.view"));
+  }
+
+  #[test]
+  fn validates_names() {
+    assert!(is_valid_name("admin.create_user"));
+    assert!(is_valid_name("billing.invoices.*"));
+    assert!(is_valid_name("*"));
+    assert!(is_valid_name("oauth.token-exchange2"));
+    assert!(!is_valid_name(""));
+    assert!(!is_valid_name("admin..create"));
+    assert!(!is_valid_name("admin.*.create"));
+    assert!(!is_valid_name("Admin.create"));
+    assert!(!is_valid_name("admin.create user"));
+  }
+}
\ No newline at end of file
@@ -0,0 +1,820 @@
+// A small Cedar-like policy language. A policy file holds any number of policies:
+//
+//   @id("ops-may-assign-roles")
+//   permit (principal in "role:ops", action == "admin.create_user_role", resource)
+//   when { context.hour >= 9 && context.hour < 17 };
+//
+//   forbid (principal, action in ["admin.delete_user", "admin.delete_role"], resource is "role")
+//   unless { principal.department == "security" };
+//
+// Scopes: `principal [== "user:<id>" | in "role:<name>"]`, `action [== "name" | in ["name", ..]]`
+// (names may be wildcards such as "admin.*") and `resource [== "type:id" | is "type"]`.
+// Conditions support `&&`, `||`, `!`, `==`, `!=`, `<`, `<=`, `>`, `>=`, `in [..]`, `has`,
+// attribute access and the methods `contains`, `startsWith` and `isInRange` (CIDR).
+//
+// A request is denied when any forbid policy applies, allowed when any permit applies and
+// denied otherwise. A permit whose conditions fail to evaluate (e.g. a missing attribute) does
+// not apply; a forbid that fails to evaluate denies the request.
+use crate::utilities::permission_matcher;
+use ipnet::IpNet;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Bool(bool),
+  Long(i64),
+  String(String),
+  Set(Vec<Value>),
+  Record(HashMap<String, Value>),
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Value::Bool(b) => write!(f, "{}", b),
+      Value::Long(n) => write!(f, "{}", n),
+      Value::String(s) => write!(f, "{:?}", s),
+      Value::Set(_) => write!(f, "[set]"),
+      Value::Record(_) => write!(f, "{{record}}"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyEffect {
+  Permit,
+  Forbid,
+}
+
+#[derive(Debug, Clone)]
+enum PrincipalScope {
+  Any,
+  Eq(String),
+  InRole(String),
+}
+
+#[derive(Debug, Clone)]
+enum ActionScope {
+  Any,
+  Eq(String),
+  In(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+enum ResourceScope {
+  Any,
+  Eq(String, String),
+  Is(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BinOp {
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  In,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+  Literal(Value),
+  Var(String),
+  Set(Vec<Expr>),
+  Attr(Box<Expr>, String),
+  Has(Box<Expr>, String),
+  Method(Box<Expr>, String, Vec<Expr>),
+  Not(Box<Expr>),
+  And(Box<Expr>, Box<Expr>),
+  Or(Box<Expr>, Box<Expr>),
+  Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Policy {
+  pub id: String,
+  pub effect: PolicyEffect,
+  principal: PrincipalScope,
+  action: ActionScope,
+  resource: ResourceScope,
+  // (`when` = true / `unless` = false, condition)
+  conditions: Vec<(bool, Expr)>,
+}
+
+pub struct PolicyRequest {
+  // `principal.id` is `user:<id>`; `principal.roles` is a set of role names.
+  pub principal: HashMap<String, Value>,
+  pub action: String,
+  pub resource: Option<(String, String)>,
+  pub context: HashMap<String, Value>,
+}
+
+#[derive(Debug)]
+pub struct PolicyDecision {
+  pub allowed: bool,
+  pub policy: Option<String>,
+  pub reason: String,
+  pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PolicySet {
+  policies: Vec<Policy>,
+}
+
+impl PolicySet {
+  pub fn new() -> Self {
+    PolicySet::default()
+  }
+
+  // Parses `text` and adds its policies; policies without an `@id` are named `<source>#<n>`.
+  pub fn add_source(&mut self, source: &str, text: &str) -> Result<usize, String> {
+    let policies = parse(source, text)?;
+    if let Some(policy) = policies.iter().find(|p| self.policies.iter().any(|q| q.id == p.id)) {
+      return Err(format!("duplicate policy id '{}'", policy.id));
+    }
+    let count = policies.len();
+    self.policies.extend(policies);
+    Ok(count)
+  }
+
+  pub fn len(&self) -> usize {
+    self.policies.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.policies.is_empty()
+  }
+
+  pub fn is_authorized(&self, request: &PolicyRequest) -> PolicyDecision {
+    let mut errors = Vec::new();
+    let mut permitted_by = None;
+    for policy in &self.policies {
+      match policy.applies(request) {
+        Ok(true) if policy.effect == PolicyEffect::Forbid => {
+          return PolicyDecision {
+            allowed: false,
+            reason: format!("forbidden by policy '{}'", policy.id),
+            policy: Some(policy.id.clone()),
+            errors,
+          };
+        }
+        Ok(true) => {
+          if permitted_by.is_none() {
+            permitted_by = Some(policy.id.clone());
+          }
+        }
+        Ok(false) => {}
+        Err(e) if policy.effect == PolicyEffect::Forbid => {
+          errors.push(format!("{}: {}", policy.id, e));
+          return PolicyDecision {
+            allowed: false,
+            reason: format!("forbid policy '{}' failed to evaluate", policy.id),
+            policy: Some(policy.id.clone()),
+            errors,
+          };
+        }
+        Err(e) => errors.push(format!("{}: {}", policy.id, e)),
+      }
+    }
+    match permitted_by {
+      Some(id) => PolicyDecision {
+        allowed: true,
+        reason: format!("permitted by policy '{}'", id),
+        policy: Some(id),
+        errors,
+      },
+      None => PolicyDecision {
+        allowed: false,
+        reason: "no policy permits the request".into(),
+        policy: None,
+        errors,
+      },
+    }
+  }
+}
+
+impl Policy {
+  fn applies(&self, request: &PolicyRequest) -> Result<bool, String> {
+    let principal_matches = match &self.principal {
+      PrincipalScope::Any => true,
+      PrincipalScope::Eq(id) => request.principal.get("id") == Some(&Value::String(id.clone())),
+      PrincipalScope::InRole(role) => match request.principal.get("roles") {
+        Some(Value::Set(roles)) => roles.contains(&Value::String(role.clone())),
+        _ => false,
+      },
+    };
+    let action_matches = match &self.action {
+      ActionScope::Any => true,
+      ActionScope::Eq(pattern) => permission_matcher::matches(pattern, &request.action),
+      ActionScope::In(patterns) => permission_matcher::any_matches(patterns.iter().map(String::as_str), &request.action),
+    };
+    let resource_matches = match (&self.resource, &request.resource) {
+      (ResourceScope::Any, _) => true,
+      (ResourceScope::Eq(t, id), Some((rt, rid))) => t == rt && id == rid,
+      (ResourceScope::Is(t), Some((rt, _))) => t == rt,
+      (_, None) => false,
+    };
+    if !(principal_matches && action_matches && resource_matches) {
+      return Ok(false);
+    }
+    for (when, condition) in &self.conditions {
+      match eval(condition, request)? {
+        Value::Bool(result) if result == *when => {}
+        Value::Bool(_) => return Ok(false),
+        other => return Err(format!("condition evaluated to {} instead of a boolean", other)),
+      }
+    }
+    Ok(true)
+  }
+}
+
+fn variable(name: &str, request: &PolicyRequest) -> Result<Value, String> {
+  match name {
+    "principal" => Ok(Value::Record(request.principal.clone())),
+    "action" => Ok(Value::Record(HashMap::from([("id".to_string(), Value::String(request.action.clone()))]))),
+    "resource" => match &request.resource {
+      Some((resource_type, resource_id)) => Ok(Value::Record(HashMap::from([
+        ("type".to_string(), Value::String(resource_type.clone())),
+        ("id".to_string(), Value::String(resource_id.clone())),
+      ]))),
+      None => Ok(Value::Record(HashMap::new())),
+    },
+    "context" => Ok(Value::Record(request.context.clone())),
+    other => Err(format!("unknown variable '{}'", other)),
+  }
+}
+
+fn eval(expr: &Expr, request: &PolicyRequest) -> Result<Value, String> {
+  match expr {
+    Expr::Literal(value) => Ok(value.clone()),
+    Expr::Var(name) => variable(name, request),
+    Expr::Set(items) => Ok(Value::Set(items.iter().map(|e| eval(e, request)).collect::<Result<_, _>>()?)),
+    Expr::Attr(target, attr) => match eval(target, request)? {
+      Value::Record(mut record) => record.remove(attr).ok_or_else(|| format!("missing attribute '{}'", attr)),
+      other => Err(format!("cannot read '{}' of {}", attr, other)),
+    },
+    Expr::Has(target, attr) => match eval(target, request)? {
+      Value::Record(record) => Ok(Value::Bool(record.contains_key(attr))),
+      other => Err(format!("cannot test '{}' on {}", attr, other)),
+    },
+    Expr::Not(inner) => Ok(Value::Bool(!as_bool(eval(inner, request)?)?)),
+    Expr::And(left, right) => {
+      if !as_bool(eval(left, request)?)? {
+        return Ok(Value::Bool(false));
+      }
+      Ok(Value::Bool(as_bool(eval(right, request)?)?))
+    }
+    Expr::Or(left, right) => {
+      if as_bool(eval(left, request)?)? {
+        return Ok(Value::Bool(true));
+      }
+      Ok(Value::Bool(as_bool(eval(right, request)?)?))
+    }
+    Expr::Binary(op, left, right) => {
+      let (left, right) = (eval(left, request)?, eval(right, request)?);
+      let result = match op {
+        BinOp::Eq => left == right,
+        BinOp::Ne => left != right,
+        BinOp::In => match right {
+          Value::Set(items) => items.contains(&left),
+          other => return Err(format!("'in' expects a set, found {}", other)),
+        },
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+          let (Value::Long(l), Value::Long(r)) = (&left, &right) else {
+            return Err(format!("cannot compare {} and {}", left, right));
+          };
+          match op {
+            BinOp::Lt => l < r,
+            BinOp::Le => l <= r,
+            BinOp::Gt => l > r,
+            _ => l >= r,
+          }
+        }
+      };
+      Ok(Value::Bool(result))
+    }
+    Expr::Method(target, method, args) => {
+      let target = eval(target, request)?;
+      let args: Vec<Value> = args.iter().map(|a| eval(a, request)).collect::<Result<_, _>>()?;
+      call_method(target, method, args)
+    }
+  }
+}
+
+fn call_method(target: Value, method: &str, args: Vec<Value>) -> Result<Value, String> {
+  match (method, target, args.as_slice()) {
+    ("contains", Value::Set(items), [arg]) => Ok(Value::Bool(items.contains(arg))),
+    ("contains", Value::String(s), [Value::String(arg)]) => Ok(Value::Bool(s.contains(arg.as_str()))),
+    ("startsWith", Value::String(s), [Value::String(prefix)]) => Ok(Value::Bool(s.starts_with(prefix.as_str()))),
+    ("isInRange", Value::String(ip), [Value::String(range)]) => {
+      let ip: IpAddr = ip.parse().map_err(|_| format!("invalid IP address {:?}", ip))?;
+      let range: IpNet = range.parse().map_err(|_| format!("invalid IP range {:?}", range))?;
+      Ok(Value::Bool(range.contains(&ip)))
+    }
+    (method, target, args) => Err(format!("cannot call {}() on {} with {} argument(s)", method, target, args.len())),
+  }
+}
+
+fn as_bool(value: Value) -> Result<bool, String> {
+  match value {
+    Value::Bool(b) => Ok(b),
+    other => Err(format!("expected a boolean, found {}", other)),
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Ident(String),
+  Str(String),
+  Long(i64),
+  Punct(&'static str),
+}
+
+const PUNCTUATION: [&str; 19] = [
+  "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")", "{", "}", "[", "]", ",", ";", ".", "@",
+];
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+  let mut tokens = Vec::new();
+  let chars: Vec<char> = text.chars().collect();
+  let (mut i, mut line) = (0, 1);
+  while i < chars.len() {
+    let c = chars[i];
+    if c == '\n' {
+      line += 1;
+      i += 1;
+    } else if c.is_whitespace() {
+      i += 1;
+    } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+      while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+      }
+    } else if c == '"' {
+      let mut value = String::new();
+      i += 1;
+      loop {
+        match chars.get(i) {
+          Some('"') => break,
+          Some('\\') => {
+            value.push(*chars.get(i + 1).ok_or_else(|| format!("line {}: unterminated string", line))?);
+            i += 2;
+          }
+          Some('\n') | None => return Err(format!("line {}: unterminated string", line)),
+          Some(ch) => {
+            value.push(*ch);
+            i += 1;
+          }
+        }
+      }
+      i += 1;
+      tokens.push((Token::Str(value), line));
+    } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+      let start = i;
+      i += 1;
+      while i < chars.len() && chars[i].is_ascii_digit() {
+        i += 1;
+      }
+      let literal: String = chars[start..i].iter().collect();
+      let value = literal.parse().map_err(|_| format!("line {}: invalid number {}", line, literal))?;
+      tokens.push((Token::Long(value), line));
+    } else if c.is_ascii_alphabetic() || c == '_' {
+      let start = i;
+      while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+        i += 1;
+      }
+      tokens.push((Token::Ident(chars[start..i].iter().collect()), line));
+    } else {
+      let rest: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+      let punct = PUNCTUATION
+        .iter()
+        .find(|p| rest.starts_with(**p))
+        .ok_or_else(|| format!("line {}: unexpected character '{}'", line, c))?;
+      i += punct.len();
+      tokens.push((Token::Punct(punct), line));
+    }
+  }
+  Ok(tokens)
+}
+
+// Bounds how deeply conditions nest, counting parentheses, sets, method calls, `!` and chained
+// `&&`, `||` and `.`, so that neither parsing nor evaluation can exhaust the stack.
+const MAX_NESTING: usize = 64;
+
+struct Parser {
+  tokens: Vec<(Token, usize)>,
+  position: usize,
+  depth: usize,
+}
+
+fn parse(source: &str, text: &str) -> Result<Vec<Policy>, String> {
+  let mut parser = Parser { tokens: tokenize(text)?, position: 0, depth: 0 };
+  let mut policies = Vec::new();
+  while parser.peek().is_some() {
+    let policy = parser.policy(&format!("{}#{}", source, policies.len()))?;
+    if policies.iter().any(|p: &Policy| p.id == policy.id) {
+      return Err(format!("duplicate policy id '{}'", policy.id));
+    }
+    policies.push(policy);
+  }
+  Ok(policies)
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position).map(|(t, _)| t)
+  }
+
+  fn line(&self) -> usize {
+    self.tokens
+      .get(self.position)
+      .or_else(|| self.tokens.last())
+      .map(|(_, line)| *line)
+      .unwrap_or(1)
+  }
+
+  fn error<T>(&self, message: &str) -> Result<T, String> {
+    Err(format!("line {}: {}", self.line(), message))
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.position).map(|(t, _)| t.clone());
+    self.position += 1;
+    token
+  }
+
+  fn eat_punct(&mut self, punct: &str) -> bool {
+    if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+      self.position += 1;
+      return true;
+    }
+    false
+  }
+
+  fn eat_ident(&mut self, ident: &str) -> bool {
+    if matches!(self.peek(), Some(Token::Ident(i)) if i == ident) {
+      self.position += 1;
+      return true;
+    }
+    false
+  }
+
+  fn enter(&mut self) -> Result<(), String> {
+    self.depth += 1;
+    if self.depth > MAX_NESTING {
+      return self.error(&format!("expression nested deeper than {} levels", MAX_NESTING));
+    }
+    Ok(())
+  }
+
+  fn expect_punct(&mut self, punct: &str) -> Result<(), String> {
+    if self.eat_punct(punct) { Ok(()) } else { self.error(&format!("expected '{}'", punct)) }
+  }
+
+  fn expect_ident(&mut self, ident: &str) -> Result<(), String> {
+    if self.eat_ident(ident) { Ok(()) } else { self.error(&format!("expected '{}'", ident)) }
+  }
+
+  fn string(&mut self) -> Result<String, String> {
+    match self.next() {
+      Some(Token::Str(s)) => Ok(s),
+      _ => {
+        self.position -= 1;
+        self.error("expected a string")
+      }
+    }
+  }
+
+  fn ident(&mut self) -> Result<String, String> {
+    match self.next() {
+      Some(Token::Ident(s)) => Ok(s),
+      _ => {
+        self.position -= 1;
+        self.error("expected an identifier")
+      }
+    }
+  }
+
+  fn policy(&mut self, default_id: &str) -> Result<Policy, String> {
+    let mut id = default_id.to_string();
+    while self.eat_punct("@") {
+      let annotation = self.ident()?;
+      self.expect_punct("(")?;
+      let value = self.string()?;
+      self.expect_punct(")")?;
+      if annotation == "id" {
+        id = value;
+      }
+    }
+    let effect = if self.eat_ident("permit") {
+      PolicyEffect::Permit
+    } else if self.eat_ident("forbid") {
+      PolicyEffect::Forbid
+    } else {
+      return self.error("expected 'permit' or 'forbid'");
+    };
+    self.expect_punct("(")?;
+    self.expect_ident("principal")?;
+    let principal = if self.eat_punct("==") {
+      PrincipalScope::Eq(self.string()?)
+    } else if self.eat_ident("in") {
+      let group = self.string()?;
+      match group.strip_prefix("role:") {
+        Some(role) => PrincipalScope::InRole(role.to_string()),
+        None => return self.error("principal scope 'in' expects \"role:<name>\""),
+      }
+    } else {
+      PrincipalScope::Any
+    };
+    self.expect_punct(",")?;
+    self.expect_ident("action")?;
+    let action = if self.eat_punct("==") {
+      ActionScope::Eq(self.string()?)
+    } else if self.eat_ident("in") {
+      self.expect_punct("[")?;
+      let mut actions = Vec::new();
+      while !self.eat_punct("]") {
+        actions.push(self.string()?);
+        if !self.eat_punct(",") {
+          self.expect_punct("]")?;
+          break;
+        }
+      }
+      ActionScope::In(actions)
+    } else {
+      ActionScope::Any
+    };
+    self.expect_punct(",")?;
+    self.expect_ident("resource")?;
+    let resource = if self.eat_punct("==") {
+      let resource = self.string()?;
+      match resource.split_once(':') {
+        Some((resource_type, resource_id)) => ResourceScope::Eq(resource_type.to_string(), resource_id.to_string()),
+        None => return self.error("resource scope '==' expects \"<type>:<id>\""),
+      }
+    } else if self.eat_ident("is") {
+      ResourceScope::Is(self.string()?)
+    } else {
+      ResourceScope::Any
+    };
+    self.expect_punct(")")?;
+    let mut conditions = Vec::new();
+    loop {
+      let when = if self.eat_ident("when") {
+        true
+      } else if self.eat_ident("unless") {
+        false
+      } else {
+        break;
+      };
+      self.expect_punct("{")?;
+      conditions.push((when, self.or()?));
+      self.expect_punct("}")?;
+    }
+    self.expect_punct(";")?;
+    Ok(Policy { id, effect, principal, action, resource, conditions })
+  }
+
+  // Parses with `parse` one level deeper. Chains enter a level per link and return to the
+  // depth they started at once complete.
+  fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+    let depth = self.depth;
+    self.enter()?;
+    let result = parse(self)?;
+    self.depth = depth;
+    Ok(result)
+  }
+
+  fn or(&mut self) -> Result<Expr, String> {
+    let depth = self.depth;
+    let mut expr = self.and()?;
+    while self.eat_punct("||") {
+      self.enter()?;
+      expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+    }
+    self.depth = depth;
+    Ok(expr)
+  }
+
+  fn and(&mut self) -> Result<Expr, String> {
+    let depth = self.depth;
+    let mut expr = self.unary()?;
+    while self.eat_punct("&&") {
+      self.enter()?;
+      expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+    }
+    self.depth = depth;
+    Ok(expr)
+  }
+
+  fn unary(&mut self) -> Result<Expr, String> {
+    if self.eat_punct("!") {
+      return self.nested(|parser| Ok(Expr::Not(Box::new(parser.unary()?))));
+    }
+    self.comparison()
+  }
+
+  fn comparison(&mut self) -> Result<Expr, String> {
+    let left = self.member()?;
+    if self.eat_ident("has") {
+      return Ok(Expr::Has(Box::new(left), self.ident()?));
+    }
+    let op = match self.peek() {
+      Some(Token::Punct("==")) => BinOp::Eq,
+      Some(Token::Punct("!=")) => BinOp::Ne,
+      Some(Token::Punct("<")) => BinOp::Lt,
+      Some(Token::Punct("<=")) => BinOp::Le,
+      Some(Token::Punct(">")) => BinOp::Gt,
+      Some(Token::Punct(">=")) => BinOp::Ge,
+      Some(Token::Ident(i)) if i == "in" => BinOp::In,
+      _ => return Ok(left),
+    };
+    self.position += 1;
+    Ok(Expr::Binary(op, Box::new(left), Box::new(self.member()?)))
+  }
+
+  fn member(&mut self) -> Result<Expr, String> {
+    let depth = self.depth;
+    let mut expr = self.primary()?;
+    while self.eat_punct(".") {
+      self.enter()?;
+      let name = self.ident()?;
+      if self.eat_punct("(") {
+        let mut args = Vec::new();
+        while !self.eat_punct(")") {
+          args.push(self.nested(Self::or)?);
+          if !self.eat_punct(",") {
+            self.expect_punct(")")?;
+            break;
+          }
+        }
+        expr = Expr::Method(Box::new(expr), name, args);
+      } else {
+        expr = Expr::Attr(Box::new(expr), name);
+      }
+    }
+    self.depth = depth;
+    Ok(expr)
+  }
+
+  fn primary(&mut self) -> Result<Expr, String> {
+    match self.next() {
+      Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+      Some(Token::Long(n)) => Ok(Expr::Literal(Value::Long(n))),
+      Some(Token::Ident(i)) if i == "true" => Ok(Expr::Literal(Value::Bool(true))),
+      Some(Token::Ident(i)) if i == "false" => Ok(Expr::Literal(Value::Bool(false))),
+      Some(Token::Ident(i)) if ["principal", "action", "resource", "context"].contains(&i.as_str()) => Ok(Expr::Var(i)),
+      Some(Token::Punct("(")) => {
+        let expr = self.nested(Self::or)?;
+        self.expect_punct(")")?;
+        Ok(expr)
+      }
+      Some(Token::Punct("[")) => {
+        let mut items = Vec::new();
+        while !self.eat_punct("]") {
+          items.push(self.nested(Self::or)?);
+          if !self.eat_punct(",") {
+            self.expect_punct("]")?;
+            break;
+          }
+        }
+        Ok(Expr::Set(items))
+      }
+      _ => {
+        self.position -= 1;
+        self.error("expected an expression")
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn policies(text: &str) -> PolicySet {
+    let mut set = PolicySet::new();
+    set.add_source("test", text).unwrap();
+    set
+  }
+
+  fn request(action: &str, resource: Option<(&str, &str)>, context: &[(&str, Value)]) -> PolicyRequest {
+    PolicyRequest {
+      principal: HashMap::from([
+        ("id".to_string(), Value::String("user:42".into())),
+        ("roles".to_string(), Value::Set(vec![Value::String("ops".into())])),
+        ("department".to_string(), Value::String("support".into())),
+      ]),
+      action: action.to_string(),
+      resource: resource.map(|(t, id)| (t.to_string(), id.to_string())),
+      context: context.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+    }
+  }
+
+  #[test]
+  fn parses_scopes_and_annotations() {
+    let set = policies(r#"
+      @id("ops-assign")
+      permit (principal in "role:ops", action == "admin.*", resource is "role");
+      forbid (principal == "user:7", action in ["a.b", "c.d"], resource == "role:1") when { true };
+      permit (principal, action, resource);
+    "#);
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.policies[0].id, "ops-assign");
+    assert_eq!(set.policies[1].effect, PolicyEffect::Forbid);
+    assert_eq!(set.policies[2].id, "test#2");
+  }
+
+  #[test]
+  fn rejects_malformed_policies() {
+    let mut set = PolicySet::new();
+    assert!(set.add_source("test", "permit (principal, action, resource)").unwrap_err().contains("expected ';'"));
+    assert!(set.add_source("test", "allow (principal, action, resource);").is_err());
+    assert!(set.add_source("test", r#"permit (principal in "group:x", action, resource);"#).is_err());
+    assert!(set.add_source("test", "permit (principal, action, resource) when { context.x == };").is_err());
+    assert!(set.add_source("test", r#"permit (principal, action, resource) when { "unterminated };"#).is_err());
+    let duplicate = r#"@id("a") permit (principal, action, resource); @id("a") forbid (principal, action, resource);"#;
+    assert!(set.add_source("test", duplicate).unwrap_err().contains("duplicate policy id 'a'"));
+  }
+
+  #[test]
+  fn caps_expression_nesting() {
+    let wrap = |open: &str, close: &str, n: usize| {
+      format!("permit (principal, action, resource) when {{ {}true{} }};", open.repeat(n), close.repeat(n))
+    };
+    let mut set = PolicySet::new();
+    assert!(set.add_source("parens", &wrap("(", ")", 20)).is_ok());
+    assert!(set.add_source("deep_parens", &wrap("(", ")", 10_000)).unwrap_err().contains("nested deeper"));
+    assert!(set.add_source("deep_sets", &wrap("[", "]", 10_000)).unwrap_err().contains("nested deeper"));
+    assert!(set.add_source("deep_nots", &wrap("!", "", 10_000)).unwrap_err().contains("nested deeper"));
+    let chain = format!("permit (principal, action, resource) when {{ true{} }};", " && true".repeat(10_000));
+    assert!(set.add_source("long_chain", &chain).unwrap_err().contains("nested deeper"));
+  }
+
+  #[test]
+  fn permits_only_matching_scopes() {
+    let set = policies(r#"permit (principal in "role:ops", action == "tickets.*", resource is "ticket");"#);
+    assert!(set.is_authorized(&request("tickets.update", Some(("ticket", "1")), &[])).allowed);
+    assert!(!set.is_authorized(&request("tickets.update", Some(("project", "1")), &[])).allowed);
+    assert!(!set.is_authorized(&request("tickets.update", None, &[])).allowed);
+    let decision = set.is_authorized(&request("billing.read", Some(("ticket", "1")), &[]));
+    assert!(!decision.allowed);
+    assert_eq!(decision.reason, "no policy permits the request");
+  }
+
+  #[test]
+  fn forbid_overrides_permit() {
+    let set = policies(r#"
+      permit (principal, action, resource);
+      @id("no-deletes")
+      forbid (principal, action == "tickets.delete", resource);
+    "#);
+    assert!(set.is_authorized(&request("tickets.update", None, &[])).allowed);
+    let decision = set.is_authorized(&request("tickets.delete", None, &[]));
+    assert!(!decision.allowed);
+    assert_eq!(decision.policy.as_deref(), Some("no-deletes"));
+  }
+
+  #[test]
+  fn evaluates_when_and_unless_conditions() {
+    let set = policies(r#"
+      permit (principal, action, resource)
+      when { context.hour >= 9 && context.hour < 17 && context.ip.isInRange("10.0.0.0/8") }
+      unless { principal.department in ["finance", "legal"] || !(principal.roles.contains("ops")) };
+    "#);
+    let context = |hour: i64, ip: &str| [("hour", Value::Long(hour)), ("ip", Value::String(ip.into()))];
+    assert!(set.is_authorized(&request("reports.view", None, &context(10, "10.1.2.3"))).allowed);
+    assert!(!set.is_authorized(&request("reports.view", None, &context(18, "10.1.2.3"))).allowed);
+    assert!(!set.is_authorized(&request("reports.view", None, &context(10, "192.168.0.1"))).allowed);
+  }
+
+  #[test]
+  fn permit_failing_to_evaluate_does_not_apply() {
+    let set = policies(r#"@id("needs-hour") permit (principal, action, resource) when { context.hour < 17 };"#);
+    let decision = set.is_authorized(&request("reports.view", None, &[]));
+    assert!(!decision.allowed);
+    assert_eq!(decision.errors, vec!["needs-hour: missing attribute 'hour'".to_string()]);
+  }
+
+  #[test]
+  fn forbid_failing_to_evaluate_denies() {
+    let set = policies(r#"
+      permit (principal, action, resource);
+      @id("office-hours")
+      forbid (principal, action, resource) unless { context.hour >= 9 };
+      @id("bad-type")
+      forbid (principal, action == "tickets.delete", resource) when { principal.department };
+    "#);
+    assert!(set.is_authorized(&request("reports.view", None, &[("hour", Value::Long(10))])).allowed);
+    let decision = set.is_authorized(&request("reports.view", None, &[]));
+    assert!(!decision.allowed);
+    assert_eq!(decision.reason, "forbid policy 'office-hours' failed to evaluate");
+    let decision = set.is_authorized(&request("tickets.delete", None, &[("hour", Value::Long(10))]));
+    assert!(!decision.allowed);
+    assert_eq!(decision.policy.as_deref(), Some("bad-type"));
+  }
+}
\ No newline at end of file
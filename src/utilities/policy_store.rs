@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
+use crate::utilities::policy::PolicySet;
+use log::debug;
+
+// The compiled policy set (files plus enabled database policies), shared by every worker.
+static STORE: LazyLock<RwLock<Option<StoreEntry>>> = LazyLock::new(|| RwLock::new(None));
+// Bumped on every invalidation so a load that raced with a change is not kept.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+struct StoreEntry {
+  policies: Arc<PolicySet>,
+  loaded_at: Instant,
+}
+
+pub fn get(ttl: Duration) -> Option<Arc<PolicySet>> {
+  let store = STORE.read().unwrap_or_else(|e| e.into_inner());
+  store
+    .as_ref()
+    .filter(|entry| entry.loaded_at.elapsed() < ttl)
+    .map(|entry| Arc::clone(&entry.policies))
+}
+
+pub fn generation() -> u64 {
+  GENERATION.load(Ordering::SeqCst)
+}
+
+// `generation` must be read before loading the policies.
+pub fn insert(policies: PolicySet, generation: u64) -> Arc<PolicySet> {
+  let policies = Arc::new(policies);
+  let mut store = STORE.write().unwrap_or_else(|e| e.into_inner());
+  if GENERATION.load(Ordering::SeqCst) != generation {
+    debug!("Policies changed while loading; not caching");
+    return policies;
+  }
+  *store = Some(StoreEntry {
+    policies: Arc::clone(&policies),
+    loaded_at: Instant::now(),
+  });
+  policies
+}
+
+pub fn invalidate() {
+  debug!("Invalidating cached policies");
+  let mut store = STORE.write().unwrap_or_else(|e| e.into_inner());
+  GENERATION.fetch_add(1, Ordering::SeqCst);
+  *store = None;
+}
\ No newline at end of file
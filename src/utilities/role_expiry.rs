@@ -0,0 +1,30 @@
+use crate::config::Config;
+use crate::database::PgPool;
+use crate::handlers::elevation::ElevationHandler;
+use crate::handlers::user_role::UserRoleHandler;
+use chrono::Utc;
+use std::thread;
+use std::time::Duration;
+use log::{error, info};
+
+// Periodically archives expired role assignments, announces newly valid ones and closes ended
+// elevations. Safe to run on every instance: each expired row is only updated once.
+pub fn spawn_sweeper(pool: PgPool, config: &Config) {
+  let interval = Duration::from_secs(config.auth.role_expiry_sweep_interval_seconds);
+  let (auth, elevation) = (config.auth.clone(), config.elevation.clone());
+  info!("Sweeping time-bound role assignments every {:?}", interval);
+  thread::spawn(move || {
+    let mut since = Utc::now();
+    loop {
+      let now = Utc::now();
+      match UserRoleHandler::new(&pool).sweep(since, now) {
+        Ok(()) => since = now,
+        Err(e) => error!("Failed to sweep role assignments: {}", e),
+      }
+      if let Err(e) = ElevationHandler::new(&pool, auth.clone(), elevation.clone()).expire(now) {
+        error!("Failed to expire elevation requests: {}", e);
+      }
+      thread::sleep(interval);
+    }
+  });
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+use crate::config::Config;
+use crate::database::PgPool;
+use crate::handlers::webhook::WebhookHandler;
+use crate::models::webhook::DueDelivery;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::thread;
+use std::time::Duration;
+use log::{error, info};
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Id";
+
+// Retry delays double per attempt up to a day.
+const MAX_RETRY_DELAY_SECONDS: i64 = 86_400;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Receivers recompute HMAC-SHA256 over "{timestamp}.{body}" with the webhook secret and
+// compare it to the signature header; the timestamp lets them reject replays.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+  let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(format!("{}.{}", timestamp, body).as_bytes());
+  format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+pub fn retry_delay(base_seconds: i64, attempts: i32) -> chrono::Duration {
+  let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+  chrono::Duration::seconds(base_seconds.saturating_mul(1 << exponent).min(MAX_RETRY_DELAY_SECONDS))
+}
+
+pub fn agent(timeout_seconds: u64) -> ureq::Agent {
+  ureq::AgentBuilder::new().timeout(Duration::from_secs(timeout_seconds)).redirects(0).build()
+}
+
+// Posts the event and returns the response status. Anything but a 2xx is an error carrying
+// the status, if the endpoint answered at all.
+pub fn deliver(agent: &ureq::Agent, due: &DueDelivery) -> Result<u16, (Option<u16>, String)> {
+  let body = serde_json::json!({
+    "id": due.event.id,
+    "occurred_at": due.event.created_at,
+    "event": due.event.payload,
+  })
+  .to_string();
+  let timestamp = chrono::Utc::now().timestamp();
+  let response = agent
+    .post(&due.webhook.url)
+    .set("Content-Type", "application/json")
+    .set(DELIVERY_HEADER, &due.delivery.id.to_string())
+    .set(TIMESTAMP_HEADER, &timestamp.to_string())
+    .set(SIGNATURE_HEADER, &sign(&due.webhook.secret, timestamp, &body))
+    .send_string(&body);
+  match response {
+    Ok(response) if (200..300).contains(&response.status()) => Ok(response.status()),
+    Ok(response) => Err((Some(response.status()), format!("Unexpected status {}", response.status()))),
+    Err(ureq::Error::Status(status, _)) => Err((Some(status), format!("Unexpected status {}", status))),
+    Err(ureq::Error::Transport(e)) => Err((None, e.to_string())),
+  }
+}
+
+// Periodically fans outbox events out to webhooks and sends due deliveries. Safe to run on
+// every instance: events and deliveries are claimed with SKIP LOCKED.
+pub fn spawn_dispatcher(pool: PgPool, config: &Config) {
+  let settings = config.webhooks.clone();
+  let interval = Duration::from_secs(settings.dispatch_interval_seconds);
+  info!("Dispatching webhook deliveries every {:?}", interval);
+  thread::spawn(move || {
+    let agent = agent(settings.timeout_seconds);
+    loop {
+      if let Err(e) = WebhookHandler::new(&pool).dispatch(&settings, &agent) {
+        error!("Failed to dispatch webhook deliveries: {}", e);
+      }
+      thread::sleep(interval);
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sign_matches_a_known_hmac() {
+    // openssl dgst -sha256 -hmac whsec_test over '1700000000.{"id":"evt_1","event":"role.deleted"}'
+    assert_eq!(
+      sign("whsec_test", 1_700_000_000, r#"{"id":"evt_1","event":"role.deleted"}"#),
+      "sha256=0673c1eb6d5fc54971225b04b0ea887dd128d8f8533f5be5c6438269b66ed60b"
+    );
+  }
+
+  #[test]
+  fn sign_covers_the_timestamp() {
+    assert_ne!(sign("whsec_test", 1_700_000_000, "{}"), sign("whsec_test", 1_700_000_001, "{}"));
+  }
+
+  #[test]
+  fn retry_delay_doubles_per_attempt() {
+    let delays: Vec<i64> = (1..=5).map(|attempts| retry_delay(30, attempts).num_seconds()).collect();
+    assert_eq!(delays, vec![30, 60, 120, 240, 480]);
+    assert_eq!(retry_delay(30, 0).num_seconds(), 30);
+  }
+
+  #[test]
+  fn retry_delay_stops_at_the_maximum() {
+    assert_eq!(retry_delay(30, 12).num_seconds(), 61_440);
+    assert_eq!(retry_delay(30, 13).num_seconds(), MAX_RETRY_DELAY_SECONDS);
+    assert_eq!(retry_delay(30, i32::MAX).num_seconds(), MAX_RETRY_DELAY_SECONDS);
+    assert_eq!(retry_delay(i64::MAX, 2).num_seconds(), MAX_RETRY_DELAY_SECONDS);
+  }
+}
\ No newline at end of file